@@ -0,0 +1,40 @@
+use crate::client::Client;
+use crate::emby::types::{ActivityLogEntry, ActivityLogResponse};
+use crate::error::Result;
+
+pub struct ActivityLog<'a> {
+    pub(crate) client: &'a Client,
+}
+
+/// Options for [`ActivityLog::entries`].
+#[derive(Debug, Default)]
+pub struct ActivityLogOptions {
+    pub limit: Option<u32>,
+    pub start_index: Option<u32>,
+    /// Only entries on or after this ISO 8601 timestamp
+    pub min_date: Option<String>,
+}
+
+impl ActivityLog<'_> {
+    /// `GET /System/ActivityLog/Entries`, newest first.
+    pub fn entries(&self, options: &ActivityLogOptions) -> Result<Vec<ActivityLogEntry>> {
+        let limit = options.limit.map(|l| l.to_string());
+        let start_index = options.start_index.map(|i| i.to_string());
+        let mut query = Vec::new();
+
+        if let Some(limit) = &limit {
+            query.push(("Limit", limit.as_str()));
+        }
+        if let Some(start_index) = &start_index {
+            query.push(("StartIndex", start_index.as_str()));
+        }
+        if let Some(min_date) = &options.min_date {
+            query.push(("MinDate", min_date.as_str()));
+        }
+
+        let response: ActivityLogResponse = self
+            .client
+            .get_with_query("/System/ActivityLog/Entries", &query)?;
+        Ok(response.items.unwrap_or_default())
+    }
+}
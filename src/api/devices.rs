@@ -0,0 +1,29 @@
+use crate::client::Client;
+use crate::emby::types::{Device, DevicesResponse};
+use crate::error::Result;
+
+pub struct Devices<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl Devices<'_> {
+    /// `GET /Devices`
+    pub fn list(&self) -> Result<Vec<Device>> {
+        let response: DevicesResponse = self.client.get("/Devices")?;
+        Ok(response.items.unwrap_or_default())
+    }
+
+    /// `DELETE /Devices?Id={id}`
+    pub fn delete(&self, id: &str) -> Result<()> {
+        self.client.delete_with_query("/Devices", &[("Id", id)])
+    }
+
+    /// `POST /Devices/Options?Id={id}`
+    pub fn rename(&self, id: &str, name: &str) -> Result<()> {
+        self.client.post_with_query(
+            "/Devices/Options",
+            &[("Id", id)],
+            Some(&serde_json::json!({"CustomName": name})),
+        )
+    }
+}
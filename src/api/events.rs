@@ -0,0 +1,235 @@
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tungstenite::Message;
+
+use crate::client::{self, Client, WebSocket, WebSocketConnector};
+use crate::emby::types::ServerEvent;
+use crate::error::{Error, Result};
+
+/// How long a read waits before checking whether the stream was dropped or
+/// a keep-alive is due.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time between keep-alives until the server asks for another with
+/// `ForceKeepAlive`.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// How often the server sends subscribed updates, in milliseconds.
+const UPDATE_INTERVAL_MS: u32 = 1500;
+
+pub struct Events<'a> {
+    pub(crate) client: &'a Client,
+}
+
+/// Updates to ask the server for with [`Events::subscribe`]. Library, user
+/// data and playback events are sent without subscribing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subscription {
+    /// `Sessions` messages with every session
+    Sessions,
+    /// `ScheduledTasksInfo` messages with every scheduled task
+    ScheduledTasks,
+    /// `ActivityLogEntry` messages with new activity log entries
+    ActivityLog,
+}
+
+impl Subscription {
+    pub const ALL: [Subscription; 3] = [Self::Sessions, Self::ScheduledTasks, Self::ActivityLog];
+
+    fn start_message(self) -> String {
+        let message_type = match self {
+            Self::Sessions => "SessionsStart",
+            Self::ScheduledTasks => "ScheduledTasksInfoStart",
+            Self::ActivityLog => "ActivityLogEntryStart",
+        };
+        serde_json::json!({
+            "MessageType": message_type,
+            "Data": format!("0,{UPDATE_INTERVAL_MS}"),
+        })
+        .to_string()
+    }
+}
+
+impl Events<'_> {
+    /// `GET /embywebsocket`, upgraded to a WebSocket.
+    ///
+    /// Events are read on a background thread, which reconnects and
+    /// subscribes again when the connection drops, unless retries are
+    /// turned off.
+    pub fn subscribe(&self, subscriptions: &[Subscription]) -> Result<EventStream> {
+        let connector = self.client.websocket("/embywebsocket");
+        let socket = open(&connector, subscriptions)?;
+
+        let (tx, rx) = mpsc::channel();
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader = Reader {
+            connector,
+            subscriptions: subscriptions.to_vec(),
+            tx,
+            closed: Arc::clone(&closed),
+        };
+        thread::spawn(move || reader.run(socket));
+
+        Ok(EventStream { rx, closed })
+    }
+}
+
+/// Events from [`Events::subscribe`], ending with an error once the
+/// connection is lost for good. Dropping it stops the background thread.
+pub struct EventStream {
+    rx: Receiver<Result<ServerEvent>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl EventStream {
+    /// Wait up to `timeout` for the next event, `None` if none arrived.
+    pub fn next_timeout(&self, timeout: Duration) -> Option<Result<ServerEvent>> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(closed())),
+        }
+    }
+}
+
+impl Iterator for EventStream {
+    type Item = Result<ServerEvent>;
+
+    /// Block until the next event.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+fn open(connector: &WebSocketConnector, subscriptions: &[Subscription]) -> Result<WebSocket> {
+    let mut socket = connector.connect(READ_TIMEOUT)?;
+    for subscription in subscriptions {
+        socket.send(Message::text(subscription.start_message()))?;
+    }
+    Ok(socket)
+}
+
+fn closed() -> Error {
+    Error::from(tungstenite::Error::ConnectionClosed)
+}
+
+struct Reader {
+    connector: WebSocketConnector,
+    subscriptions: Vec<Subscription>,
+    tx: Sender<Result<ServerEvent>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Reader {
+    fn run(self, mut socket: WebSocket) {
+        loop {
+            let error = match self.forward(&mut socket) {
+                Ok(()) => return,
+                Err(e) => e,
+            };
+
+            let Some(reconnected) = self.reconnect() else {
+                let _ = self.tx.send(Err(error));
+                return;
+            };
+            socket = reconnected;
+        }
+    }
+
+    /// Send events to the stream until it's dropped, or fail when the
+    /// connection does.
+    fn forward(&self, socket: &mut WebSocket) -> Result<()> {
+        let mut keep_alive = KEEP_ALIVE;
+        let mut last_sent = Instant::now();
+
+        while !self.closed.load(Ordering::Relaxed) {
+            if last_sent.elapsed() >= keep_alive {
+                socket.send(Message::text(r#"{"MessageType":"KeepAlive"}"#))?;
+                last_sent = Instant::now();
+            }
+
+            let text = match socket.read() {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return Err(closed()),
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(e))
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // Skip messages that aren't events rather than dropping the connection
+            let Ok(event) = serde_json::from_str::<ServerEvent>(text.as_str()) else {
+                continue;
+            };
+
+            match event.message_type.as_str() {
+                // The server closes connections that are quiet for longer than this
+                "ForceKeepAlive" => {
+                    if let Some(seconds) = event.data.as_u64() {
+                        keep_alive = Duration::from_secs(seconds / 2).max(READ_TIMEOUT);
+                    }
+                }
+                "KeepAlive" => {}
+                _ => {
+                    if self.tx.send(Ok(event)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep trying to reconnect while the stream is in use, e.g. while the
+    /// server restarts.
+    fn reconnect(&self) -> Option<WebSocket> {
+        if !self.connector.reconnect {
+            return None;
+        }
+
+        let mut attempt = 0;
+        while !self.closed.load(Ordering::Relaxed) {
+            thread::sleep(client::backoff(attempt));
+            if let Ok(socket) = open(&self.connector, &self.subscriptions) {
+                return Some(socket);
+            }
+            attempt = attempt.saturating_add(1);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_ask_for_periodic_updates() {
+        assert_eq!(
+            Subscription::Sessions.start_message(),
+            r#"{"MessageType":"SessionsStart","Data":"0,1500"}"#
+        );
+        assert_eq!(
+            Subscription::ScheduledTasks.start_message(),
+            r#"{"MessageType":"ScheduledTasksInfoStart","Data":"0,1500"}"#
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::api::Pages;
+use crate::client::Client;
+use crate::emby::types::{BaseItemDto, QueryResultBaseItemDto};
+use crate::error::Result;
+
+pub struct Items<'a> {
+    pub(crate) client: &'a Client,
+}
+
+/// Options for [`Items::latest`].
+#[derive(Debug, Default)]
+pub struct LatestOptions {
+    pub limit: Option<u32>,
+    /// Comma-separated item types, e.g. `Movie,Series`
+    pub include_item_types: Option<String>,
+    /// Comma-separated extra fields to return
+    pub fields: Option<String>,
+    /// Group episodes by series and tracks by album
+    pub group_items: bool,
+}
+
+/// Options for [`Items::search`]. List filters match items with any of the
+/// given values.
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    /// Free-text search, or empty to match every item
+    pub search_term: String,
+    pub limit: Option<u32>,
+    pub start_index: Option<u32>,
+    /// Comma-separated extra fields to return
+    pub fields: Option<String>,
+    /// Comma-separated item types, e.g. `Movie,Series`
+    pub include_item_types: Option<String>,
+    /// Comma-separated item types to leave out
+    pub exclude_item_types: Option<String>,
+    pub genres: Vec<String>,
+    pub years: Vec<u32>,
+    /// Parental ratings, e.g. `PG-13`
+    pub official_ratings: Vec<String>,
+    pub studios: Vec<String>,
+    /// Only items under this folder, e.g. a library's item ID
+    pub parent_id: Option<String>,
+    /// User whose play state `is_played` refers to
+    pub user_id: Option<String>,
+    pub is_played: Option<bool>,
+    /// Only items with this person in the cast or crew
+    pub person: Option<String>,
+    /// Comma-separated roles for `person`, e.g. `Actor,Director`
+    pub person_types: Option<String>,
+    pub is_4k: Option<bool>,
+    pub is_hd: Option<bool>,
+    /// Comma-separated sort fields, e.g. `SortName` or `DateCreated`
+    pub sort_by: Option<String>,
+    /// `Ascending` or `Descending`
+    pub sort_order: Option<String>,
+}
+
+/// Options for [`Items::refresh`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct RefreshOptions {
+    pub recursive: bool,
+    pub metadata_refresh_mode: String,
+    pub image_refresh_mode: String,
+    pub replace_all_metadata: bool,
+    pub replace_all_images: bool,
+}
+
+impl Default for RefreshOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            metadata_refresh_mode: "Default".to_string(),
+            image_refresh_mode: "Default".to_string(),
+            replace_all_metadata: false,
+            replace_all_images: false,
+        }
+    }
+}
+
+impl<'a> Items<'a> {
+    /// `GET /Users/{user_id}/Items/Latest`
+    pub fn latest(&self, user_id: &str, options: &LatestOptions) -> Result<Vec<BaseItemDto>> {
+        let limit = options.limit.map(|l| l.to_string());
+        let mut query = vec![(
+            "GroupItems",
+            if options.group_items { "true" } else { "false" },
+        )];
+
+        if let Some(limit) = &limit {
+            query.push(("Limit", limit));
+        }
+        if let Some(fields) = &options.fields {
+            query.push(("Fields", fields));
+        }
+        if let Some(types) = &options.include_item_types {
+            query.push(("IncludeItemTypes", types));
+        }
+
+        self.client
+            .get_with_query(&format!("/Users/{user_id}/Items/Latest"), &query)
+    }
+
+    /// `GET /Users/{user_id}/Items/{id}`
+    ///
+    /// Returns the item with all of its fields and the user's play state.
+    pub fn get(&self, user_id: &str, id: &str) -> Result<BaseItemDto> {
+        self.client.get(&format!("/Users/{user_id}/Items/{id}"))
+    }
+
+    /// `GET /Items` for the items with these IDs.
+    pub fn by_ids(&self, ids: &[String]) -> Result<Vec<BaseItemDto>> {
+        let ids = ids.join(",");
+        let result: QueryResultBaseItemDto = self
+            .client
+            .get_with_query("/Items", &[("Ids", ids.as_str())])?;
+
+        Ok(result.items.unwrap_or_default())
+    }
+
+    /// `GET /Users/{user_id}/Items` for every media item (not folder) under
+    /// `parent_id`, e.g. the episodes of a series or season.
+    pub fn children(&self, user_id: &str, parent_id: &str) -> Result<Vec<BaseItemDto>> {
+        let result: QueryResultBaseItemDto = self.client.get_with_query(
+            &format!("/Users/{user_id}/Items"),
+            &[
+                ("ParentId", parent_id),
+                ("Recursive", "true"),
+                ("IsFolder", "false"),
+            ],
+        )?;
+
+        Ok(result.items.unwrap_or_default())
+    }
+
+    /// `GET /Items` with a search term and filters, searching recursively.
+    pub fn search(&self, options: &SearchOptions) -> Result<QueryResultBaseItemDto> {
+        let joined = |values: Vec<String>, separator: &str| {
+            Some(values.join(separator)).filter(|v| !v.is_empty())
+        };
+        let years = options.years.iter().map(u32::to_string).collect();
+        let params = [
+            (
+                "SearchTerm",
+                Some(options.search_term.clone()).filter(|t| !t.is_empty()),
+            ),
+            ("Limit", options.limit.map(|l| l.to_string())),
+            ("StartIndex", options.start_index.map(|i| i.to_string())),
+            ("Fields", options.fields.clone()),
+            ("IncludeItemTypes", options.include_item_types.clone()),
+            ("ExcludeItemTypes", options.exclude_item_types.clone()),
+            ("Genres", joined(options.genres.clone(), "|")),
+            ("Years", joined(years, ",")),
+            (
+                "OfficialRatings",
+                joined(options.official_ratings.clone(), "|"),
+            ),
+            ("Studios", joined(options.studios.clone(), "|")),
+            ("ParentId", options.parent_id.clone()),
+            ("UserId", options.user_id.clone()),
+            ("IsPlayed", options.is_played.map(|b| b.to_string())),
+            ("Person", options.person.clone()),
+            ("PersonTypes", options.person_types.clone()),
+            ("Is4K", options.is_4k.map(|b| b.to_string())),
+            ("IsHD", options.is_hd.map(|b| b.to_string())),
+            ("SortBy", options.sort_by.clone()),
+            ("SortOrder", options.sort_order.clone()),
+        ];
+
+        let mut query = vec![("Recursive", "true")];
+        for (key, value) in &params {
+            if let Some(value) = value {
+                query.push((key, value));
+            }
+        }
+
+        self.client.get_with_query("/Items", &query)
+    }
+
+    /// [`Items::search`] one page at a time, up to `options.limit` results
+    /// or all of them when it's `None`.
+    pub fn search_pages<'b>(&self, options: &'b SearchOptions) -> Pages<'b>
+    where
+        'a: 'b,
+    {
+        let items = self.client.items();
+        Pages::new(options.limit, move |start_index, limit| {
+            items.search(&SearchOptions {
+                limit: Some(limit),
+                start_index: (start_index > 0).then_some(start_index),
+                ..options.clone()
+            })
+        })
+    }
+
+    /// `GET /Items` under `parent_id`, returning only the number of media
+    /// items (not folders) it contains.
+    pub fn count(&self, parent_id: &str) -> Result<u32> {
+        let result: QueryResultBaseItemDto = self.client.get_with_query(
+            "/Items",
+            &[
+                ("ParentId", parent_id),
+                ("Recursive", "true"),
+                ("IsFolder", "false"),
+                ("Limit", "0"),
+            ],
+        )?;
+
+        Ok(result.total_record_count.unwrap_or(0))
+    }
+
+    /// `POST /Items/{id}/Refresh`
+    pub fn refresh(&self, id: &str, options: &RefreshOptions) -> Result<()> {
+        let body = serde_json::json!({
+            "Recursive": options.recursive,
+            "MetadataRefreshMode": options.metadata_refresh_mode,
+            "ImageRefreshMode": options.image_refresh_mode,
+            "ReplaceAllMetadata": options.replace_all_metadata,
+            "ReplaceAllImages": options.replace_all_images,
+        });
+
+        self.client
+            .post(&format!("/Items/{id}/Refresh"), Some(&body))
+    }
+}
@@ -0,0 +1,102 @@
+use crate::client::Client;
+use crate::emby::types::VirtualFolder;
+use crate::error::Result;
+
+pub struct Library<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl Library<'_> {
+    /// `GET /Library/VirtualFolders`
+    pub fn virtual_folders(&self) -> Result<Vec<VirtualFolder>> {
+        self.client.get("/Library/VirtualFolders")
+    }
+
+    /// `POST /Library/VirtualFolders`
+    ///
+    /// A `collection_type` of `None` creates a mixed content library.
+    pub fn add_virtual_folder(
+        &self,
+        name: &str,
+        collection_type: Option<&str>,
+        paths: &[String],
+        refresh: bool,
+    ) -> Result<()> {
+        let mut query = vec![("Name", name), ("RefreshLibrary", bool_str(refresh))];
+        if let Some(collection_type) = collection_type {
+            query.push(("CollectionType", collection_type));
+        }
+
+        let path_infos: Vec<serde_json::Value> = paths
+            .iter()
+            .map(|path| serde_json::json!({"Path": path}))
+            .collect();
+
+        self.client.post_with_query(
+            "/Library/VirtualFolders",
+            &query,
+            Some(&serde_json::json!({"LibraryOptions": {"PathInfos": path_infos}})),
+        )
+    }
+
+    /// `DELETE /Library/VirtualFolders`
+    pub fn remove_virtual_folder(&self, name: &str) -> Result<()> {
+        self.client.delete_with_query(
+            "/Library/VirtualFolders",
+            &[("Name", name), ("RefreshLibrary", "false")],
+        )
+    }
+
+    /// `POST /Library/VirtualFolders/Name`
+    pub fn rename_virtual_folder(&self, name: &str, new_name: &str) -> Result<()> {
+        self.client.post_with_query(
+            "/Library/VirtualFolders/Name",
+            &[
+                ("Name", name),
+                ("NewName", new_name),
+                ("RefreshLibrary", "false"),
+            ],
+            None,
+        )
+    }
+
+    /// `POST /Library/VirtualFolders/Paths`
+    pub fn add_media_path(&self, name: &str, path: &str, refresh: bool) -> Result<()> {
+        self.client.post_with_query(
+            "/Library/VirtualFolders/Paths",
+            &[("RefreshLibrary", bool_str(refresh))],
+            Some(&serde_json::json!({"Name": name, "PathInfo": {"Path": path}})),
+        )
+    }
+
+    /// `DELETE /Library/VirtualFolders/Paths`
+    pub fn remove_media_path(&self, name: &str, path: &str, refresh: bool) -> Result<()> {
+        self.client.delete_with_query(
+            "/Library/VirtualFolders/Paths",
+            &[
+                ("Name", name),
+                ("Path", path),
+                ("RefreshLibrary", bool_str(refresh)),
+            ],
+        )
+    }
+
+    /// `POST /Library/Media/Updated`
+    ///
+    /// `update_type` is one of `Created`, `Modified` or `Deleted`.
+    pub fn media_updated(&self, paths: &[String], update_type: &str) -> Result<()> {
+        let updates: Vec<serde_json::Value> = paths
+            .iter()
+            .map(|path| serde_json::json!({"Path": path, "UpdateType": update_type}))
+            .collect();
+
+        self.client.post(
+            "/Library/Media/Updated",
+            Some(&serde_json::json!({"Updates": updates})),
+        )
+    }
+}
+
+fn bool_str(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
@@ -0,0 +1,14 @@
+use crate::client::Client;
+use crate::emby::types::ParentalRating;
+use crate::error::Result;
+
+pub struct Localization<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl Localization<'_> {
+    /// `GET /Localization/ParentalRatings`
+    pub fn parental_ratings(&self) -> Result<Vec<ParentalRating>> {
+        self.client.get("/Localization/ParentalRatings")
+    }
+}
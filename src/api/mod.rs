@@ -0,0 +1,86 @@
+//! Typed wrappers around the Emby REST API, and its WebSocket for live
+//! events.
+//!
+//! Each endpoint group is reached through an accessor on [`Client`], e.g.
+//! `client.sessions().list()` or `client.scheduled_tasks().run(id)`, and
+//! returns the structs from [`crate::emby::types`].
+
+pub mod activity_log;
+pub mod devices;
+pub mod events;
+pub mod items;
+pub mod library;
+pub mod localization;
+pub mod paging;
+pub mod scheduled_tasks;
+pub mod sessions;
+pub mod shows;
+pub mod system;
+pub mod user_library;
+pub mod users;
+
+use crate::client::Client;
+
+pub use activity_log::{ActivityLog, ActivityLogOptions};
+pub use devices::Devices;
+pub use events::{EventStream, Events, Subscription};
+pub use items::{Items, LatestOptions, RefreshOptions, SearchOptions};
+pub use library::Library;
+pub use localization::Localization;
+pub use paging::Pages;
+pub use scheduled_tasks::ScheduledTasks;
+pub use sessions::{PlaystateCommand, Sessions};
+pub use shows::Shows;
+pub use system::System;
+pub use user_library::UserLibrary;
+pub use users::Users;
+
+impl Client {
+    pub fn activity_log(&self) -> ActivityLog<'_> {
+        ActivityLog { client: self }
+    }
+
+    pub fn devices(&self) -> Devices<'_> {
+        Devices { client: self }
+    }
+
+    pub fn events(&self) -> Events<'_> {
+        Events { client: self }
+    }
+
+    pub fn items(&self) -> Items<'_> {
+        Items { client: self }
+    }
+
+    pub fn library(&self) -> Library<'_> {
+        Library { client: self }
+    }
+
+    pub fn localization(&self) -> Localization<'_> {
+        Localization { client: self }
+    }
+
+    pub fn scheduled_tasks(&self) -> ScheduledTasks<'_> {
+        ScheduledTasks { client: self }
+    }
+
+    pub fn sessions(&self) -> Sessions<'_> {
+        Sessions { client: self }
+    }
+
+    pub fn shows(&self) -> Shows<'_> {
+        Shows { client: self }
+    }
+
+    pub fn system(&self) -> System<'_> {
+        System { client: self }
+    }
+
+    pub fn user_library(&self) -> UserLibrary<'_> {
+        UserLibrary { client: self }
+    }
+
+    pub fn users(&self) -> Users<'_> {
+        Users { client: self }
+    }
+}
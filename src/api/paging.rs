@@ -0,0 +1,162 @@
+use crate::emby::types::{BaseItemDto, QueryResultBaseItemDto};
+use crate::error::Result;
+
+/// Items requested per page when a query needs more than one request.
+pub const PAGE_SIZE: u32 = 500;
+
+type FetchPage<'a> = dyn FnMut(u32, u32) -> Result<QueryResultBaseItemDto> + 'a;
+
+/// Iterator over the pages of an item query, requesting one page of
+/// `StartIndex`/`Limit` at a time until `limit` items have been returned or
+/// the server runs out. Servers may return fewer items than asked for, so a
+/// short page only ends the query when there's no `TotalRecordCount`.
+pub struct Pages<'a> {
+    fetch: Box<FetchPage<'a>>,
+    remaining: Option<u32>,
+    start_index: u32,
+    done: bool,
+}
+
+impl<'a> Pages<'a> {
+    /// `fetch` is called with the start index and limit of each page.
+    /// A `limit` of `None` returns every item.
+    pub fn new(
+        limit: Option<u32>,
+        fetch: impl FnMut(u32, u32) -> Result<QueryResultBaseItemDto> + 'a,
+    ) -> Self {
+        Self {
+            fetch: Box::new(fetch),
+            remaining: limit,
+            start_index: 0,
+            done: false,
+        }
+    }
+
+    /// Fetch every remaining page into one list.
+    pub fn collect_items(self) -> Result<Vec<BaseItemDto>> {
+        let pages = self.collect::<Result<Vec<_>>>()?;
+        Ok(pages.into_iter().flatten().collect())
+    }
+}
+
+impl Iterator for Pages<'_> {
+    type Item = Result<Vec<BaseItemDto>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.remaining == Some(0) {
+            return None;
+        }
+
+        let page_size = self.remaining.map_or(PAGE_SIZE, |r| r.min(PAGE_SIZE));
+        let result = match (self.fetch)(self.start_index, page_size) {
+            Ok(result) => result,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+
+        let items = result.items.unwrap_or_default();
+        let count = u32::try_from(items.len()).unwrap_or(u32::MAX);
+        self.start_index = self.start_index.saturating_add(count);
+        self.remaining = self.remaining.map(|r| r.saturating_sub(count));
+        self.done = match result.total_record_count {
+            Some(total) => count == 0 || self.start_index >= total,
+            None => count < page_size,
+        };
+
+        (!items.is_empty()).then_some(Ok(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(start: u32, count: u32, total: u32) -> QueryResultBaseItemDto {
+        let items = (start..(start + count).min(total))
+            .map(|i| BaseItemDto {
+                id: Some(i.to_string()),
+                ..BaseItemDto::default()
+            })
+            .collect();
+        QueryResultBaseItemDto {
+            items: Some(items),
+            total_record_count: Some(total),
+        }
+    }
+
+    #[test]
+    fn pages_until_total() {
+        let mut requests = Vec::new();
+        let items = Pages::new(None, |start, limit| {
+            requests.push((start, limit));
+            Ok(page(start, limit, 1200))
+        })
+        .collect_items()
+        .unwrap();
+
+        assert_eq!(items.len(), 1200);
+        assert_eq!(items[1199].id.as_deref(), Some("1199"));
+        assert_eq!(requests, [(0, 500), (500, 500), (1000, 500)]);
+    }
+
+    #[test]
+    fn stops_at_limit() {
+        let mut requests = Vec::new();
+        let items = Pages::new(Some(600), |start, limit| {
+            requests.push((start, limit));
+            Ok(page(start, limit, 1200))
+        })
+        .collect_items()
+        .unwrap();
+
+        assert_eq!(items.len(), 600);
+        assert_eq!(requests, [(0, 500), (500, 100)]);
+    }
+
+    #[test]
+    fn small_limit_is_one_request() {
+        let mut requests = 0;
+        let items = Pages::new(Some(25), |start, limit| {
+            requests += 1;
+            Ok(page(start, limit, 1200))
+        })
+        .collect_items()
+        .unwrap();
+
+        assert_eq!(items.len(), 25);
+        assert_eq!(requests, 1);
+    }
+
+    #[test]
+    fn continues_after_short_page_until_total() {
+        let mut requests = Vec::new();
+        let items = Pages::new(None, |start, limit| {
+            requests.push((start, limit));
+            // The server caps pages at 200 items
+            Ok(page(start, limit.min(200), 450))
+        })
+        .collect_items()
+        .unwrap();
+
+        assert_eq!(items.len(), 450);
+        assert_eq!(requests, [(0, 500), (200, 500), (400, 500)]);
+    }
+
+    #[test]
+    fn stops_on_short_page_without_total() {
+        let mut requests = 0;
+        let items = Pages::new(None, |start, limit| {
+            requests += 1;
+            let mut result = page(start, limit, 700);
+            result.total_record_count = None;
+            Ok(result)
+        })
+        .collect_items()
+        .unwrap();
+
+        assert_eq!(items.len(), 700);
+        assert_eq!(requests, 2);
+    }
+}
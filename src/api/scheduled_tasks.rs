@@ -0,0 +1,37 @@
+use crate::client::Client;
+use crate::emby::types::{TaskInfo, TaskTriggerInfo};
+use crate::error::Result;
+
+pub struct ScheduledTasks<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl ScheduledTasks<'_> {
+    /// `GET /ScheduledTasks`
+    pub fn list(&self) -> Result<Vec<TaskInfo>> {
+        self.client.get("/ScheduledTasks")
+    }
+
+    /// `GET /ScheduledTasks/{id}`
+    pub fn get(&self, id: &str) -> Result<TaskInfo> {
+        self.client.get(&format!("/ScheduledTasks/{id}"))
+    }
+
+    /// `POST /ScheduledTasks/Running/{id}`
+    pub fn run(&self, id: &str) -> Result<()> {
+        self.client
+            .post(&format!("/ScheduledTasks/Running/{id}"), None)
+    }
+
+    /// `DELETE /ScheduledTasks/Running/{id}`
+    pub fn stop(&self, id: &str) -> Result<()> {
+        self.client.delete(&format!("/ScheduledTasks/Running/{id}"))
+    }
+
+    /// `POST /ScheduledTasks/{id}/Triggers`, replacing all of the task's triggers.
+    pub fn set_triggers(&self, id: &str, triggers: &[TaskTriggerInfo]) -> Result<()> {
+        let body = serde_json::to_value(triggers)?;
+        self.client
+            .post(&format!("/ScheduledTasks/{id}/Triggers"), Some(&body))
+    }
+}
@@ -0,0 +1,86 @@
+use crate::client::Client;
+use crate::emby::types::Session;
+use crate::error::Result;
+
+pub struct Sessions<'a> {
+    pub(crate) client: &'a Client,
+}
+
+/// Playback commands sent with [`Sessions::playing`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaystateCommand {
+    Pause,
+    Unpause,
+    Stop,
+    NextTrack,
+    PreviousTrack,
+}
+
+impl PlaystateCommand {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pause => "Pause",
+            Self::Unpause => "Unpause",
+            Self::Stop => "Stop",
+            Self::NextTrack => "NextTrack",
+            Self::PreviousTrack => "PreviousTrack",
+        }
+    }
+}
+
+impl Sessions<'_> {
+    /// `GET /Sessions`
+    pub fn list(&self) -> Result<Vec<Session>> {
+        self.client.get("/Sessions")
+    }
+
+    /// `POST /Sessions/Logout`, revoking the client's access token.
+    pub fn logout(&self) -> Result<()> {
+        self.client.post("/Sessions/Logout", None)
+    }
+
+    /// `POST /Sessions/{id}/Playing/{command}`
+    pub fn playing(&self, id: &str, command: PlaystateCommand) -> Result<()> {
+        self.client.post(
+            &format!("/Sessions/{id}/Playing/{}", command.as_str()),
+            None,
+        )
+    }
+
+    /// `POST /Sessions/{id}/Playing/Seek`
+    pub fn seek(&self, id: &str, position_ticks: u64) -> Result<()> {
+        let ticks = position_ticks.to_string();
+        self.client.post_with_query(
+            &format!("/Sessions/{id}/Playing/Seek"),
+            &[("SeekPositionTicks", &ticks)],
+            None,
+        )
+    }
+
+    /// `POST /Sessions/{id}/Command` with `SetVolume`, volume from 0 to 100.
+    pub fn set_volume(&self, id: &str, volume: u8) -> Result<()> {
+        let body = serde_json::json!({
+            "Name": "SetVolume",
+            "Arguments": { "Volume": volume.to_string() },
+        });
+        self.client
+            .post(&format!("/Sessions/{id}/Command"), Some(&body))
+    }
+
+    /// `POST /Sessions/{id}/Message`, shown on the client for `timeout_ms`
+    /// milliseconds (or until dismissed when `None`).
+    pub fn send_message(
+        &self,
+        id: &str,
+        header: &str,
+        text: &str,
+        timeout_ms: Option<u64>,
+    ) -> Result<()> {
+        let mut body = serde_json::json!({ "Header": header, "Text": text });
+        if let Some(timeout) = timeout_ms {
+            body["TimeoutMs"] = timeout.into();
+        }
+        self.client
+            .post(&format!("/Sessions/{id}/Message"), Some(&body))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::api::Pages;
+use crate::client::Client;
+use crate::emby::types::QueryResultBaseItemDto;
+use crate::error::Result;
+
+pub struct Shows<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl<'a> Shows<'a> {
+    /// `GET /Shows/NextUp`
+    pub fn next_up(&self, user_id: &str, limit: u32) -> Result<QueryResultBaseItemDto> {
+        self.episodes("/Shows/NextUp", user_id, 0, limit)
+    }
+
+    /// [`Shows::next_up`] one page at a time, up to `limit` episodes or all
+    /// of them when it's `None`.
+    pub fn next_up_pages<'b>(&self, user_id: &'b str, limit: Option<u32>) -> Pages<'b>
+    where
+        'a: 'b,
+    {
+        let shows = self.client.shows();
+        Pages::new(limit, move |start_index, limit| {
+            shows.episodes("/Shows/NextUp", user_id, start_index, limit)
+        })
+    }
+
+    /// `GET /Shows/Upcoming`
+    pub fn upcoming(&self, user_id: &str, limit: u32) -> Result<QueryResultBaseItemDto> {
+        self.episodes("/Shows/Upcoming", user_id, 0, limit)
+    }
+
+    /// [`Shows::upcoming`] one page at a time, up to `limit` episodes or all
+    /// of them when it's `None`.
+    pub fn upcoming_pages<'b>(&self, user_id: &'b str, limit: Option<u32>) -> Pages<'b>
+    where
+        'a: 'b,
+    {
+        let shows = self.client.shows();
+        Pages::new(limit, move |start_index, limit| {
+            shows.episodes("/Shows/Upcoming", user_id, start_index, limit)
+        })
+    }
+
+    fn episodes(
+        &self,
+        path: &str,
+        user_id: &str,
+        start_index: u32,
+        limit: u32,
+    ) -> Result<QueryResultBaseItemDto> {
+        let limit = limit.to_string();
+        let start_index = (start_index > 0).then(|| start_index.to_string());
+        let mut query = vec![
+            ("UserId", user_id),
+            ("Limit", limit.as_str()),
+            ("Fields", "Overview"),
+        ];
+        if let Some(start_index) = &start_index {
+            query.push(("StartIndex", start_index.as_str()));
+        }
+
+        self.client.get_with_query(path, &query)
+    }
+}
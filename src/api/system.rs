@@ -0,0 +1,19 @@
+use crate::client::Client;
+use crate::emby::types::SystemInfo;
+use crate::error::Result;
+
+pub struct System<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl System<'_> {
+    /// `GET /System/Info`
+    pub fn info(&self) -> Result<SystemInfo> {
+        self.client.get("/System/Info")
+    }
+
+    /// `POST /System/Restart`
+    pub fn restart(&self) -> Result<()> {
+        self.client.post("/System/Restart", None)
+    }
+}
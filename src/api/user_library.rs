@@ -0,0 +1,48 @@
+use crate::client::Client;
+use crate::error::Result;
+
+/// Per-user item state: played, favorites and likes.
+pub struct UserLibrary<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl UserLibrary<'_> {
+    /// `POST /Users/{user_id}/PlayedItems/{id}`
+    pub fn mark_played(&self, user_id: &str, id: &str) -> Result<()> {
+        self.client
+            .post(&format!("/Users/{user_id}/PlayedItems/{id}"), None)
+    }
+
+    /// `DELETE /Users/{user_id}/PlayedItems/{id}`
+    pub fn mark_unplayed(&self, user_id: &str, id: &str) -> Result<()> {
+        self.client
+            .delete(&format!("/Users/{user_id}/PlayedItems/{id}"))
+    }
+
+    /// `POST /Users/{user_id}/FavoriteItems/{id}`
+    pub fn add_favorite(&self, user_id: &str, id: &str) -> Result<()> {
+        self.client
+            .post(&format!("/Users/{user_id}/FavoriteItems/{id}"), None)
+    }
+
+    /// `DELETE /Users/{user_id}/FavoriteItems/{id}`
+    pub fn remove_favorite(&self, user_id: &str, id: &str) -> Result<()> {
+        self.client
+            .delete(&format!("/Users/{user_id}/FavoriteItems/{id}"))
+    }
+
+    /// `POST /Users/{user_id}/Items/{id}/Rating`
+    pub fn set_like(&self, user_id: &str, id: &str, likes: bool) -> Result<()> {
+        self.client.post_with_query(
+            &format!("/Users/{user_id}/Items/{id}/Rating"),
+            &[("Likes", if likes { "true" } else { "false" })],
+            None,
+        )
+    }
+
+    /// `DELETE /Users/{user_id}/Items/{id}/Rating`
+    pub fn clear_like(&self, user_id: &str, id: &str) -> Result<()> {
+        self.client
+            .delete(&format!("/Users/{user_id}/Items/{id}/Rating"))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::client::Client;
+use crate::emby::types::{AuthenticationResult, User, UserPolicy};
+use crate::error::Result;
+
+pub struct Users<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl Users<'_> {
+    /// `GET /Users`
+    pub fn list(&self) -> Result<Vec<User>> {
+        self.client.get("/Users")
+    }
+
+    /// `GET /Users/{id}`
+    pub fn get(&self, id: &str) -> Result<User> {
+        self.client.get(&format!("/Users/{id}"))
+    }
+
+    /// `POST /Users/New`, optionally copying the policy and configuration
+    /// of another user.
+    pub fn create(&self, name: &str, copy_from: Option<&str>) -> Result<User> {
+        let mut body = serde_json::json!({ "Name": name });
+        if let Some(id) = copy_from {
+            body["CopyFromUserId"] = id.into();
+            body["UserCopyOptions"] = serde_json::json!(["UserPolicy", "UserConfiguration"]);
+        }
+
+        self.client.post_json("/Users/New", &body)
+    }
+
+    /// `DELETE /Users/{id}`
+    pub fn delete(&self, id: &str) -> Result<()> {
+        self.client.delete(&format!("/Users/{id}"))
+    }
+
+    /// `POST /Users/{id}`
+    pub fn update(&self, id: &str, user: &User) -> Result<()> {
+        let body = serde_json::to_value(user)?;
+        self.client.post(&format!("/Users/{id}"), Some(&body))
+    }
+
+    /// `POST /Users/{id}/Policy`
+    pub fn update_policy(&self, id: &str, policy: &UserPolicy) -> Result<()> {
+        let body = serde_json::to_value(policy)?;
+        self.client
+            .post(&format!("/Users/{id}/Policy"), Some(&body))
+    }
+
+    /// `POST /Users/{id}/Password`, setting a new password as an administrator.
+    /// An empty password removes it.
+    pub fn set_password(&self, id: &str, password: &str) -> Result<()> {
+        let body = if password.is_empty() {
+            serde_json::json!({ "Id": id, "ResetPassword": true })
+        } else {
+            serde_json::json!({ "Id": id, "CurrentPw": "", "NewPw": password })
+        };
+
+        self.client
+            .post(&format!("/Users/{id}/Password"), Some(&body))
+    }
+
+    /// `POST /Users/AuthenticateByName`
+    pub fn authenticate_by_name(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<AuthenticationResult> {
+        self.client.post_json(
+            "/Users/AuthenticateByName",
+            &serde_json::json!({ "Username": username, "Pw": password }),
+        )
+    }
+}
@@ -1,12 +1,80 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::client::HttpOptions;
+use crate::config::Config;
+use crate::error::Result;
+use crate::format::output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "emby", about = "CLI for some random stuff in Emby")]
 pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+#[derive(clap::Args)]
+pub struct GlobalArgs {
+    /// Config profile to use
+    #[arg(long, global = true, env = "EMBY_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t)]
+    pub output: OutputFormat,
+
+    /// Seconds to wait when connecting to the server [default: 10]
+    #[arg(
+        long,
+        global = true,
+        env = "EMBY_CONNECT_TIMEOUT",
+        value_name = "SECONDS"
+    )]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds to wait for the server to respond [default: 30]
+    #[arg(long, global = true, env = "EMBY_READ_TIMEOUT", value_name = "SECONDS")]
+    pub read_timeout: Option<u64>,
+
+    /// Times to retry a request after a connection error or server error [default: 3]
+    #[arg(long, global = true, env = "EMBY_RETRIES", value_name = "COUNT")]
+    pub retries: Option<u32>,
+}
+
+impl GlobalArgs {
+    /// Load the config for `--profile`. HTTP options given on the command
+    /// line take precedence over the config file.
+    pub fn load_config(&self) -> Result<Config> {
+        let mut config = Config::load(self.profile.as_deref())?;
+        config.http = self.http_options(config.http);
+        Ok(config)
+    }
+
+    /// Apply `--connect-timeout`, `--read-timeout` and `--retries` to `http`.
+    pub fn http_options(&self, mut http: HttpOptions) -> HttpOptions {
+        if let Some(seconds) = self.connect_timeout {
+            http.connect_timeout = Duration::from_secs(seconds);
+        }
+        if let Some(seconds) = self.read_timeout {
+            http.read_timeout = Duration::from_secs(seconds);
+        }
+        if let Some(retries) = self.retries {
+            http.retries = retries;
+        }
+        http
+    }
+}
+
+/// The number of results to fetch for `--limit`/`--all`, `None` for all of them.
+pub fn result_limit(limit: u32, all: bool) -> Option<u32> {
+    (!all && limit > 0).then_some(limit)
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// Trigger library scans
@@ -15,20 +83,26 @@ pub enum Command {
     /// Show what's currently playing
     Playing(PlayingArgs),
 
+    /// Full-screen view of sessions, tasks and activity
+    Dashboard(DashboardArgs),
+
+    /// Control playback sessions
+    Session(SessionArgs),
+
     /// Restart Emby
     Restart,
 
     /// Show system information
     System,
 
-    /// List users
-    Users,
+    /// List and manage users
+    Users(UsersArgs),
 
-    /// List devices
-    Devices,
+    /// List and manage devices
+    Devices(DevicesArgs),
 
-    /// List libraries
-    Libraries,
+    /// List and manage libraries
+    Libraries(LibrariesArgs),
 
     /// Find Emby servers on the local network
     FindServer(FindServerArgs),
@@ -36,12 +110,39 @@ pub enum Command {
     /// Show recent activity log
     Activity(ActivityArgs),
 
+    /// Stream live server events as NDJSON
+    Events(EventsArgs),
+
+    /// Run commands or webhooks when server events happen
+    Hooks(HooksArgs),
+
     /// Show recently added media
     Latest(LatestArgs),
 
     /// Search the library
     Search(SearchArgs),
 
+    /// Show everything about a single item
+    Item(ItemArgs),
+
+    /// Search interactively and act on the chosen item
+    Pick(PickArgs),
+
+    /// Mark items as played
+    Played(UserDataArgs),
+
+    /// Mark items as not played
+    Unplayed(UserDataArgs),
+
+    /// Add items to favorites
+    Favorite(UserDataArgs),
+
+    /// Remove items from favorites
+    Unfavorite(UserDataArgs),
+
+    /// Like or dislike items
+    Rate(RateArgs),
+
     /// Show next episodes to watch
     NextUp(NextUpArgs),
 
@@ -50,6 +151,12 @@ pub enum Command {
 
     /// List and run scheduled tasks
     Tasks(TasksArgs),
+
+    /// Log in with a username and password
+    Login(LoginArgs),
+
+    /// Log out and revoke the stored access token
+    Logout,
 }
 
 #[derive(clap::Args)]
@@ -62,10 +169,23 @@ pub struct FindServerArgs {
 #[derive(clap::Args)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct ScanArgs {
-    /// Library types to scan (shows, movies, music, all)
+    /// Libraries to scan, by type (movies, shows, music, ...), name or ID, or all
     #[arg(default_value = "all")]
     pub libraries: Vec<String>,
 
+    /// Tell Emby a file or folder changed instead of scanning whole libraries
+    #[arg(short, long = "path", value_name = "PATH", conflicts_with = "wait")]
+    pub paths: Vec<String>,
+
+    /// How the paths given with --path changed
+    #[arg(
+        long,
+        default_value = "Modified",
+        value_parser = ["Created", "Modified", "Deleted"],
+        requires = "paths"
+    )]
+    pub update_type: String,
+
     /// Recursively scan directories
     #[arg(short = 'r', long, default_value_t = true)]
     pub recursive: bool,
@@ -89,6 +209,14 @@ pub struct ScanArgs {
     /// Replace all images
     #[arg(long)]
     pub replace_all_images: bool,
+
+    /// Wait for the scan to finish, showing progress
+    #[arg(short, long)]
+    pub wait: bool,
+
+    /// Give up waiting after N seconds
+    #[arg(short, long, requires = "wait")]
+    pub timeout: Option<u64>,
 }
 
 #[derive(clap::Args)]
@@ -113,11 +241,170 @@ pub struct PlayingArgs {
     pub users: Vec<String>,
 }
 
+#[derive(clap::Args)]
+pub struct DashboardArgs {
+    /// Seconds between refreshes
+    #[arg(short = 'n', long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+}
+
+#[derive(clap::Args)]
+pub struct SessionArgs {
+    #[command(subcommand)]
+    pub command: SessionCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SessionCommand {
+    /// Pause playback
+    Pause(SessionTarget),
+
+    /// Resume paused playback
+    Resume(SessionTarget),
+
+    /// Stop playback
+    Stop(SessionTarget),
+
+    /// Seek to a position
+    Seek(SessionSeekArgs),
+
+    /// Skip to the next track or episode
+    Next(SessionTarget),
+
+    /// Skip to the previous track or episode
+    Previous(SessionTarget),
+
+    /// Set the playback volume
+    Volume(SessionVolumeArgs),
+
+    /// Display a message on the client
+    Message(SessionMessageArgs),
+}
+
+/// Selects sessions by ID, user and/or device. All given filters must match.
+#[derive(clap::Args)]
+#[group(required = true, multiple = true)]
+pub struct SessionTarget {
+    /// Session ID
+    #[arg(short, long)]
+    pub id: Option<String>,
+
+    /// User name
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Device name or ID
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Apply to every matching session instead of requiring exactly one
+    #[arg(short, long)]
+    pub all: bool,
+}
+
+#[derive(clap::Args)]
+pub struct SessionSeekArgs {
+    #[command(flatten)]
+    pub target: SessionTarget,
+
+    /// Position as HH:MM:SS, MM:SS or seconds
+    pub position: String,
+}
+
+#[derive(clap::Args)]
+pub struct SessionVolumeArgs {
+    #[command(flatten)]
+    pub target: SessionTarget,
+
+    /// Volume from 0 to 100
+    #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub volume: u8,
+}
+
+#[derive(clap::Args)]
+pub struct SessionMessageArgs {
+    #[command(flatten)]
+    pub target: SessionTarget,
+
+    /// Message text
+    pub text: String,
+
+    /// Message header
+    #[arg(long, default_value = "Message")]
+    pub header: String,
+
+    /// Hide the message after N seconds (default: until dismissed)
+    #[arg(short, long)]
+    pub timeout: Option<u64>,
+}
+
 #[derive(clap::Args)]
 pub struct ActivityArgs {
-    /// Maximum number of entries to show
+    /// Maximum number of entries to show (0 for all)
     #[arg(short, long, default_value_t = 25)]
     pub limit: u32,
+
+    /// Show every entry, same as --limit 0
+    #[arg(short, long, conflicts_with = "limit")]
+    pub all: bool,
+
+    /// Keep running and print new entries as they arrive
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Seconds between checks for new entries
+    #[arg(short = 'n', long, default_value_t = 5, requires = "follow")]
+    pub interval: u64,
+
+    /// Only entries since a time (2024-06-15, "2024-06-15 10:30") or duration ago (30m, 2h, 7d)
+    #[arg(short, long)]
+    pub since: Option<String>,
+
+    /// Only entries before a time or duration ago
+    #[arg(short, long, conflicts_with = "follow")]
+    pub until: Option<String>,
+
+    /// Only entries with this severity (Info, Warn, Error)
+    #[arg(long = "severity", value_name = "SEVERITY")]
+    pub severities: Vec<String>,
+
+    /// Only entries of this type, e.g. `AuthenticationFailed` or `VideoPlayback`
+    #[arg(short, long = "type", value_name = "TYPE")]
+    pub types: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct EventsArgs {
+    /// Only events of these types, e.g. `Sessions` or `LibraryChanged`
+    #[arg(short, long = "type", value_delimiter = ',', value_name = "TYPE")]
+    pub types: Vec<String>,
+
+    /// Exit after printing this many events
+    #[arg(short = 'n', long)]
+    pub count: Option<u32>,
+}
+
+#[derive(clap::Args)]
+pub struct HooksArgs {
+    #[command(subcommand)]
+    pub command: HooksCommand,
+}
+
+#[derive(Subcommand)]
+pub enum HooksCommand {
+    /// Keep running and fire hooks as events arrive
+    Run(HooksRunArgs),
+}
+
+#[derive(clap::Args)]
+pub struct HooksRunArgs {
+    /// TOML file with the hooks to run
+    #[arg(short, long, value_name = "PATH")]
+    pub config: PathBuf,
+
+    /// Print the hooks that would run instead of running them
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(clap::Args)]
@@ -130,39 +417,262 @@ pub struct LatestArgs {
     #[arg(short, long)]
     pub r#type: Option<String>,
 
-    /// User name (defaults to first admin user)
+    /// User name (defaults to logged-in user, or first admin user)
     #[arg(short, long)]
     pub user: Option<String>,
 }
 
 #[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct SearchArgs {
-    /// Search query
-    pub query: String,
+    /// Search query (optional when filtering)
+    pub query: Option<String>,
 
-    /// Maximum number of results
+    /// Maximum number of results (0 for all)
     #[arg(short, long, default_value_t = 25)]
     pub limit: u32,
+
+    /// Show every result, same as --limit 0
+    #[arg(short, long, conflicts_with = "limit")]
+    pub all: bool,
+
+    /// Only items of these types
+    #[arg(
+        short,
+        long = "type",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "TYPE"
+    )]
+    pub types: Vec<ItemType>,
+
+    /// Only items in this genre (repeat for any of several)
+    #[arg(short, long = "genre", value_name = "GENRE")]
+    pub genres: Vec<String>,
+
+    /// Only items from a year or range of years, e.g. 1995 or 1990-1999
+    #[arg(short, long)]
+    pub year: Option<String>,
+
+    /// Only items with this parental rating, e.g. PG-13 (repeat for any of several)
+    #[arg(long = "parental-rating", value_name = "RATING")]
+    pub parental_ratings: Vec<String>,
+
+    /// Only items in this library (name or ID)
+    #[arg(long)]
+    pub library: Option<String>,
+
+    /// Only items the user has played
+    #[arg(long, conflicts_with = "unplayed")]
+    pub played: bool,
+
+    /// Only items the user hasn't played
+    #[arg(long)]
+    pub unplayed: bool,
+
+    /// User for --played and --unplayed (defaults to logged-in user, or first admin user)
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Only items with this person in the cast or crew
+    #[arg(short, long)]
+    pub person: Option<String>,
+
+    /// Only count --person in these roles
+    #[arg(
+        long = "person-type",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "TYPE",
+        requires = "person"
+    )]
+    pub person_types: Vec<PersonType>,
+
+    /// Only items from this studio (repeat for any of several)
+    #[arg(long = "studio", value_name = "STUDIO")]
+    pub studios: Vec<String>,
+
+    /// Only videos of this resolution
+    #[arg(long, value_enum)]
+    pub resolution: Option<Resolution>,
+
+    /// Sort results (name ascending; the others newest, best or longest first)
+    #[arg(short, long, value_enum)]
+    pub sort: Option<SearchSort>,
+
+    /// Reverse the sort order
+    #[arg(short, long, requires = "sort")]
+    pub reverse: bool,
+
+    /// Columns to show [default: type,name,year,id]
+    #[arg(short, long, value_enum, value_delimiter = ',', value_name = "FIELD")]
+    pub fields: Vec<SearchField>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ItemType {
+    Movie,
+    #[value(alias = "show")]
+    Series,
+    Season,
+    Episode,
+    #[value(alias = "track", alias = "song")]
+    Audio,
+    Album,
+    Artist,
+    #[value(name = "musicvideo")]
+    MusicVideo,
+    Video,
+    Book,
+    Photo,
+    #[value(alias = "boxset")]
+    Collection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PersonType {
+    Actor,
+    Director,
+    Writer,
+    Producer,
+    #[value(name = "gueststar")]
+    GuestStar,
+    Composer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Resolution {
+    /// 2160p and up
+    #[value(name = "4k", alias = "uhd")]
+    Uhd,
+    /// 720p or 1080p
+    Hd,
+    /// Below 720p
+    Sd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SearchSort {
+    Name,
+    #[value(name = "date-added", alias = "added")]
+    DateAdded,
+    Premiere,
+    /// Community rating
+    Rating,
+    Runtime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SearchField {
+    Type,
+    Name,
+    Year,
+    Id,
+    /// Parental rating
+    Rating,
+    /// Community rating
+    Score,
+    Runtime,
+    Genres,
+    Studios,
+    Added,
+    Premiered,
+    Path,
+}
+
+#[derive(clap::Args)]
+pub struct ItemArgs {
+    /// Item ID, as shown by search
+    pub id: String,
+
+    /// User whose play state to show (defaults to logged-in user, or first admin user)
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Don't colorize output
+    #[arg(short, long)]
+    pub plain: bool,
+}
+
+#[derive(clap::Args)]
+pub struct PickArgs {
+    /// Initial search query
+    pub query: Option<String>,
+
+    /// Only items of these types
+    #[arg(
+        short,
+        long = "type",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "TYPE"
+    )]
+    pub types: Vec<ItemType>,
+
+    /// User whose play state to show and change (defaults to logged-in user, or first admin user)
+    #[arg(short, long)]
+    pub user: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct UserDataArgs {
+    /// Item IDs
+    #[arg(required = true)]
+    pub ids: Vec<String>,
+
+    /// User to change (defaults to logged-in user, or first admin user)
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Apply to every episode or track in a series, season, album or folder
+    #[arg(short, long)]
+    pub recursive: bool,
+}
+
+#[derive(clap::Args)]
+pub struct RateArgs {
+    /// Rating to give
+    #[arg(value_enum)]
+    pub rating: Rating,
+
+    #[command(flatten)]
+    pub items: UserDataArgs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Rating {
+    Like,
+    Dislike,
+    /// Remove a like or dislike
+    Clear,
 }
 
 #[derive(clap::Args)]
 pub struct NextUpArgs {
-    /// Maximum number of items to show
+    /// Maximum number of items to show (0 for all)
     #[arg(short, long, default_value_t = 20)]
     pub limit: u32,
 
-    /// User name (defaults to first admin user)
+    /// Show every item, same as --limit 0
+    #[arg(short, long, conflicts_with = "limit")]
+    pub all: bool,
+
+    /// User name (defaults to logged-in user, or first admin user)
     #[arg(short, long)]
     pub user: Option<String>,
 }
 
 #[derive(clap::Args)]
 pub struct UpcomingArgs {
-    /// Maximum number of items to show
+    /// Maximum number of items to show (0 for all)
     #[arg(short, long, default_value_t = 20)]
     pub limit: u32,
 
-    /// User name (defaults to first admin user)
+    /// Show every item, same as --limit 0
+    #[arg(short, long, conflicts_with = "limit")]
+    pub all: bool,
+
+    /// User name (defaults to logged-in user, or first admin user)
     #[arg(short, long)]
     pub user: Option<String>,
 }
@@ -181,10 +691,387 @@ pub struct TasksArgs {
 pub enum TasksCommand {
     /// Run a scheduled task
     Run(TasksRunArgs),
+
+    /// Stop a running scheduled task
+    Stop(TasksStopArgs),
+
+    /// List, add or remove a scheduled task's triggers
+    Triggers(TasksTriggersArgs),
 }
 
 #[derive(clap::Args)]
 pub struct TasksRunArgs {
-    /// Task ID to run
-    pub id: String,
+    /// Task name (or a unique prefix of it) or ID
+    pub task: String,
+
+    /// Wait for the task to finish, showing progress
+    #[arg(short, long)]
+    pub wait: bool,
+
+    /// Give up waiting after N seconds
+    #[arg(short, long, requires = "wait")]
+    pub timeout: Option<u64>,
+}
+
+#[derive(clap::Args)]
+pub struct TasksStopArgs {
+    /// Task name (or a unique prefix of it) or ID
+    pub task: String,
+}
+
+#[derive(clap::Args)]
+pub struct TasksTriggersArgs {
+    /// Task name (or a unique prefix of it) or ID
+    pub task: String,
+
+    #[command(subcommand)]
+    pub command: Option<TriggersCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum TriggersCommand {
+    /// Add a trigger
+    Add(TriggersAddArgs),
+
+    /// Remove a trigger by its number in the list
+    Remove(TriggersRemoveArgs),
+}
+
+#[derive(clap::Args)]
+pub struct TriggersAddArgs {
+    /// When the task should run
+    #[arg(value_enum)]
+    pub kind: TriggerKind,
+
+    /// Time of day for daily and weekly triggers, e.g. 03:00
+    #[arg(long, required_if_eq_any([("kind", "daily"), ("kind", "weekly")]))]
+    pub at: Option<String>,
+
+    /// Day of the week for weekly triggers
+    #[arg(long, value_enum, ignore_case = true, required_if_eq("kind", "weekly"))]
+    pub day: Option<Weekday>,
+
+    /// Time between runs for interval triggers, e.g. 30m, 6h or 1d
+    #[arg(long, required_if_eq("kind", "interval"))]
+    pub every: Option<String>,
+
+    /// Stop the task if it runs longer than this, e.g. 2h
+    #[arg(long)]
+    pub max_runtime: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct TriggersRemoveArgs {
+    /// Trigger number, as shown by `emby tasks triggers <task>`
+    pub number: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TriggerKind {
+    /// Every day at --at
+    Daily,
+    /// Every week on --day at --at
+    Weekly,
+    /// Every --every
+    Interval,
+    /// When the server starts
+    Startup,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Weekday {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+#[derive(clap::Args)]
+pub struct UsersArgs {
+    #[command(subcommand)]
+    pub command: Option<UsersCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum UsersCommand {
+    /// Create a user
+    Create(UsersCreateArgs),
+
+    /// Delete a user
+    Delete(UsersDeleteArgs),
+
+    /// Rename a user
+    Rename(UsersRenameArgs),
+
+    /// Set or remove a user's password
+    SetPassword(UsersSetPasswordArgs),
+
+    /// Show or change a user's policy
+    Policy(UsersPolicyArgs),
+
+    /// Create and update users to match a JSON or TOML file
+    Apply(UsersApplyArgs),
+}
+
+#[derive(clap::Args)]
+pub struct UsersApplyArgs {
+    /// Users file (.json or .toml), or - for stdin
+    pub file: PathBuf,
+
+    /// Show what would change without changing anything
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Delete users that aren't in the file, other than administrators
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Don't ask before deleting users
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args)]
+pub struct UsersCreateArgs {
+    /// Name of the new user
+    pub name: String,
+
+    /// Copy the policy and settings of an existing user
+    #[arg(short, long, value_name = "USER")]
+    pub copy_from: Option<String>,
+
+    /// Prompt for a password for the new user
+    #[arg(short, long, conflicts_with = "password_stdin")]
+    pub password: bool,
+
+    /// Read a password for the new user from stdin
+    #[arg(long)]
+    pub password_stdin: bool,
+}
+
+#[derive(clap::Args)]
+pub struct UsersDeleteArgs {
+    /// User name or ID
+    pub user: String,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args)]
+pub struct UsersRenameArgs {
+    /// User name or ID
+    pub user: String,
+
+    /// New user name
+    pub new_name: String,
+}
+
+#[derive(clap::Args)]
+pub struct UsersSetPasswordArgs {
+    /// User name or ID
+    pub user: String,
+
+    /// Read the password from stdin instead of prompting
+    #[arg(long, conflicts_with = "remove")]
+    pub password_stdin: bool,
+
+    /// Remove the password so the user can sign in without one
+    #[arg(long)]
+    pub remove: bool,
+}
+
+#[derive(clap::Args)]
+pub struct UsersPolicyArgs {
+    /// User name or ID
+    pub user: String,
+
+    /// Allow the user to sign in
+    #[arg(long, value_name = "BOOL")]
+    pub enabled: Option<bool>,
+
+    /// Make the user an administrator
+    #[arg(long, value_name = "BOOL")]
+    pub admin: Option<bool>,
+
+    /// Allow access from outside the local network
+    #[arg(long, value_name = "BOOL")]
+    pub remote_access: Option<bool>,
+
+    /// Only allow access to these libraries, by name or ID
+    #[arg(long, value_delimiter = ',', value_name = "LIBRARY")]
+    pub libraries: Option<Vec<String>>,
+
+    /// Allow access to all libraries
+    #[arg(long, conflicts_with = "libraries")]
+    pub all_libraries: bool,
+
+    /// Maximum remote streaming bitrate, e.g. 8M or 1500k, or "none"
+    #[arg(long, value_name = "BITRATE")]
+    pub max_bitrate: Option<String>,
+
+    /// Highest parental rating the user may watch, e.g. PG-13, or "none"
+    #[arg(long, value_name = "RATING")]
+    pub max_parental_rating: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct DevicesArgs {
+    #[command(subcommand)]
+    pub command: Option<DevicesCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum DevicesCommand {
+    /// Delete a device, signing it out
+    Delete(DevicesDeleteArgs),
+
+    /// Rename a device
+    Rename(DevicesRenameArgs),
+
+    /// Delete devices that haven't been active for a while
+    Prune(DevicesPruneArgs),
+}
+
+#[derive(clap::Args)]
+pub struct DevicesDeleteArgs {
+    /// Device name or ID
+    pub device: String,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args)]
+pub struct DevicesRenameArgs {
+    /// Device name or ID
+    pub device: String,
+
+    /// New device name
+    pub new_name: String,
+}
+
+#[derive(clap::Args)]
+pub struct DevicesPruneArgs {
+    /// Delete devices last active longer ago than this, e.g. 90d or 12w
+    #[arg(long, value_name = "DURATION")]
+    pub older_than: String,
+
+    /// Show which devices would be deleted without deleting them
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args)]
+pub struct LibrariesArgs {
+    #[command(subcommand)]
+    pub command: Option<LibrariesCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum LibrariesCommand {
+    /// Create a library
+    Create(LibrariesCreateArgs),
+
+    /// Add a folder to a library
+    AddPath(LibrariesPathArgs),
+
+    /// Remove a folder from a library
+    RemovePath(LibrariesPathArgs),
+
+    /// Rename a library
+    Rename(LibrariesRenameArgs),
+
+    /// Delete a library, leaving its files on disk
+    Delete(LibrariesDeleteArgs),
+}
+
+#[derive(clap::Args)]
+pub struct LibrariesCreateArgs {
+    /// Library name
+    pub name: String,
+
+    /// Content type
+    #[arg(short, long = "type", value_enum, default_value_t = LibraryType::Mixed)]
+    pub library_type: LibraryType,
+
+    /// Folder with the library's media (repeatable)
+    #[arg(short, long = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Don't scan the library afterwards
+    #[arg(long)]
+    pub no_scan: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LibraryType {
+    Movies,
+    #[value(name = "tvshows", alias = "shows", alias = "tv")]
+    TvShows,
+    Music,
+    #[value(name = "musicvideos")]
+    MusicVideos,
+    #[value(name = "homevideos")]
+    HomeVideos,
+    Books,
+    Photos,
+    /// Mixed movies and shows
+    Mixed,
+}
+
+#[derive(clap::Args)]
+pub struct LibrariesPathArgs {
+    /// Library name or ID
+    pub library: String,
+
+    /// Folder path on the server
+    pub path: String,
+
+    /// Don't scan the library afterwards
+    #[arg(long)]
+    pub no_scan: bool,
+}
+
+#[derive(clap::Args)]
+pub struct LibrariesRenameArgs {
+    /// Library name or ID
+    pub library: String,
+
+    /// New library name
+    pub new_name: String,
+}
+
+#[derive(clap::Args)]
+pub struct LibrariesDeleteArgs {
+    /// Library name or ID
+    pub library: String,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args)]
+pub struct LoginArgs {
+    /// Server URL (defaults to the configured `api_url`)
+    #[arg(short, long)]
+    pub server: Option<String>,
+
+    /// User name (prompted if omitted)
+    #[arg(short, long)]
+    pub username: Option<String>,
+
+    /// Read the password from stdin instead of prompting
+    #[arg(long, requires = "username")]
+    pub password_stdin: bool,
 }
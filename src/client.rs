@@ -1,67 +1,136 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
 use serde::de::DeserializeOwned;
+use tungstenite::HandshakeError;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
+use tungstenite::stream::MaybeTlsStream;
 
 use crate::config::Config;
-use crate::error::Result;
+use crate::error::{Error, Result};
+
+/// An open WebSocket connection to the server.
+pub type WebSocket = tungstenite::WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// Delay before the first retry; it doubles with every further attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest delay between retries.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Timeouts and retries for requests to the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// Time allowed to connect to the server
+    pub connect_timeout: Duration,
+    /// Time allowed for the server to start responding, and for reading the body
+    pub read_timeout: Duration,
+    /// How often to retry a GET after a connection error or a 5xx response
+    pub retries: u32,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            retries: 3,
+        }
+    }
+}
 
 pub struct Client {
     agent: ureq::Agent,
     base_url: String,
     api_key: String,
+    user_id: Option<String>,
+    authorization: String,
+    connect_timeout: Duration,
+    retries: u32,
 }
 
 impl Client {
     pub fn new(config: &Config) -> Self {
-        let agent = ureq::Agent::new_with_config(ureq::config::Config::builder().build());
         Self {
-            agent,
+            agent: agent(&config.http),
             base_url: config.api_url.trim_end_matches('/').to_string(),
             api_key: config.api_key.clone(),
+            user_id: config.user_id.clone(),
+            authorization: authorization(),
+            connect_timeout: config.http.connect_timeout,
+            retries: config.http.retries,
         }
     }
 
-    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
-        let url = self.url(path);
-        let response = self
-            .agent
-            .get(&url)
-            .header("X-Emby-Token", &self.api_key)
-            .header("Accept", "*/*")
-            .call()?;
+    /// Build a client without credentials, used to log in.
+    pub fn anonymous(api_url: &str, http: HttpOptions) -> Self {
+        Self::new(&Config {
+            api_url: api_url.to_string(),
+            api_key: String::new(),
+            user_id: None,
+            http,
+        })
+    }
 
-        let body: T = response.into_body().read_json()?;
-        Ok(body)
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.get_with_query(path, &[])
     }
 
+    /// GET and parse a JSON response. Connection errors and 5xx responses
+    /// are retried with exponential backoff, since a GET is safe to repeat.
     pub fn get_with_query<T: DeserializeOwned>(
         &self,
         path: &str,
         query: &[(&str, &str)],
     ) -> Result<T> {
         let url = self.url(path);
-        let mut request = self
-            .agent
-            .get(&url)
-            .header("X-Emby-Token", &self.api_key)
-            .header("Accept", "*/*");
 
-        for (key, value) in query {
-            request = request.query(key, value);
-        }
+        self.with_retries(|| {
+            let mut request = self
+                .agent
+                .get(&url)
+                .header("X-Emby-Token", &self.api_key)
+                .header("X-Emby-Authorization", &self.authorization)
+                .header("Accept", "*/*");
 
-        let response = request.call()?;
-        let body: T = response.into_body().read_json()?;
-        Ok(body)
+            for (key, value) in query {
+                request = request.query(key, value);
+            }
+
+            let response = request.call()?;
+            let body: T = response.into_body().read_json()?;
+            Ok(body)
+        })
     }
 
     pub fn post(&self, path: &str, body: Option<&serde_json::Value>) -> Result<()> {
+        self.post_with_query(path, &[], body)
+    }
+
+    pub fn post_with_query(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&serde_json::Value>,
+    ) -> Result<()> {
         let url = self.url(path);
-        let request = self
+        let mut request = self
             .agent
             .post(&url)
             .header("X-Emby-Token", &self.api_key)
+            .header("X-Emby-Authorization", &self.authorization)
             .header("Accept", "*/*")
             .header("Content-Type", "application/json");
 
+        for (key, value) in query {
+            request = request.query(key, value);
+        }
+
         if let Some(json) = body {
             request.send_json(json)?;
         } else {
@@ -71,13 +140,237 @@ impl Client {
         Ok(())
     }
 
+    /// POST a JSON body and parse the JSON response.
+    pub fn post_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<T> {
+        let url = self.url(path);
+        let response = self
+            .agent
+            .post(&url)
+            .header("X-Emby-Token", &self.api_key)
+            .header("X-Emby-Authorization", &self.authorization)
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/json")
+            .send_json(body)?;
+
+        let body: T = response.into_body().read_json()?;
+        Ok(body)
+    }
+
+    pub fn delete(&self, path: &str) -> Result<()> {
+        self.delete_with_query(path, &[])
+    }
+
+    pub fn delete_with_query(&self, path: &str, query: &[(&str, &str)]) -> Result<()> {
+        let url = self.url(path);
+        let mut request = self
+            .agent
+            .delete(&url)
+            .header("X-Emby-Token", &self.api_key)
+            .header("X-Emby-Authorization", &self.authorization)
+            .header("Accept", "*/*");
+
+        for (key, value) in query {
+            request = request.query(key, value);
+        }
+
+        request.call()?;
+        Ok(())
+    }
+
+    /// Where and how to open a WebSocket at `path`, e.g. `/embywebsocket`.
+    /// The connector can be moved to another thread to reconnect from there.
+    pub fn websocket(&self, path: &str) -> WebSocketConnector {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let base_url = self
+            .base_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+
+        WebSocketConnector {
+            url: format!(
+                "{base_url}/{path}?api_key={}&deviceId={}",
+                self.api_key,
+                device_id()
+            ),
+            api_key: self.api_key.clone(),
+            authorization: self.authorization.clone(),
+            connect_timeout: self.connect_timeout,
+            reconnect: self.retries > 0,
+        }
+    }
+
     /// Returns the base API URL, e.g., `http://emby.local:8096`
     pub fn api_url(&self) -> &str {
         &self.base_url
     }
 
+    /// Returns the user ID when authenticated with `emby login`.
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    fn with_retries<T>(&self, mut request: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match request() {
+                Err(e) if attempt < self.retries && e.is_transient() => {
+                    thread::sleep(backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     fn url(&self, path: &str) -> String {
         let path = path.strip_prefix('/').unwrap_or(path);
         format!("{}/emby/{path}", self.base_url)
     }
 }
+
+/// An HTTP agent with the configured timeouts.
+pub(crate) fn agent(http: &HttpOptions) -> ureq::Agent {
+    ureq::Agent::new_with_config(
+        ureq::config::Config::builder()
+            .timeout_connect(Some(http.connect_timeout))
+            .timeout_recv_response(Some(http.read_timeout))
+            .timeout_recv_body(Some(http.read_timeout))
+            .build(),
+    )
+}
+
+/// Opens WebSocket connections, see [`Client::websocket`].
+#[derive(Debug, Clone)]
+pub struct WebSocketConnector {
+    url: String,
+    api_key: String,
+    authorization: String,
+    connect_timeout: Duration,
+    /// Whether to reconnect after the connection drops, off with `--retries 0`
+    pub reconnect: bool,
+}
+
+impl WebSocketConnector {
+    /// Connect and complete the WebSocket handshake. Reads on the returned
+    /// socket time out after `read_timeout`, so callers can do other work
+    /// between messages.
+    pub fn connect(&self, read_timeout: Duration) -> Result<WebSocket> {
+        let mut request = self.url.as_str().into_client_request()?;
+        let headers = request.headers_mut();
+        for (name, value) in [
+            ("X-Emby-Token", &self.api_key),
+            ("X-Emby-Authorization", &self.authorization),
+        ] {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(name, value);
+            }
+        }
+
+        let uri = request.uri();
+        let host = uri.host().unwrap_or_default().to_string();
+        let port = uri
+            .port_u16()
+            .unwrap_or(if uri.scheme_str() == Some("wss") {
+                443
+            } else {
+                80
+            });
+
+        let stream = self.connect_tcp(&host, port)?;
+        stream.set_read_timeout(Some(self.connect_timeout))?;
+        let (socket, _response) =
+            tungstenite::client_tls(request, stream).map_err(|e| match e {
+                HandshakeError::Failure(e) => Error::from(e),
+                HandshakeError::Interrupted(_) => unreachable(io::ErrorKind::TimedOut.into()),
+            })?;
+
+        match socket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(read_timeout))?,
+            MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(Some(read_timeout))?,
+            _ => {}
+        }
+        Ok(socket)
+    }
+
+    fn connect_tcp(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no addresses for host");
+        for addr in (host, port).to_socket_addrs().map_err(unreachable)? {
+            match TcpStream::connect_timeout(&addr, self.connect_timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(unreachable(last_error))
+    }
+}
+
+fn unreachable(error: io::Error) -> Error {
+    Error::Unreachable(ureq::Error::Io(error))
+}
+
+/// Delay before retry number `attempt` (counting from 0).
+pub(crate) fn backoff(attempt: u32) -> Duration {
+    RETRY_BACKOFF
+        .saturating_mul(2_u32.saturating_pow(attempt))
+        .min(MAX_RETRY_BACKOFF)
+}
+
+/// Identifies the CLI to Emby, which tracks sessions and access tokens per
+/// device. The device ID must be stable per machine: logging in again from
+/// the same device revokes that device's previous token.
+fn authorization() -> String {
+    format!(
+        r#"Emby Client="emby-cli", Device="{}", DeviceId="{}", Version="{}""#,
+        hostname(),
+        device_id(),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+fn device_id() -> String {
+    format!("emby-cli-{}", hostname())
+}
+
+fn hostname() -> String {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_limit() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_RETRY_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn websocket_url_uses_ws_scheme() {
+        let client = Client::new(&Config {
+            api_url: "https://emby.example.com/".to_string(),
+            api_key: "secret".to_string(),
+            user_id: None,
+            http: HttpOptions::default(),
+        });
+        let connector = client.websocket("/embywebsocket");
+
+        assert!(
+            connector.url.starts_with(
+                "wss://emby.example.com/embywebsocket?api_key=secret&deviceId=emby-cli-"
+            )
+        );
+    }
+}
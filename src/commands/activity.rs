@@ -1,49 +1,399 @@
-use crate::cli::ActivityArgs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::{ActivityLogOptions, EventStream, Subscription};
+use crate::cli::{self, ActivityArgs, GlobalArgs};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::ticks;
-use crate::emby::types::ActivityLogResponse;
-use crate::error::Result;
-use crate::format::table;
-
-pub fn run(args: &ActivityArgs) -> Result<()> {
-    let config = Config::load()?;
+use crate::emby::types::ActivityLogEntry;
+use crate::emby::{ticks, time};
+use crate::error::{Error, Result};
+use crate::format::output::{self, OutputFormat};
+
+/// Entries fetched per request when filters may skip some of them.
+const PAGE_SIZE: u32 = 100;
+
+/// Most entries fetched per request, so large limits are paged.
+const MAX_PAGE_SIZE: u32 = 500;
+
+const HEADERS: [&str; 4] = ["Date", "Severity", "Name", "Overview"];
+
+pub fn run(global: &GlobalArgs, args: &ActivityArgs) -> Result<()> {
+    let filter = Filter::new(args, time::now())?;
+
+    if args.follow && !matches!(global.output, OutputFormat::Table | OutputFormat::Ndjson) {
+        return Err(Error::Usage(
+            "Follow mode only supports table and ndjson output".to_string(),
+        ));
+    }
+
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let limit = args.limit.to_string();
-    let response: ActivityLogResponse =
-        client.get_with_query("/System/ActivityLog/Entries", &[("Limit", limit.as_str())])?;
+    let entries = fetch_entries(&client, &filter, cli::result_limit(args.limit, args.all))?;
+
+    if args.follow {
+        return follow(&client, global.output, &filter, &entries, args.interval);
+    }
 
-    let entries = response.items.unwrap_or_default();
+    let rows: Vec<Vec<String>> = entries.iter().map(row).collect();
+    output::print_rows(global.output, &HEADERS, rows, "No activity found")?;
 
-    if entries.is_empty() {
-        println!("No activity found");
-        return Ok(());
+    Ok(())
+}
+
+/// Fetch up to `limit` matching entries (all of them for `None`), newest
+/// first, paging through the log when filters leave a page short.
+fn fetch_entries(
+    client: &Client,
+    filter: &Filter,
+    limit: Option<u32>,
+) -> Result<Vec<ActivityLogEntry>> {
+    let page_size = match limit {
+        Some(limit) if filter.is_server_side() => limit.min(MAX_PAGE_SIZE),
+        Some(limit) => limit.clamp(PAGE_SIZE, MAX_PAGE_SIZE),
+        None => MAX_PAGE_SIZE,
+    };
+
+    let mut matched = Vec::new();
+    let mut start_index = 0;
+
+    loop {
+        let page = client.activity_log().entries(&ActivityLogOptions {
+            limit: Some(page_size),
+            start_index: (start_index > 0).then_some(start_index),
+            min_date: filter.since.map(time::format_iso),
+        })?;
+
+        let count = page.len();
+        matched.extend(page.into_iter().filter(|e| filter.matches(e)));
+
+        let enough = limit.is_some_and(|limit| matched.len() >= limit as usize);
+        if enough || count < page_size as usize {
+            break;
+        }
+        start_index += page_size;
     }
 
-    let rows: Vec<Vec<String>> = entries
-        .iter()
-        .map(|e| {
-            let date = e
-                .date
-                .as_deref()
-                .map_or_else(String::new, ticks::format_premiere_date);
-            let severity = e.severity.as_deref().unwrap_or("").to_string();
-            let name = e.name.as_deref().unwrap_or("").to_string();
-            let overview = e
-                .short_overview
+    if let Some(limit) = limit {
+        matched.truncate(limit as usize);
+    }
+    Ok(matched)
+}
+
+fn follow(
+    client: &Client,
+    format: OutputFormat,
+    filter: &Filter,
+    entries: &[ActivityLogEntry],
+    interval: u64,
+) -> Result<()> {
+    // Start from the newest entry in the log, even if it was filtered out
+    let mut cursor = Cursor::newest(client)?;
+
+    print_follow(format, entries.iter().rev())?;
+
+    // Check as soon as the server announces new entries, polling as well in
+    // case its WebSocket is unavailable
+    let mut events = client.events().subscribe(&[Subscription::ActivityLog]).ok();
+
+    loop {
+        wait_for_entries(Duration::from_secs(interval), &mut events);
+
+        match fetch_new_entries(client, &mut cursor) {
+            Ok(new) => print_follow(format, new.iter().filter(|e| filter.matches(e)))?,
+            Err(e) => eprintln!("Error fetching activity: {e}"),
+        }
+    }
+}
+
+/// Sleep for `interval`, or less if `events` says there are new entries.
+/// Stops listening to `events` if the connection is lost.
+fn wait_for_entries(interval: Duration, events: &mut Option<EventStream>) {
+    let Some(stream) = events else {
+        thread::sleep(interval);
+        return;
+    };
+
+    let deadline = Instant::now() + interval;
+    while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+        match stream.next_timeout(timeout) {
+            Some(Ok(event)) if event.message_type == "ActivityLogEntry" => return,
+            Some(Ok(_)) => {}
+            Some(Err(_)) => {
+                *events = None;
+                thread::sleep(timeout);
+                return;
+            }
+            None => return,
+        }
+    }
+}
+
+/// Fetch entries newer than `cursor`, oldest first, and move the cursor past them.
+pub(crate) fn fetch_new_entries(
+    client: &Client,
+    cursor: &mut Cursor,
+) -> Result<Vec<ActivityLogEntry>> {
+    let min_date = cursor.date.map(time::format_iso);
+    let mut new = Vec::new();
+    let mut start_index = 0;
+
+    loop {
+        let page = client.activity_log().entries(&ActivityLogOptions {
+            limit: Some(PAGE_SIZE),
+            start_index: (start_index > 0).then_some(start_index),
+            min_date: min_date.clone(),
+        })?;
+
+        let count = page.len();
+        let before = new.len();
+        new.extend(page.into_iter().filter(|e| cursor.is_new(e)));
+
+        // Stop once a page reaches entries we've already seen
+        if count < PAGE_SIZE as usize || new.len() - before < count {
+            break;
+        }
+        start_index += PAGE_SIZE;
+    }
+
+    new.reverse();
+    for entry in &new {
+        cursor.advance(entry);
+    }
+
+    Ok(new)
+}
+
+fn print_follow<'a>(
+    format: OutputFormat,
+    entries: impl Iterator<Item = &'a ActivityLogEntry>,
+) -> Result<()> {
+    for entry in entries {
+        if format == OutputFormat::Ndjson {
+            println!(
+                "{}",
+                output::render_rows(format, &HEADERS, vec![row(entry)])?
+            );
+        } else {
+            println!("{}", format_line(entry));
+        }
+    }
+
+    Ok(())
+}
+
+fn row(entry: &ActivityLogEntry) -> Vec<String> {
+    let date = entry
+        .date
+        .as_deref()
+        .map_or_else(String::new, ticks::format_premiere_date);
+    let severity = entry.severity.as_deref().unwrap_or("").to_string();
+    let name = entry.name.as_deref().unwrap_or("").to_string();
+
+    vec![date, severity, name, overview(entry).to_string()]
+}
+
+/// Format an entry as a single log line, e.g.
+/// `2024-06-15 10:30:00  Info   josh logged in: Login from 192.168.1.100`.
+pub(crate) fn format_line(entry: &ActivityLogEntry) -> String {
+    let date = entry
+        .date
+        .as_deref()
+        .and_then(time::parse_timestamp)
+        .map_or_else(
+            || entry.date.clone().unwrap_or_default(),
+            time::format_datetime,
+        );
+    let severity = entry.severity.as_deref().unwrap_or("");
+    let name = entry.name.as_deref().unwrap_or("");
+
+    match overview(entry) {
+        "" => format!("{date}  {severity:<5}  {name}"),
+        overview => format!("{date}  {severity:<5}  {name}: {overview}"),
+    }
+}
+
+fn overview(entry: &ActivityLogEntry) -> &str {
+    entry
+        .short_overview
+        .as_deref()
+        .or(entry.overview.as_deref())
+        .unwrap_or("")
+}
+
+fn entry_time(entry: &ActivityLogEntry) -> Option<i64> {
+    entry.date.as_deref().and_then(time::parse_timestamp)
+}
+
+/// Filters from the command line.
+struct Filter {
+    since: Option<i64>,
+    until: Option<i64>,
+    severities: Vec<String>,
+    types: Vec<String>,
+}
+
+impl Filter {
+    fn new(args: &ActivityArgs, now: i64) -> Result<Self> {
+        let parse = |value: &Option<String>| {
+            value
                 .as_deref()
-                .or(e.overview.as_deref())
-                .unwrap_or("")
-                .to_string();
+                .map(|s| {
+                    time::parse_since(s, now).ok_or_else(|| {
+                        Error::Usage(format!(
+                            "Invalid time '{s}', expected a date like 2024-06-15, \
+                             \"2024-06-15 10:30\" or a duration like 30m, 2h or 7d"
+                        ))
+                    })
+                })
+                .transpose()
+        };
 
-            vec![date, severity, name, overview]
+        Ok(Self {
+            since: parse(&args.since)?,
+            until: parse(&args.until)?,
+            severities: args.severities.clone(),
+            types: args.types.clone(),
         })
-        .collect();
+    }
+
+    /// Whether the server applies every filter, so one page of `limit` is enough.
+    fn is_server_side(&self) -> bool {
+        self.until.is_none() && self.severities.is_empty() && self.types.is_empty()
+    }
 
-    println!(
-        "{}",
-        table::build_table(&["Date", "Severity", "Name", "Overview"], rows)
-    );
+    fn matches(&self, entry: &ActivityLogEntry) -> bool {
+        let matches_any = |wanted: &[String], value: Option<&str>| {
+            wanted.is_empty()
+                || value.is_some_and(|v| wanted.iter().any(|w| w.eq_ignore_ascii_case(v)))
+        };
 
-    Ok(())
+        let date = entry_time(entry);
+        let in_window = self
+            .since
+            .is_none_or(|since| date.is_some_and(|d| d >= since))
+            && self
+                .until
+                .is_none_or(|until| date.is_some_and(|d| d < until));
+
+        in_window
+            && matches_any(&self.severities, entry.severity.as_deref())
+            && matches_any(&self.types, entry.entry_type.as_deref())
+    }
+}
+
+/// The newest entry seen so far in follow mode.
+#[derive(Debug, Default)]
+pub(crate) struct Cursor {
+    id: Option<i64>,
+    date: Option<i64>,
+}
+
+impl Cursor {
+    /// A cursor at the newest entry in the log.
+    pub(crate) fn newest(client: &Client) -> Result<Self> {
+        let mut cursor = Self::default();
+        for entry in client.activity_log().entries(&ActivityLogOptions {
+            limit: Some(1),
+            ..ActivityLogOptions::default()
+        })? {
+            cursor.advance(&entry);
+        }
+        Ok(cursor)
+    }
+
+    fn is_new(&self, entry: &ActivityLogEntry) -> bool {
+        match (self.id, entry.id) {
+            (Some(last), Some(id)) => id > last,
+            _ => match (self.date, entry_time(entry)) {
+                (Some(last), Some(date)) => date > last,
+                (None, _) => true,
+                (Some(_), None) => false,
+            },
+        }
+    }
+
+    fn advance(&mut self, entry: &ActivityLogEntry) {
+        if let Some(id) = entry.id {
+            self.id = Some(self.id.map_or(id, |last| last.max(id)));
+        }
+        if let Some(date) = entry_time(entry) {
+            self.date = Some(self.date.map_or(date, |last| last.max(date)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, date: &str, severity: &str, entry_type: &str) -> ActivityLogEntry {
+        ActivityLogEntry {
+            id: Some(id),
+            name: Some(format!("Entry {id}")),
+            overview: None,
+            short_overview: None,
+            entry_type: Some(entry_type.to_string()),
+            date: Some(date.to_string()),
+            severity: Some(severity.to_string()),
+        }
+    }
+
+    fn filter() -> Filter {
+        Filter {
+            since: None,
+            until: None,
+            severities: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn filter_by_severity_and_type() {
+        let filter = Filter {
+            severities: vec!["error".to_string()],
+            types: vec!["AuthenticationFailed".to_string()],
+            ..filter()
+        };
+
+        let date = "2024-06-15T10:30:00Z";
+        assert!(filter.matches(&entry(1, date, "Error", "AuthenticationFailed")));
+        assert!(!filter.matches(&entry(2, date, "Info", "AuthenticationFailed")));
+        assert!(!filter.matches(&entry(3, date, "Error", "VideoPlayback")));
+    }
+
+    #[test]
+    fn filter_by_time_window() {
+        let filter = Filter {
+            since: time::parse_timestamp("2024-06-14"),
+            until: time::parse_timestamp("2024-06-15"),
+            ..filter()
+        };
+
+        assert!(filter.matches(&entry(1, "2024-06-14T02:00:00Z", "Info", "X")));
+        assert!(!filter.matches(&entry(2, "2024-06-15T10:30:00Z", "Info", "X")));
+        assert!(!filter.matches(&entry(3, "2024-06-13T20:15:00Z", "Info", "X")));
+    }
+
+    #[test]
+    fn cursor_tracks_newest_entry() {
+        let mut cursor = Cursor::default();
+        let first = entry(10, "2024-06-15T10:30:00Z", "Info", "X");
+        assert!(cursor.is_new(&first));
+
+        cursor.advance(&first);
+        assert!(!cursor.is_new(&first));
+        assert!(!cursor.is_new(&entry(9, "2024-06-15T10:31:00Z", "Info", "X")));
+        assert!(cursor.is_new(&entry(11, "2024-06-15T10:30:00Z", "Info", "X")));
+    }
+
+    #[test]
+    fn format_line_includes_time_and_overview() {
+        let mut e = entry(1, "2024-06-15T10:30:00.0000000Z", "Info", "X");
+        e.short_overview = Some("Login from 192.168.1.100".to_string());
+
+        assert_eq!(
+            format_line(&e),
+            "2024-06-15 10:30:00  Info   Entry 1: Login from 192.168.1.100"
+        );
+    }
 }
@@ -0,0 +1,736 @@
+use std::io::{self, IsTerminal};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::api::{ActivityLogOptions, PlaystateCommand, Subscription};
+use crate::cli::{DashboardArgs, GlobalArgs};
+use crate::client::Client;
+use crate::commands::activity;
+use crate::commands::tui::{Line, Screen, Style, Target, TerminalGuard};
+use crate::emby::tasks;
+use crate::emby::types::{ActivityLogEntry, ServerEvent, Session, SystemInfo, TaskInfo};
+use crate::error::{Error, Result};
+use crate::format::playing::{self, PlayingEntry};
+use crate::format::progress;
+
+/// Activity log entries to fetch.
+const ACTIVITY_ENTRIES: u32 = 50;
+
+/// Width of progress bars, excluding the brackets and percentage.
+const BAR_WIDTH: usize = 20;
+
+/// Width of the task name column.
+const TASK_WIDTH: usize = 36;
+
+/// How long a message replaces the key help.
+const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+const HELP: &str = "Tab switch pane | Up/Down select | s stop | p pause | r run task | q quit";
+
+pub fn run(global: &GlobalArgs, args: &DashboardArgs) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(Error::Usage(
+            "emby dashboard needs a terminal, use emby playing, tasks or activity in scripts"
+                .to_string(),
+        ));
+    }
+
+    let config = global.load_config()?;
+    let client = Arc::new(Client::new(&config));
+    let interval = Duration::from_secs(args.interval);
+
+    let mut stdout = io::stdout();
+    let _guard = TerminalGuard::enter(Target::Stdout)?;
+
+    // Live updates from the server, with polling as a fallback
+    let mut events = client.events().subscribe(&Subscription::ALL).ok();
+
+    let mut dashboard = Dashboard::new(client.api_url().to_string());
+    let mut screen = Screen::default();
+    let mut last_refresh: Option<Instant> = None;
+    let mut pending: Option<Receiver<Snapshot>> = None;
+
+    loop {
+        while let Some(event) = events.as_ref().and_then(|e| e.next_timeout(Duration::ZERO)) {
+            match event {
+                Ok(event) => {
+                    if dashboard.apply_event(event) {
+                        last_refresh = None;
+                    }
+                }
+                Err(e) => {
+                    dashboard.show_message(format!("Live updates stopped: {e}"));
+                    events = None;
+                }
+            }
+        }
+
+        // Fetch in the background so keys still work while the server is slow
+        // or down
+        if let Some(snapshot) = pending.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            dashboard.apply_snapshot(snapshot);
+            pending = None;
+        }
+        if pending.is_none() && last_refresh.is_none_or(|at| at.elapsed() >= interval) {
+            pending = Some(spawn_fetch(&client, dashboard.info.is_none()));
+            last_refresh = Some(Instant::now());
+        }
+
+        dashboard.expire_message(Instant::now());
+        let (width, height) = terminal::size()?;
+        screen.draw(&mut stdout, dashboard.lines(width, height), (width, height))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match dashboard.handle_key(key) {
+                Outcome::Continue => {}
+                Outcome::Quit => break,
+                Outcome::Act(action) => {
+                    dashboard.show_message(run_action(&client, &action));
+                    last_refresh = None;
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything a refresh fetches, each kept separately so one failed
+/// request doesn't hide the others.
+struct Snapshot {
+    info: Option<Result<SystemInfo>>,
+    sessions: Result<Vec<Session>>,
+    tasks: Result<Vec<TaskInfo>>,
+    activity: Result<Vec<ActivityLogEntry>>,
+}
+
+/// Fetch a [`Snapshot`] on another thread, including the server info when
+/// `with_info` is set.
+fn spawn_fetch(client: &Arc<Client>, with_info: bool) -> Receiver<Snapshot> {
+    let client = Arc::clone(client);
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(Snapshot {
+            info: with_info.then(|| client.system().info()),
+            sessions: client.sessions().list(),
+            tasks: client.scheduled_tasks().list(),
+            activity: client.activity_log().entries(&ActivityLogOptions {
+                limit: Some(ACTIVITY_ENTRIES),
+                ..ActivityLogOptions::default()
+            }),
+        });
+    });
+    rx
+}
+
+fn run_action(client: &Client, action: &Action) -> String {
+    let result = match action {
+        Action::Stop { session_id, .. } => client
+            .sessions()
+            .playing(session_id, PlaystateCommand::Stop),
+        Action::TogglePause {
+            session_id, paused, ..
+        } => {
+            let command = if *paused {
+                PlaystateCommand::Unpause
+            } else {
+                PlaystateCommand::Pause
+            };
+            client.sessions().playing(session_id, command)
+        }
+        Action::RunTask { task_id, .. } => client.scheduled_tasks().run(task_id),
+    };
+
+    match result {
+        Ok(()) => action.description(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pane {
+    Sessions,
+    Tasks,
+    Activity,
+}
+
+const PANES: [Pane; 3] = [Pane::Sessions, Pane::Tasks, Pane::Activity];
+
+/// An action chosen from the keyboard.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Stop {
+        session_id: String,
+        label: String,
+    },
+    TogglePause {
+        session_id: String,
+        paused: bool,
+        label: String,
+    },
+    RunTask {
+        task_id: String,
+        name: String,
+    },
+}
+
+impl Action {
+    fn description(&self) -> String {
+        match self {
+            Self::Stop { label, .. } => format!("Stopped {label}"),
+            Self::TogglePause {
+                paused: true,
+                label,
+                ..
+            } => format!("Resumed {label}"),
+            Self::TogglePause { label, .. } => format!("Paused {label}"),
+            Self::RunTask { name, .. } => format!("Started {name}"),
+        }
+    }
+}
+
+/// What a key press asks the dashboard loop to do.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Quit,
+    Act(Action),
+}
+
+struct Dashboard {
+    api_url: String,
+    info: Option<SystemInfo>,
+    sessions: Vec<PlayingEntry>,
+    tasks: Vec<TaskInfo>,
+    activity: Vec<ActivityLogEntry>,
+    pane: Pane,
+    /// Selected row in each pane, in the order of [`PANES`]
+    selected: [usize; 3],
+    /// Shown instead of the key help until it expires, with when it was set
+    message: Option<(String, Instant)>,
+}
+
+impl Dashboard {
+    fn new(api_url: String) -> Self {
+        Self {
+            api_url,
+            info: None,
+            sessions: Vec::new(),
+            tasks: Vec::new(),
+            activity: Vec::new(),
+            pane: Pane::Sessions,
+            selected: [0; 3],
+            message: None,
+        }
+    }
+
+    /// Show what a refresh fetched, keeping what was shown before for
+    /// anything that failed.
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let mut errors = Vec::new();
+
+        match snapshot.info {
+            Some(Ok(info)) => self.info = Some(info),
+            Some(Err(e)) => errors.push(e),
+            None => {}
+        }
+        match snapshot.sessions {
+            Ok(sessions) => self.set_sessions(playing::build_entries(&sessions)),
+            Err(e) => errors.push(e),
+        }
+        match snapshot.tasks {
+            Ok(tasks) => self.set_tasks(tasks),
+            Err(e) => errors.push(e),
+        }
+        match snapshot.activity {
+            Ok(entries) => self.set_activity(entries),
+            Err(e) => errors.push(e),
+        }
+
+        if let Some(e) = errors.first() {
+            self.show_message(format!("Error: {e}"));
+        }
+    }
+
+    fn show_message(&mut self, message: String) {
+        self.message = Some((message, Instant::now()));
+    }
+
+    /// Bring the key help back once the message has been shown for
+    /// [`MESSAGE_DURATION`].
+    fn expire_message(&mut self, now: Instant) {
+        if self
+            .message
+            .as_ref()
+            .is_some_and(|(_, shown)| now.duration_since(*shown) >= MESSAGE_DURATION)
+        {
+            self.message = None;
+        }
+    }
+
+    /// Update from an event the server pushed. Returns whether everything
+    /// should be fetched again, e.g. for new activity.
+    fn apply_event(&mut self, event: ServerEvent) -> bool {
+        match event.message_type.as_str() {
+            "Sessions" => {
+                if let Ok(sessions) = serde_json::from_value::<Vec<Session>>(event.data) {
+                    self.set_sessions(playing::build_entries(&sessions));
+                }
+                false
+            }
+            "ScheduledTasksInfo" => {
+                if let Ok(tasks) = serde_json::from_value(event.data) {
+                    self.set_tasks(tasks);
+                }
+                false
+            }
+            "ActivityLogEntry" | "ScheduledTaskEnded" => true,
+            _ => false,
+        }
+    }
+
+    fn set_sessions(&mut self, sessions: Vec<PlayingEntry>) {
+        self.sessions = sessions;
+        self.clamp_selection();
+    }
+
+    /// Visible tasks, running ones first.
+    fn set_tasks(&mut self, tasks: Vec<TaskInfo>) {
+        let mut tasks: Vec<TaskInfo> = tasks
+            .into_iter()
+            .filter(|t| !t.is_hidden.unwrap_or(false))
+            .collect();
+        tasks.sort_by_key(|t| !tasks::is_running(t));
+        self.tasks = tasks;
+        self.clamp_selection();
+    }
+
+    fn set_activity(&mut self, entries: Vec<ActivityLogEntry>) {
+        self.activity = entries;
+        self.clamp_selection();
+    }
+
+    fn rows(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Sessions => self.sessions.len(),
+            Pane::Tasks => self.tasks.len(),
+            Pane::Activity => self.activity.len(),
+        }
+    }
+
+    fn pane_index(pane: Pane) -> usize {
+        PANES.iter().position(|p| *p == pane).unwrap_or(0)
+    }
+
+    fn clamp_selection(&mut self) {
+        for pane in PANES {
+            let last = self.rows(pane).saturating_sub(1);
+            let selected = &mut self.selected[Self::pane_index(pane)];
+            *selected = (*selected).min(last);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Outcome::Quit;
+        }
+
+        let index = Self::pane_index(self.pane);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Outcome::Quit,
+            KeyCode::Tab | KeyCode::Right => self.pane = PANES[(index + 1) % PANES.len()],
+            KeyCode::BackTab | KeyCode::Left => {
+                self.pane = PANES[(index + PANES.len() - 1) % PANES.len()];
+            }
+            KeyCode::Char(c @ '1'..='3') => {
+                self.pane = PANES[usize::from(c as u8 - b'1')];
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected[index] = self.selected[index].saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let last = self.rows(self.pane).saturating_sub(1);
+                self.selected[index] = (self.selected[index] + 1).min(last);
+            }
+            KeyCode::Char('s') => {
+                return self.session_action(|entry| Action::Stop {
+                    session_id: entry.session_id.clone(),
+                    label: session_label(entry),
+                });
+            }
+            KeyCode::Char('p') => {
+                return self.session_action(|entry| Action::TogglePause {
+                    session_id: entry.session_id.clone(),
+                    paused: entry.state == "Paused",
+                    label: session_label(entry),
+                });
+            }
+            KeyCode::Char('r') => {
+                let Some(task) = self.selected_task() else {
+                    return Outcome::Continue;
+                };
+                match task.id.as_deref().filter(|id| !id.is_empty()) {
+                    Some(id) => {
+                        return Outcome::Act(Action::RunTask {
+                            task_id: id.to_string(),
+                            name: task.name.clone().unwrap_or_default(),
+                        });
+                    }
+                    None => self.show_message("Task has no ID".to_string()),
+                }
+            }
+            _ => {}
+        }
+
+        Outcome::Continue
+    }
+
+    /// Act on the selected session, unless the server didn't give it an ID.
+    fn session_action(&mut self, action: impl FnOnce(&PlayingEntry) -> Action) -> Outcome {
+        let Some(entry) = self.selected_session() else {
+            return Outcome::Continue;
+        };
+        if entry.session_id.is_empty() {
+            self.show_message("Session has no ID".to_string());
+            return Outcome::Continue;
+        }
+        Outcome::Act(action(entry))
+    }
+
+    fn selected_session(&self) -> Option<&PlayingEntry> {
+        (self.pane == Pane::Sessions)
+            .then(|| self.sessions.get(self.selected[0]))
+            .flatten()
+    }
+
+    fn selected_task(&self) -> Option<&TaskInfo> {
+        (self.pane == Pane::Tasks)
+            .then(|| self.tasks.get(self.selected[1]))
+            .flatten()
+    }
+
+    /// The screen's lines: server info, then the three panes sharing the
+    /// height, then the status line.
+    fn lines(&self, width: u16, height: u16) -> Vec<Line> {
+        let width = usize::from(width);
+        let height = usize::from(height);
+        let mut lines = vec![Line::new(self.header(), Style::Title), Line::blank()];
+
+        // Leave room for the header, a blank line and the status line
+        let available = height.saturating_sub(4);
+        let pane_height = available / PANES.len();
+        for (i, pane) in PANES.iter().enumerate() {
+            let rows = if i == PANES.len() - 1 {
+                available - pane_height * (PANES.len() - 1)
+            } else {
+                pane_height
+            };
+            self.pane_lines(*pane, rows, width, &mut lines);
+        }
+
+        lines.push(Line::blank());
+        lines.push(Line::new(
+            self.message
+                .as_ref()
+                .map_or(HELP, |(text, _)| text.as_str()),
+            Style::Normal,
+        ));
+        lines
+    }
+
+    fn header(&self) -> String {
+        let Some(info) = &self.info else {
+            return self.api_url.clone();
+        };
+
+        let mut header = format!(
+            "{} - Emby {} on {} - {}",
+            info.server_name.as_deref().unwrap_or("Emby"),
+            info.version.as_deref().unwrap_or("Unknown"),
+            info.operating_system_display_name
+                .as_deref()
+                .unwrap_or("Unknown"),
+            self.api_url
+        );
+        if info.has_update_available.unwrap_or(false) {
+            header.push_str(" - update available");
+        }
+        header
+    }
+
+    /// A pane's title and as many of its rows as fit in `height` lines,
+    /// scrolled so the selected row is visible.
+    fn pane_lines(&self, pane: Pane, height: usize, width: usize, lines: &mut Vec<Line>) {
+        if height == 0 {
+            return;
+        }
+
+        let start = lines.len();
+        let index = Self::pane_index(pane);
+        let marker = if pane == self.pane { ">" } else { " " };
+        let title = match pane {
+            Pane::Sessions => format!("Now playing ({})", self.sessions.len()),
+            Pane::Tasks => {
+                let running = self.tasks.iter().filter(|t| tasks::is_running(t)).count();
+                format!("Scheduled tasks ({running} running)")
+            }
+            Pane::Activity => "Recent activity".to_string(),
+        };
+        lines.push(Line::new(
+            format!("{marker}{} {title}", index + 1),
+            Style::Title,
+        ));
+
+        let rows: Vec<String> = match pane {
+            Pane::Sessions => self.sessions.iter().map(session_line).collect(),
+            Pane::Tasks => self.tasks.iter().map(task_line).collect(),
+            Pane::Activity => self.activity.iter().map(activity::format_line).collect(),
+        };
+
+        let visible = height - 1;
+        if rows.is_empty() && visible > 0 {
+            let empty = match pane {
+                Pane::Sessions => "Nothing is playing",
+                Pane::Tasks => "No scheduled tasks",
+                Pane::Activity => "No activity",
+            };
+            lines.push(Line::new(format!("  {empty}"), Style::Normal));
+        }
+
+        let selected = self.selected[index];
+        let offset = selected.saturating_sub(visible.saturating_sub(1));
+        for (i, row) in rows.iter().enumerate().skip(offset).take(visible) {
+            if pane == self.pane && i == selected {
+                lines.push(Line::new(
+                    format!("{:<width$}", format!("  {row}")),
+                    Style::Selected,
+                ));
+            } else {
+                lines.push(Line::new(format!("  {row}"), Style::Normal));
+            }
+        }
+
+        // Keep the next pane in place when this one has fewer rows
+        let used = lines.len() - start;
+        lines.extend((used..height).map(|_| Line::blank()));
+    }
+}
+
+fn session_label(entry: &PlayingEntry) -> String {
+    format!("{} on {}", entry.name, entry.device)
+}
+
+/// e.g. `Playing [#####---------------]  25% josh on Living Room: Heat`
+fn session_line(entry: &PlayingEntry) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let bar = progress::progress_bar(entry.progress_percent as f64, BAR_WIDTH);
+    format!(
+        "{:<7} {bar} {} on {}: {}",
+        entry.state, entry.user, entry.device, entry.name
+    )
+}
+
+/// e.g. `Scan media library   Running [###-----------------]  15%`, or the
+/// last result for tasks that aren't running.
+fn task_line(task: &TaskInfo) -> String {
+    let name = task.name.as_deref().unwrap_or("");
+    let state = task.state.as_deref().unwrap_or("");
+
+    if tasks::is_running(task) {
+        let percent = task.current_progress_percentage.unwrap_or(0.0);
+        return format!(
+            "{name:<TASK_WIDTH$} {state:<10} {}",
+            progress::progress_bar(percent, BAR_WIDTH)
+        );
+    }
+
+    let last = task
+        .last_execution_result
+        .as_ref()
+        .and_then(|r| r.status.as_deref())
+        .unwrap_or("");
+    format!("{name:<TASK_WIDTH$} {state:<10} {last}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn dashboard() -> Dashboard {
+        let sessions: Vec<Session> = serde_json::from_value(serde_json::json!([
+            {
+                "Id": "session-1",
+                "UserName": "josh",
+                "DeviceName": "Living Room",
+                "NowPlayingItem": {"Name": "Heat", "Type": "Movie", "RunTimeTicks": 36_000_000_000_u64},
+                "PlayState": {"PositionTicks": 9_000_000_000_u64, "IsPaused": true}
+            },
+            {"Id": "session-2", "UserName": "idle"}
+        ]))
+        .unwrap();
+        let tasks = serde_json::from_value(serde_json::json!([
+            {"Name": "Clean cache", "Id": "task-1", "State": "Idle"},
+            {"Name": "Scan media library", "Id": "task-2", "State": "Running",
+             "CurrentProgressPercentage": 15.0},
+            {"Name": "Hidden", "Id": "task-3", "State": "Idle", "IsHidden": true}
+        ]))
+        .unwrap();
+
+        let mut dashboard = Dashboard::new("http://emby.local:8096".to_string());
+        dashboard.set_sessions(playing::build_entries(&sessions));
+        dashboard.set_tasks(tasks);
+        dashboard
+    }
+
+    #[test]
+    fn tab_cycles_panes() {
+        let mut dashboard = dashboard();
+        dashboard.handle_key(key(KeyCode::Tab));
+        assert_eq!(dashboard.pane, Pane::Tasks);
+        dashboard.handle_key(key(KeyCode::BackTab));
+        dashboard.handle_key(key(KeyCode::BackTab));
+        assert_eq!(dashboard.pane, Pane::Activity);
+        dashboard.handle_key(key(KeyCode::Char('1')));
+        assert_eq!(dashboard.pane, Pane::Sessions);
+    }
+
+    #[test]
+    fn session_actions() {
+        let mut dashboard = dashboard();
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('s'))),
+            Outcome::Act(Action::Stop {
+                session_id: "session-1".to_string(),
+                label: "Heat on Living Room".to_string(),
+            })
+        );
+
+        let Outcome::Act(action) = dashboard.handle_key(key(KeyCode::Char('p'))) else {
+            panic!("expected an action");
+        };
+        assert_eq!(action.description(), "Resumed Heat on Living Room");
+
+        // Tasks can only be run from the tasks pane
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('r'))),
+            Outcome::Continue
+        );
+    }
+
+    #[test]
+    fn actions_need_an_id() {
+        let mut dashboard = dashboard();
+        dashboard.sessions[0].session_id = String::new();
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('s'))),
+            Outcome::Continue
+        );
+        assert_eq!(dashboard.message.as_ref().unwrap().0, "Session has no ID");
+
+        dashboard.set_tasks(
+            serde_json::from_value(serde_json::json!([{"Name": "Clean cache"}])).unwrap(),
+        );
+        dashboard.handle_key(key(KeyCode::Char('2')));
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('r'))),
+            Outcome::Continue
+        );
+        assert_eq!(dashboard.message.as_ref().unwrap().0, "Task has no ID");
+    }
+
+    #[test]
+    fn running_tasks_come_first() {
+        let mut dashboard = dashboard();
+        assert_eq!(dashboard.tasks.len(), 2);
+
+        dashboard.handle_key(key(KeyCode::Char('2')));
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('r'))),
+            Outcome::Act(Action::RunTask {
+                task_id: "task-2".to_string(),
+                name: "Scan media library".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn pushed_tasks_replace_the_list() {
+        let mut dashboard = dashboard();
+        let refresh = dashboard.apply_event(ServerEvent {
+            message_type: "ScheduledTasksInfo".to_string(),
+            data: serde_json::json!([{"Name": "Clean cache", "Id": "task-1", "State": "Running"}]),
+        });
+
+        assert!(!refresh);
+        assert_eq!(dashboard.tasks.len(), 1);
+        assert!(tasks::is_running(&dashboard.tasks[0]));
+        assert!(dashboard.apply_event(ServerEvent {
+            message_type: "ActivityLogEntry".to_string(),
+            data: serde_json::json!([]),
+        }));
+    }
+
+    #[test]
+    fn lines_fill_the_screen() {
+        let dashboard = dashboard();
+        let lines = dashboard.lines(100, 16);
+
+        assert_eq!(lines.len(), 16);
+        assert_eq!(lines[0].text, "http://emby.local:8096");
+        assert_eq!(lines[2].text, ">1 Now playing (1)");
+        assert_eq!(lines[3].style, Style::Selected);
+        assert_eq!(
+            lines[3].text.trim_end(),
+            "  Paused  [#####---------------]  25% josh on Living Room: Heat"
+        );
+        assert_eq!(lines[6].text, " 2 Scheduled tasks (1 running)");
+        assert!(lines[7].text.contains("Scan media library"));
+        assert_eq!(lines[10].text, " 3 Recent activity");
+        assert_eq!(lines[11].text, "  No activity");
+        assert_eq!(lines[15].text, HELP);
+    }
+
+    #[test]
+    fn failed_fetches_keep_what_was_shown() {
+        let mut dashboard = dashboard();
+        dashboard.apply_snapshot(Snapshot {
+            info: None,
+            sessions: Err(Error::Server("down".to_string())),
+            tasks: Ok(Vec::new()),
+            activity: Ok(Vec::new()),
+        });
+
+        assert_eq!(dashboard.sessions.len(), 1);
+        assert!(dashboard.tasks.is_empty());
+        assert!(dashboard.message.as_ref().unwrap().0.contains("down"));
+    }
+
+    #[test]
+    fn messages_expire() {
+        let mut dashboard = dashboard();
+        dashboard.show_message("Stopped Heat on Living Room".to_string());
+        assert_eq!(
+            dashboard.lines(100, 16)[15].text,
+            "Stopped Heat on Living Room"
+        );
+
+        dashboard.expire_message(Instant::now());
+        assert!(dashboard.message.is_some());
+        dashboard.expire_message(Instant::now() + MESSAGE_DURATION);
+        assert_eq!(dashboard.lines(100, 16)[15].text, HELP);
+    }
+}
@@ -1,20 +1,27 @@
+use crate::cli::{
+    DevicesArgs, DevicesCommand, DevicesDeleteArgs, DevicesPruneArgs, DevicesRenameArgs, GlobalArgs,
+};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::types::DevicesResponse;
-use crate::error::Result;
-use crate::format::table;
+use crate::commands::prompt;
+use crate::emby::types::Device;
+use crate::emby::{devices, ticks, time};
+use crate::error::{Error, Result};
+use crate::format::output;
 
-pub fn run() -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs, args: &DevicesArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let response: DevicesResponse = client.get("/Devices")?;
 
-    let devices = response.items.unwrap_or_default();
-
-    if devices.is_empty() {
-        println!("No devices found");
-        return Ok(());
+    match &args.command {
+        None => list_devices(global, &client),
+        Some(DevicesCommand::Delete(delete_args)) => delete_device(&client, delete_args),
+        Some(DevicesCommand::Rename(rename_args)) => rename_device(&client, rename_args),
+        Some(DevicesCommand::Prune(prune_args)) => prune_devices(&client, prune_args),
     }
+}
+
+fn list_devices(global: &GlobalArgs, client: &Client) -> Result<()> {
+    let devices = client.devices().list()?;
 
     let rows: Vec<Vec<String>> = devices
         .iter()
@@ -29,18 +36,122 @@ pub fn run() -> Result<()> {
                 d.ip_address.as_deref().unwrap_or("").to_string(),
                 d.last_user_name.as_deref().unwrap_or("").to_string(),
                 app,
+                last_active(d),
                 d.id.as_deref().unwrap_or("").to_string(),
             ]
         })
         .collect();
 
+    output::print_rows(
+        global.output,
+        &[
+            "Name",
+            "IP Address",
+            "Last User",
+            "App (Version)",
+            "Last Active",
+            "ID",
+        ],
+        rows,
+        "No devices found",
+    )?;
+
+    Ok(())
+}
+
+fn delete_device(client: &Client, args: &DevicesDeleteArgs) -> Result<()> {
+    let device = devices::find_device(client, &args.device)?;
+    let id = device_id(&device)?;
+    let name = device.name.as_deref().unwrap_or(id);
+
+    if !args.yes && !prompt::confirm(&format!("Delete device {name} ({id})?"))? {
+        println!("Not deleting {name}");
+        return Ok(());
+    }
+
+    client.devices().delete(id)?;
+    println!("Deleted device {name} ({id})");
+    Ok(())
+}
+
+fn rename_device(client: &Client, args: &DevicesRenameArgs) -> Result<()> {
+    let device = devices::find_device(client, &args.device)?;
+    let id = device_id(&device)?;
+
+    client.devices().rename(id, &args.new_name)?;
     println!(
-        "{}",
-        table::build_table(
-            &["Name", "IP Address", "Last User", "App (Version)", "ID"],
-            rows
-        )
+        "Renamed device {} to {}",
+        device.name.as_deref().unwrap_or(id),
+        args.new_name
     );
+    Ok(())
+}
 
+fn prune_devices(client: &Client, args: &DevicesPruneArgs) -> Result<()> {
+    // A zero or negative age would put the cutoff at or after now and
+    // select every device
+    let cutoff = time::parse_duration(&args.older_than)
+        .filter(|age| *age > 0)
+        .and_then(|age| time::now().checked_sub(age))
+        .ok_or_else(|| {
+            Error::Usage(format!(
+                "Invalid duration '{}', expected e.g. 30d, 12w or 6h",
+                args.older_than
+            ))
+        })?;
+
+    let all = client.devices().list()?;
+    let stale = devices::stale_devices(&all, cutoff);
+
+    if stale.is_empty() {
+        println!("No devices inactive for {}", args.older_than);
+        return Ok(());
+    }
+
+    let verb = if args.dry_run {
+        "Would delete"
+    } else {
+        "Deleting"
+    };
+    for device in &stale {
+        println!(
+            "{verb} {} ({}), last active {}",
+            device.name.as_deref().unwrap_or(""),
+            device.id.as_deref().unwrap_or(""),
+            last_active(device)
+        );
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let count = match stale.len() {
+        1 => "1 device".to_string(),
+        n => format!("{n} devices"),
+    };
+    if !args.yes && !prompt::confirm(&format!("Delete {count}?"))? {
+        println!("Not deleting any devices");
+        return Ok(());
+    }
+
+    for device in &stale {
+        client.devices().delete(device_id(device)?)?;
+    }
+    println!("Deleted {count}");
     Ok(())
 }
+
+fn last_active(device: &Device) -> String {
+    device
+        .date_last_activity
+        .as_deref()
+        .map_or_else(String::new, ticks::format_premiere_date)
+}
+
+fn device_id(device: &Device) -> Result<&str> {
+    device
+        .id
+        .as_deref()
+        .ok_or_else(|| Error::Server("Server did not return a device ID".to_string()))
+}
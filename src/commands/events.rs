@@ -0,0 +1,37 @@
+use crate::api::Subscription;
+use crate::cli::{EventsArgs, GlobalArgs};
+use crate::client::Client;
+use crate::error::{Error, Result};
+use crate::format::output::OutputFormat;
+
+pub fn run(global: &GlobalArgs, args: &EventsArgs) -> Result<()> {
+    if !matches!(global.output, OutputFormat::Table | OutputFormat::Ndjson) {
+        return Err(Error::Usage("emby events only writes ndjson".to_string()));
+    }
+
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let events = client.events().subscribe(&Subscription::ALL)?;
+
+    let mut printed = 0;
+    for event in events {
+        let event = event?;
+        if !args.types.is_empty()
+            && !args
+                .types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&event.message_type))
+        {
+            continue;
+        }
+
+        println!("{}", serde_json::to_string(&event)?);
+
+        printed += 1;
+        if args.count.is_some_and(|count| printed >= count) {
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -1,9 +1,9 @@
 use std::net::UdpSocket;
 use std::time::Duration;
 
-use crate::cli::FindServerArgs;
+use crate::cli::{FindServerArgs, GlobalArgs};
 use crate::error::Result;
-use crate::format::table;
+use crate::format::output;
 
 const DISCOVERY_PORT: u16 = 7359;
 const DISCOVERY_MSG: &[u8] = b"who is EmbyServer?";
@@ -16,7 +16,7 @@ struct DiscoveryResponse {
     name: Option<String>,
 }
 
-pub fn run(args: &FindServerArgs) -> Result<()> {
+pub fn run(global: &GlobalArgs, args: &FindServerArgs) -> Result<()> {
     let timeout = Duration::from_secs(args.timeout);
 
     let socket = UdpSocket::bind("0.0.0.0:0")?;
@@ -48,11 +48,6 @@ pub fn run(args: &FindServerArgs) -> Result<()> {
         }
     }
 
-    if servers.is_empty() {
-        println!("No Emby servers found on the local network");
-        return Ok(());
-    }
-
     let rows: Vec<Vec<String>> = servers
         .iter()
         .map(|s| {
@@ -64,7 +59,12 @@ pub fn run(args: &FindServerArgs) -> Result<()> {
         })
         .collect();
 
-    println!("{}", table::build_table(&["Name", "Address", "ID"], rows));
+    output::print_rows(
+        global.output,
+        &["Name", "Address", "ID"],
+        rows,
+        "No Emby servers found on the local network",
+    )?;
 
     Ok(())
 }
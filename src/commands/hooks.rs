@@ -0,0 +1,645 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::api::Subscription;
+use crate::cli::{GlobalArgs, HooksArgs, HooksCommand, HooksRunArgs};
+use crate::client::{self, Client};
+use crate::commands::activity::{self, Cursor};
+use crate::emby::types::{ActivityLogEntry, BaseItemDto, ServerEvent, Session};
+use crate::error::{Error, Result};
+
+pub fn run(global: &GlobalArgs, args: &HooksArgs) -> Result<()> {
+    match &args.command {
+        HooksCommand::Run(run_args) => run_hooks(global, run_args),
+    }
+}
+
+fn run_hooks(global: &GlobalArgs, args: &HooksRunArgs) -> Result<()> {
+    let hooks = read_hooks_file(&args.config)?;
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let agent = client::agent(&config.http);
+
+    // Only ask for the updates some hook needs
+    let wants = |kinds: &[EventKind]| hooks.iter().any(|h| kinds.contains(&h.event));
+    let mut subscriptions = Vec::new();
+    if wants(&[EventKind::PlaybackStarted, EventKind::PlaybackStopped]) {
+        subscriptions.push(Subscription::Sessions);
+    }
+    let activity = wants(&[EventKind::TaskFailed, EventKind::UserLockedOut]);
+    if activity {
+        subscriptions.push(Subscription::ActivityLog);
+    }
+
+    let events = client.events().subscribe(&subscriptions)?;
+    let mut tracker = Tracker::new(if activity {
+        Some(Cursor::newest(&client)?)
+    } else {
+        None
+    });
+
+    eprintln!(
+        "Running {} hook{}, waiting for events",
+        hooks.len(),
+        if hooks.len() == 1 { "" } else { "s" }
+    );
+
+    for event in events {
+        let hook_events = match tracker.update(&client, event?) {
+            Ok(hook_events) => hook_events,
+            Err(e) => {
+                eprintln!("Error fetching event details: {e}");
+                continue;
+            }
+        };
+
+        for hook_event in hook_events {
+            println!("{}: {}", hook_event.kind.name(), hook_event.var("summary"));
+
+            for (i, hook) in hooks.iter().enumerate() {
+                if hook.event != hook_event.kind {
+                    continue;
+                }
+                if args.dry_run {
+                    println!("  Would run hook {}", i + 1);
+                } else if let Err(e) = hook.run(&agent, &hook_event) {
+                    eprintln!("Hook {} ({}) failed: {e}", i + 1, hook.event.name());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Events hooks can match, named as in the hooks file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    PlaybackStarted,
+    PlaybackStopped,
+    ItemAdded,
+    TaskFailed,
+    UserLockedOut,
+}
+
+impl EventKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::PlaybackStarted => "playback_started",
+            Self::PlaybackStopped => "playback_stopped",
+            Self::ItemAdded => "item_added",
+            Self::TaskFailed => "task_failed",
+            Self::UserLockedOut => "user_locked_out",
+        }
+    }
+
+    /// Variables for templates and commands, besides `event` and `summary`.
+    fn variables(self) -> &'static [&'static str] {
+        match self {
+            Self::PlaybackStarted | Self::PlaybackStopped => &[
+                "user",
+                "device",
+                "client",
+                "item",
+                "item_type",
+                "item_id",
+                "session_id",
+            ],
+            Self::ItemAdded => &["item", "item_type", "item_id", "series", "year"],
+            Self::TaskFailed | Self::UserLockedOut => &["name", "overview", "severity", "date"],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HooksFile {
+    #[serde(rename = "hook", default)]
+    hooks: Vec<Hook>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Hook {
+    event: EventKind,
+    /// Shell command, run with the event's variables as `EMBY_*` environment variables
+    command: Option<String>,
+    /// Webhook to POST JSON to
+    url: Option<String>,
+    /// Body for `url`, with `{{variable}}` in strings replaced
+    payload: Option<toml::Value>,
+    /// Extra headers for `url`, e.g. `Authorization`
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl Hook {
+    /// Check the hook can run, `number` counting from 1 for error messages.
+    fn validate(&self, number: usize) -> std::result::Result<(), String> {
+        let name = self.event.name();
+        match (&self.command, &self.url) {
+            (Some(_), Some(_)) => {
+                return Err(format!(
+                    "Hook {number} ({name}) has both command and url, use one"
+                ));
+            }
+            (None, None) => {
+                return Err(format!("Hook {number} ({name}) needs a command or url"));
+            }
+            _ => {}
+        }
+        if self.url.is_none() && (self.payload.is_some() || !self.headers.is_empty()) {
+            return Err(format!(
+                "Hook {number} ({name}) has a payload or headers but no url"
+            ));
+        }
+
+        let payload = self.payload.as_ref().map(toml_to_json).unwrap_or_default();
+        let mut templates = Vec::new();
+        collect_strings(&payload, &mut templates);
+        templates.extend(self.headers.values().map(String::as_str));
+
+        for template in templates {
+            for variable in template_variables(template) {
+                if !is_variable(self.event, variable) {
+                    return Err(format!(
+                        "Hook {number} ({name}) uses unknown variable '{variable}', expected one of: event, summary, {}",
+                        self.event.variables().join(", ")
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, agent: &ureq::Agent, event: &HookEvent) -> Result<()> {
+        if let Some(command) = &self.command {
+            return run_command(command, event);
+        }
+
+        let url = self.url.as_deref().unwrap_or_default();
+        let body = match &self.payload {
+            Some(payload) => render_json(&toml_to_json(payload), event),
+            None => event.to_json(),
+        };
+
+        let mut request = agent.post(url).header("Content-Type", "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, render(value, event));
+        }
+        request.send_json(&body)?;
+        Ok(())
+    }
+}
+
+fn read_hooks_file(path: &Path) -> Result<Vec<Hook>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Error::Usage(format!("Failed to read '{}': {e}", path.display())))?;
+    let file: HooksFile = toml::from_str(&contents)
+        .map_err(|e| Error::Usage(format!("Failed to parse '{}': {e}", path.display())))?;
+
+    if file.hooks.is_empty() {
+        return Err(Error::Usage(format!(
+            "'{}' doesn't define any [[hook]]",
+            path.display()
+        )));
+    }
+    for (i, hook) in file.hooks.iter().enumerate() {
+        hook.validate(i + 1)
+            .map_err(|e| Error::Usage(format!("{e} in '{}'", path.display())))?;
+    }
+
+    Ok(file.hooks)
+}
+
+fn run_command(command: &str, event: &HookEvent) -> Result<()> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+
+    let mut child = process::Command::new(shell);
+    child.arg(flag).arg(command);
+    for (name, value) in &event.vars {
+        child.env(format!("EMBY_{}", name.to_uppercase()), value);
+    }
+
+    let status = child.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("`{command}` exited with {status}")).into())
+    }
+}
+
+/// An event a hook can run for, with the variables it exposes.
+#[derive(Debug, Clone, PartialEq)]
+struct HookEvent {
+    kind: EventKind,
+    vars: BTreeMap<&'static str, String>,
+}
+
+impl HookEvent {
+    fn new(kind: EventKind, summary: String, vars: Vec<(&'static str, String)>) -> Self {
+        let mut all = BTreeMap::from([("event", kind.name().to_string()), ("summary", summary)]);
+        all.extend(vars);
+        Self { kind, vars: all }
+    }
+
+    fn var(&self, name: &str) -> &str {
+        self.vars.get(name).map_or("", String::as_str)
+    }
+
+    /// The default webhook payload: every variable.
+    fn to_json(&self) -> Value {
+        self.vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), Value::String(value.clone())))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+fn is_variable(kind: EventKind, name: &str) -> bool {
+    name == "event" || name == "summary" || kind.variables().contains(&name)
+}
+
+/// The names inside `{{ }}` in `template`.
+fn template_variables(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+    names
+}
+
+/// Replace each `{{variable}}` in `template` with its value.
+fn render(template: &str, event: &HookEvent) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(event.var(rest[start + 2..start + end].trim()));
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Render every string in a JSON payload, so values never need escaping.
+fn render_json(value: &Value, event: &HookEvent) -> Value {
+    match value {
+        Value::String(s) => Value::String(render(s, event)),
+        Value::Array(values) => values.iter().map(|v| render_json(v, event)).collect(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.clone(), render_json(v, event)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        _ => value.clone(),
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, strings: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => strings.push(s),
+        Value::Array(values) => values.iter().for_each(|v| collect_strings(v, strings)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, strings)),
+        _ => {}
+    }
+}
+
+fn toml_to_json(value: &toml::Value) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Turns server events into hook events by comparing them with what came
+/// before.
+struct Tracker {
+    /// What each session is playing, `None` until the first update
+    playing: Option<BTreeMap<String, HookEvent>>,
+    /// The newest activity log entry seen, when watching the activity log
+    cursor: Option<Cursor>,
+}
+
+impl Tracker {
+    fn new(cursor: Option<Cursor>) -> Self {
+        Self {
+            playing: None,
+            cursor,
+        }
+    }
+
+    /// Hook events for a server event, looking up details where the event
+    /// only has IDs.
+    fn update(&mut self, client: &Client, event: ServerEvent) -> Result<Vec<HookEvent>> {
+        match event.message_type.as_str() {
+            "Sessions" => {
+                let sessions: Vec<Session> = serde_json::from_value(event.data)?;
+                Ok(self.sessions(&sessions))
+            }
+            "LibraryChanged" => {
+                let ids: Vec<String> = event
+                    .data
+                    .get("ItemsAdded")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()?
+                    .unwrap_or_default();
+                if ids.is_empty() {
+                    return Ok(Vec::new());
+                }
+                Ok(items_added(&client.items().by_ids(&ids)?))
+            }
+            "ActivityLogEntry" => match &mut self.cursor {
+                Some(cursor) => Ok(activity_entries(&activity::fetch_new_entries(
+                    client, cursor,
+                )?)),
+                None => Ok(Vec::new()),
+            },
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Playback started and stopped events since the last update. The first
+    /// update only records what's already playing.
+    fn sessions(&mut self, sessions: &[Session]) -> Vec<HookEvent> {
+        let now: BTreeMap<String, HookEvent> = sessions.iter().filter_map(playing_event).collect();
+        let Some(before) = self.playing.replace(now.clone()) else {
+            return Vec::new();
+        };
+
+        let same_item = |a: &HookEvent, b: &HookEvent| a.var("item_id") == b.var("item_id");
+        let mut events = Vec::new();
+
+        for (session_id, playing) in &before {
+            if !now.get(session_id).is_some_and(|n| same_item(n, playing)) {
+                let mut stopped = playing.clone();
+                stopped.kind = EventKind::PlaybackStopped;
+                stopped
+                    .vars
+                    .insert("event", EventKind::PlaybackStopped.name().to_string());
+                events.push(stopped);
+            }
+        }
+        for (session_id, playing) in now {
+            if !before
+                .get(&session_id)
+                .is_some_and(|b| same_item(b, &playing))
+            {
+                events.push(playing);
+            }
+        }
+
+        events
+    }
+}
+
+/// A playback started event for a session that's playing something, keyed
+/// by session ID.
+fn playing_event(session: &Session) -> Option<(String, HookEvent)> {
+    let item = session.now_playing_item.as_ref()?;
+    let session_id = session.id.clone().unwrap_or_default();
+    let name = item.name.clone().unwrap_or_default();
+    let title = match &item.series_name {
+        Some(series) => format!("{series} - {name}"),
+        None => name,
+    };
+    let user = session.user_name.clone().unwrap_or_default();
+    let device = session.device_name.clone().unwrap_or_default();
+
+    let event = HookEvent::new(
+        EventKind::PlaybackStarted,
+        format!("{user} on {device}: {title}"),
+        vec![
+            ("user", user),
+            ("device", device),
+            ("client", session.client.clone().unwrap_or_default()),
+            ("item", title),
+            ("item_type", item.media_type.clone().unwrap_or_default()),
+            ("item_id", item.id.clone().unwrap_or_default()),
+            ("session_id", session_id.clone()),
+        ],
+    );
+    Some((session_id, event))
+}
+
+/// Item added events, skipping folders such as the series and season of a
+/// new episode.
+fn items_added(items: &[BaseItemDto]) -> Vec<HookEvent> {
+    items
+        .iter()
+        .filter(|item| !item.is_folder.unwrap_or(false))
+        .map(|item| {
+            let name = item.name.clone().unwrap_or_default();
+            let item_type = item.media_type.clone().unwrap_or_default();
+            let series = item.series_name.clone().unwrap_or_default();
+            let title = if series.is_empty() {
+                name
+            } else {
+                format!("{series} - {name}")
+            };
+
+            HookEvent::new(
+                EventKind::ItemAdded,
+                format!("{item_type} {title}"),
+                vec![
+                    ("item", title),
+                    ("item_type", item_type),
+                    ("item_id", item.id.clone().unwrap_or_default()),
+                    ("series", series),
+                    (
+                        "year",
+                        item.production_year
+                            .map(|y| y.to_string())
+                            .unwrap_or_default(),
+                    ),
+                ],
+            )
+        })
+        .collect()
+}
+
+/// Task failed and user locked out events from new activity log entries.
+fn activity_entries(entries: &[ActivityLogEntry]) -> Vec<HookEvent> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let kind = match entry.entry_type.as_deref() {
+                Some("ScheduledTaskFailed") => EventKind::TaskFailed,
+                Some("UserLockedOut") => EventKind::UserLockedOut,
+                _ => return None,
+            };
+            let name = entry.name.clone().unwrap_or_default();
+            let overview = entry
+                .overview
+                .clone()
+                .or_else(|| entry.short_overview.clone())
+                .unwrap_or_default();
+
+            Some(HookEvent::new(
+                kind,
+                name.clone(),
+                vec![
+                    ("name", name),
+                    ("overview", overview),
+                    ("severity", entry.severity.clone().unwrap_or_default()),
+                    ("date", entry.date.clone().unwrap_or_default()),
+                ],
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, item: Option<(&str, &str)>) -> Session {
+        serde_json::from_value(serde_json::json!({
+            "Id": id,
+            "UserName": "josh",
+            "DeviceName": "Living Room",
+            "Client": "Emby Web",
+            "NowPlayingItem": item.map(|(item_id, name)| serde_json::json!({
+                "Id": item_id,
+                "Name": name,
+                "Type": "Movie",
+            })),
+        }))
+        .unwrap()
+    }
+
+    fn kinds(events: &[HookEvent]) -> Vec<(EventKind, &str)> {
+        events.iter().map(|e| (e.kind, e.var("item"))).collect()
+    }
+
+    #[test]
+    fn sessions_start_and_stop_playback() {
+        let mut tracker = Tracker::new(None);
+
+        // Already playing when the hooks started
+        assert!(
+            tracker
+                .sessions(&[session("a", Some(("1", "Heat")))])
+                .is_empty()
+        );
+
+        let events = tracker.sessions(&[
+            session("a", Some(("1", "Heat"))),
+            session("b", Some(("2", "Ronin"))),
+        ]);
+        assert_eq!(kinds(&events), [(EventKind::PlaybackStarted, "Ronin")]);
+        assert_eq!(events[0].var("summary"), "josh on Living Room: Ronin");
+        assert_eq!(events[0].var("session_id"), "b");
+
+        // Switching items stops one and starts the other
+        let events = tracker.sessions(&[session("a", Some(("3", "Thief"))), session("b", None)]);
+        assert_eq!(
+            kinds(&events),
+            [
+                (EventKind::PlaybackStopped, "Heat"),
+                (EventKind::PlaybackStopped, "Ronin"),
+                (EventKind::PlaybackStarted, "Thief"),
+            ]
+        );
+        assert_eq!(events[0].var("event"), "playback_stopped");
+    }
+
+    #[test]
+    fn items_added_skips_folders() {
+        let items: Vec<BaseItemDto> = serde_json::from_value(serde_json::json!([
+            {"Id": "10", "Name": "Breaking Bad", "Type": "Series", "IsFolder": true},
+            {"Id": "11", "Name": "Pilot", "Type": "Episode", "IsFolder": false,
+             "SeriesName": "Breaking Bad", "ProductionYear": 2008}
+        ]))
+        .unwrap();
+
+        let events = items_added(&items);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].var("item"), "Breaking Bad - Pilot");
+        assert_eq!(events[0].var("summary"), "Episode Breaking Bad - Pilot");
+        assert_eq!(events[0].var("year"), "2008");
+    }
+
+    #[test]
+    fn activity_entries_match_failures_and_lockouts() {
+        let entries: Vec<ActivityLogEntry> = serde_json::from_value(serde_json::json!([
+            {"Id": 1, "Name": "josh logged in", "Type": "AuthenticationSucceeded"},
+            {"Id": 2, "Name": "Scan media library failed", "Type": "ScheduledTaskFailed",
+             "Overview": "Access denied", "Severity": "Error"},
+            {"Id": 3, "Name": "User josh has been locked out", "Type": "UserLockedOut"}
+        ]))
+        .unwrap();
+
+        let events = activity_entries(&entries);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::TaskFailed);
+        assert_eq!(events[0].var("overview"), "Access denied");
+        assert_eq!(events[1].kind, EventKind::UserLockedOut);
+    }
+
+    #[test]
+    fn renders_templates() {
+        let event = HookEvent::new(
+            EventKind::TaskFailed,
+            "Scan failed".to_string(),
+            vec![("overview", "Said \"no\"".to_string())],
+        );
+
+        assert_eq!(
+            render("{{ event }}: {{summary}} ({{overview}})", &event),
+            "task_failed: Scan failed (Said \"no\")"
+        );
+        assert_eq!(render("no {{ closing", &event), "no {{ closing");
+
+        let payload = serde_json::json!({"text": "{{summary}}", "tags": ["{{event}}", 1]});
+        assert_eq!(
+            render_json(&payload, &event),
+            serde_json::json!({"text": "Scan failed", "tags": ["task_failed", 1]})
+        );
+    }
+
+    #[test]
+    fn validates_hooks() {
+        let parse = |toml: &str| {
+            let file: HooksFile = toml::from_str(toml).unwrap();
+            file.hooks[0].validate(1)
+        };
+
+        assert!(parse("[[hook]]\nevent = \"item_added\"\ncommand = \"true\"").is_ok());
+        assert!(
+            parse("[[hook]]\nevent = \"item_added\"\nurl = \"http://x\"\npayload = { text = \"{{item}} added\" }")
+                .is_ok()
+        );
+
+        let err = parse("[[hook]]\nevent = \"item_added\"").unwrap_err();
+        assert!(err.contains("needs a command or url"), "{err}");
+
+        let err = parse("[[hook]]\nevent = \"item_added\"\nurl = \"http://x\"\npayload = { text = \"{{user}}\" }")
+            .unwrap_err();
+        assert!(err.contains("unknown variable 'user'"), "{err}");
+
+        let err =
+            parse("[[hook]]\nevent = \"item_added\"\ncommand = \"true\"\nheaders = { A = \"b\" }")
+                .unwrap_err();
+        assert!(err.contains("no url"), "{err}");
+    }
+}
@@ -0,0 +1,25 @@
+use crate::cli::{GlobalArgs, ItemArgs};
+use crate::client::Client;
+use crate::emby::users;
+use crate::error::Result;
+use crate::format::color::ColorConfig;
+use crate::format::item;
+use crate::format::output::{self, OutputFormat};
+
+pub fn run(global: &GlobalArgs, args: &ItemArgs) -> Result<()> {
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let user_id = users::resolve_user_id(&client, args.user.as_deref())?;
+    let found = client.items().get(&user_id, &args.id)?;
+
+    if global.output != OutputFormat::Table {
+        let json = serde_json::to_value(&found)?;
+        println!("{}", output::render_objects(global.output, &[json])?);
+        return Ok(());
+    }
+
+    let colors = ColorConfig::new(args.plain);
+    println!("{}", item::format_item(&found, &colors));
+
+    Ok(())
+}
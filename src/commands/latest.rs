@@ -1,46 +1,36 @@
-use crate::cli::LatestArgs;
+use crate::api::LatestOptions;
+use crate::cli::{GlobalArgs, LatestArgs};
 use crate::client::Client;
-use crate::config::Config;
 use crate::emby::types::BaseItemDto;
 use crate::emby::{ticks, users};
 use crate::error::{Error, Result};
-use crate::format::table;
+use crate::format::output;
 
-pub fn run(args: &LatestArgs) -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs, args: &LatestArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
     let user_id = users::resolve_user_id(&client, args.user.as_deref())?;
-    let limit = args.limit.to_string();
-
-    let mut query = vec![
-        ("Limit", limit.as_str()),
-        ("Fields", "DateCreated,Overview,ProductionYear"),
-        ("GroupItems", "true"),
-    ];
-
     let include_types = match args.r#type.as_deref() {
         Some("movies") => Some("Movie"),
         Some("shows") => Some("Series"),
         Some("music") => Some("Audio"),
         Some(other) => {
-            return Err(Error::Config(format!(
+            return Err(Error::Usage(format!(
                 "Unknown type '{other}'. Use: movies, shows, music"
             )));
         }
         None => None,
     };
 
-    if let Some(types) = include_types {
-        query.push(("IncludeItemTypes", types));
-    }
-
-    let items: Vec<BaseItemDto> =
-        client.get_with_query(&format!("/Users/{user_id}/Items/Latest"), &query)?;
-
-    if items.is_empty() {
-        println!("No recently added items");
-        return Ok(());
-    }
+    let items = client.items().latest(
+        &user_id,
+        &LatestOptions {
+            limit: Some(args.limit),
+            include_item_types: include_types.map(String::from),
+            fields: Some("DateCreated,Overview,ProductionYear".to_string()),
+            group_items: true,
+        },
+    )?;
 
     let rows: Vec<Vec<String>> = items
         .iter()
@@ -55,7 +45,12 @@ pub fn run(args: &LatestArgs) -> Result<()> {
         })
         .collect();
 
-    println!("{}", table::build_table(&["Type", "Name", "Year"], rows));
+    output::print_rows(
+        global.output,
+        &["Type", "Name", "Year"],
+        rows,
+        "No recently added items",
+    )?;
 
     Ok(())
 }
@@ -83,20 +78,8 @@ mod tests {
     fn item(media_type: &str) -> BaseItemDto {
         BaseItemDto {
             name: Some("Test Name".to_string()),
-            id: None,
             media_type: Some(media_type.to_string()),
-            series_name: None,
-            index_number: None,
-            parent_index_number: None,
-            production_year: None,
-            premiere_date: None,
-            date_created: None,
-            run_time_ticks: None,
-            overview: None,
-            container: None,
-            official_rating: None,
-            album: None,
-            album_artist: None,
+            ..BaseItemDto::default()
         }
     }
 
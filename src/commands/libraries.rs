@@ -1,31 +1,152 @@
+use crate::cli::{
+    GlobalArgs, LibrariesArgs, LibrariesCommand, LibrariesCreateArgs, LibrariesDeleteArgs,
+    LibrariesPathArgs, LibrariesRenameArgs, LibraryType,
+};
 use crate::client::Client;
-use crate::config::Config;
+use crate::commands::prompt;
+use crate::emby::libraries;
 use crate::emby::types::VirtualFolder;
-use crate::error::Result;
-use crate::format::table;
+use crate::error::{Error, Result};
+use crate::format::output;
 
-pub fn run() -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs, args: &LibrariesArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let libraries: Vec<VirtualFolder> = client.get("/Library/VirtualFolders")?;
 
-    if libraries.is_empty() {
-        println!("No libraries found");
-        return Ok(());
+    match &args.command {
+        None => list_libraries(global, &client),
+        Some(LibrariesCommand::Create(create_args)) => create_library(&client, create_args),
+        Some(LibrariesCommand::AddPath(path_args)) => add_path(&client, path_args),
+        Some(LibrariesCommand::RemovePath(path_args)) => remove_path(&client, path_args),
+        Some(LibrariesCommand::Rename(rename_args)) => rename_library(&client, rename_args),
+        Some(LibrariesCommand::Delete(delete_args)) => delete_library(&client, delete_args),
+    }
+}
+
+fn list_libraries(global: &GlobalArgs, client: &Client) -> Result<()> {
+    let libraries = client.library().virtual_folders()?;
+
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(libraries.len());
+    for l in &libraries {
+        // A count that fails leaves its cell blank rather than hiding the list
+        let items = l
+            .item_id
+            .as_deref()
+            .and_then(|id| client.items().count(id).ok())
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+
+        rows.push(vec![
+            l.name.as_deref().unwrap_or("").to_string(),
+            l.collection_type.as_deref().unwrap_or("").to_string(),
+            items,
+            l.locations.as_deref().unwrap_or_default().join(", "),
+            l.item_id.as_deref().unwrap_or("").to_string(),
+        ]);
     }
 
-    let rows: Vec<Vec<String>> = libraries
-        .iter()
-        .map(|l| {
-            vec![
-                l.name.as_deref().unwrap_or("").to_string(),
-                l.collection_type.as_deref().unwrap_or("").to_string(),
-                l.item_id.as_deref().unwrap_or("").to_string(),
-            ]
-        })
-        .collect();
+    output::print_rows(
+        global.output,
+        &["Name", "Type", "Items", "Locations", "ID"],
+        rows,
+        "No libraries found",
+    )?;
+
+    Ok(())
+}
+
+fn create_library(client: &Client, args: &LibrariesCreateArgs) -> Result<()> {
+    let collection_type = collection_type(args.library_type);
 
-    println!("{}", table::build_table(&["Name", "Type", "ID"], rows));
+    client
+        .library()
+        .add_virtual_folder(&args.name, collection_type, &args.paths, !args.no_scan)?;
 
+    println!(
+        "Created library {} ({})",
+        args.name,
+        collection_type.unwrap_or("mixed")
+    );
     Ok(())
 }
+
+fn add_path(client: &Client, args: &LibrariesPathArgs) -> Result<()> {
+    let library = libraries::find_library(client, &args.library)?;
+    let name = library_name(&library)?;
+
+    client
+        .library()
+        .add_media_path(name, &args.path, !args.no_scan)?;
+
+    println!("Added {} to library {name}", args.path);
+    Ok(())
+}
+
+fn remove_path(client: &Client, args: &LibrariesPathArgs) -> Result<()> {
+    let library = libraries::find_library(client, &args.library)?;
+    let name = library_name(&library)?;
+    let locations = library.locations.as_deref().unwrap_or_default();
+
+    if !locations.contains(&args.path) {
+        return Err(Error::NotFound(format!(
+            "Library {name} has no path {} (paths: {})",
+            args.path,
+            locations.join(", ")
+        )));
+    }
+
+    client
+        .library()
+        .remove_media_path(name, &args.path, !args.no_scan)?;
+
+    println!("Removed {} from library {name}", args.path);
+    Ok(())
+}
+
+fn rename_library(client: &Client, args: &LibrariesRenameArgs) -> Result<()> {
+    let library = libraries::find_library(client, &args.library)?;
+    let name = library_name(&library)?;
+
+    client
+        .library()
+        .rename_virtual_folder(name, &args.new_name)?;
+
+    println!("Renamed library {name} to {}", args.new_name);
+    Ok(())
+}
+
+fn delete_library(client: &Client, args: &LibrariesDeleteArgs) -> Result<()> {
+    let library = libraries::find_library(client, &args.library)?;
+    let name = library_name(&library)?;
+
+    if !args.yes && !prompt::confirm(&format!("Delete library {name}? Its files stay on disk."))? {
+        println!("Not deleting {name}");
+        return Ok(());
+    }
+
+    client.library().remove_virtual_folder(name)?;
+    println!("Deleted library {name}");
+    Ok(())
+}
+
+/// The `CollectionType` Emby expects, or `None` for a mixed library.
+fn collection_type(library_type: LibraryType) -> Option<&'static str> {
+    match library_type {
+        LibraryType::Movies => Some("movies"),
+        LibraryType::TvShows => Some("tvshows"),
+        LibraryType::Music => Some("music"),
+        LibraryType::MusicVideos => Some("musicvideos"),
+        LibraryType::HomeVideos => Some("homevideos"),
+        LibraryType::Books => Some("books"),
+        LibraryType::Photos => Some("photos"),
+        LibraryType::Mixed => None,
+    }
+}
+
+/// Emby addresses libraries by name when changing them.
+fn library_name(library: &VirtualFolder) -> Result<&str> {
+    library
+        .name
+        .as_deref()
+        .ok_or_else(|| Error::Server("Server did not return a library name".to_string()))
+}
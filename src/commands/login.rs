@@ -0,0 +1,51 @@
+use crate::cli::{GlobalArgs, LoginArgs};
+use crate::client::{Client, HttpOptions};
+use crate::commands::prompt::{prompt, prompt_password, read_stdin_line};
+use crate::config;
+use crate::error::{Error, Result};
+
+pub fn run(global: &GlobalArgs, args: &LoginArgs) -> Result<()> {
+    let profile = global.profile.as_deref();
+
+    let api_url = match &args.server {
+        Some(server) => server.clone(),
+        None => config::load_api_url(profile)?
+            .ok_or_else(|| Error::Config("No server URL configured, pass --server".to_string()))?,
+    };
+
+    let username = match &args.username {
+        Some(username) => username.clone(),
+        None => prompt("Username: ")?,
+    };
+
+    let password = if args.password_stdin {
+        read_stdin_line()?
+    } else {
+        prompt_password("Password: ")?
+    };
+
+    let client = Client::anonymous(&api_url, global.http_options(HttpOptions::default()));
+    let auth = client
+        .users()
+        .authenticate_by_name(&username, &password)
+        .map_err(|e| match e {
+            Error::Auth(_) => Error::Auth("Invalid username or password".to_string()),
+            e => e,
+        })?;
+
+    let access_token = auth
+        .access_token
+        .ok_or_else(|| Error::Server("Server did not return an access token".to_string()))?;
+    let user = auth
+        .user
+        .ok_or_else(|| Error::Server("Server did not return a user".to_string()))?;
+    let user_id = user
+        .id
+        .ok_or_else(|| Error::Server("Server did not return a user ID".to_string()))?;
+    let name = user.name.unwrap_or(username);
+
+    let path = config::save_login(profile, client.api_url(), &access_token, &user_id)?;
+    println!("Logged in as {name}, saved token to {}", path.display());
+
+    Ok(())
+}
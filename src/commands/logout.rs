@@ -0,0 +1,26 @@
+use crate::cli::GlobalArgs;
+use crate::client::Client;
+use crate::config;
+use crate::error::{Error, Result};
+
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let profile = global.profile.as_deref();
+    let config = global.load_config()?;
+
+    if config.user_id.is_none() {
+        return Err(Error::Config(
+            "Not logged in, the config uses an API key".to_string(),
+        ));
+    }
+
+    let client = Client::new(&config);
+
+    // Forget the token locally even if the server no longer accepts it
+    let revoked = client.sessions().logout();
+    config::clear_login(profile)?;
+    revoked?;
+
+    println!("Logged out");
+
+    Ok(())
+}
@@ -1,14 +1,25 @@
 pub mod activity;
+pub mod dashboard;
 pub mod devices;
+pub mod events;
 pub mod find_server;
+pub mod hooks;
+pub mod item;
 pub mod latest;
 pub mod libraries;
+pub mod login;
+pub mod logout;
 pub mod next_up;
+pub mod pick;
 pub mod playing;
+pub mod prompt;
 pub mod restart;
 pub mod scan;
 pub mod search;
+pub mod session;
 pub mod system;
 pub mod tasks;
+pub mod tui;
 pub mod upcoming;
+pub mod user_data;
 pub mod users;
@@ -0,0 +1,503 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{self, Stdio};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::api::{RefreshOptions, SearchOptions};
+use crate::cli::{GlobalArgs, PickArgs};
+use crate::client::Client;
+use crate::commands::search::{format_search_name, item_type};
+use crate::commands::tui::{Line, Screen, Style, Target, TerminalGuard};
+use crate::commands::user_data::Action;
+use crate::emby::types::BaseItemDto;
+use crate::emby::users;
+use crate::error::{Error, Result};
+use crate::format::color::ColorConfig;
+use crate::format::item;
+
+/// How long to wait after the last keystroke before searching.
+const SEARCH_DELAY: Duration = Duration::from_millis(200);
+
+/// Results fetched per search.
+const MAX_RESULTS: u32 = 50;
+
+/// Width of the type column in the result list.
+const TYPE_WIDTH: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MenuAction {
+    Details,
+    Played,
+    Unplayed,
+    Refresh,
+    Open,
+    PrintId,
+}
+
+const MENU: [(MenuAction, &str); 6] = [
+    (MenuAction::Details, "Show details"),
+    (MenuAction::Played, "Mark played"),
+    (MenuAction::Unplayed, "Mark unplayed"),
+    (MenuAction::Refresh, "Refresh metadata"),
+    (MenuAction::Open, "Open in browser"),
+    (MenuAction::PrintId, "Print ID"),
+];
+
+pub fn run(global: &GlobalArgs, args: &PickArgs) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(Error::Usage(
+            "emby pick needs a terminal, use emby search in scripts".to_string(),
+        ));
+    }
+
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let user_id = users::resolve_user_id(&client, args.user.as_deref())?;
+
+    let mut picker = Picker::new(args.query.clone().unwrap_or_default());
+    let chosen = {
+        // Draw on stderr so "Print ID" leaves only the ID on stdout
+        let mut stderr = io::stderr();
+        let _guard = TerminalGuard::enter(Target::Stderr)?;
+        pick(&client, &user_id, args, &mut picker, &mut stderr)?
+    };
+
+    match chosen {
+        Some((item, action)) => run_action(&client, &user_id, &item, action),
+        None => Ok(()),
+    }
+}
+
+/// Run the picker until an item and action are chosen, or it's cancelled.
+fn pick(
+    client: &Client,
+    user_id: &str,
+    args: &PickArgs,
+    picker: &mut Picker,
+    out: &mut impl Write,
+) -> Result<Option<(BaseItemDto, MenuAction)>> {
+    let mut screen = Screen::default();
+
+    loop {
+        render(out, &mut screen, picker)?;
+
+        let timeout = if picker.search_pending {
+            SEARCH_DELAY
+        } else {
+            Duration::from_secs(60)
+        };
+
+        if !event::poll(timeout)? {
+            if picker.search_pending {
+                picker.search_pending = false;
+                picker.status = Status::Searching;
+                render(out, &mut screen, picker)?;
+                match search(client, user_id, args, &picker.query) {
+                    Ok(items) => picker.set_results(items),
+                    Err(e) => picker.status = Status::Failed(e.to_string()),
+                }
+            }
+            continue;
+        }
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match picker.handle_key(key) {
+            Outcome::Continue => {}
+            Outcome::Quit => return Ok(None),
+            Outcome::Chosen(action) => {
+                let item = picker.results.swap_remove(picker.selected);
+                return Ok(Some((item, action)));
+            }
+        }
+    }
+}
+
+fn search(
+    client: &Client,
+    user_id: &str,
+    args: &PickArgs,
+    query: &str,
+) -> Result<Vec<BaseItemDto>> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let types: Vec<&str> = args.types.iter().map(|t| item_type(*t)).collect();
+    let response = client.items().search(&SearchOptions {
+        search_term: query.to_string(),
+        limit: Some(MAX_RESULTS),
+        user_id: Some(user_id.to_string()),
+        fields: Some("ProductionYear,SeriesName".to_string()),
+        include_item_types: (!types.is_empty()).then(|| types.join(",")),
+        exclude_item_types: types
+            .is_empty()
+            .then(|| "Folder,UserView,CollectionFolder".to_string()),
+        ..SearchOptions::default()
+    })?;
+
+    Ok(response.items.unwrap_or_default())
+}
+
+fn run_action(
+    client: &Client,
+    user_id: &str,
+    item: &BaseItemDto,
+    action: MenuAction,
+) -> Result<()> {
+    let id = item
+        .id
+        .as_deref()
+        .ok_or_else(|| Error::Server("Server did not return an item ID".to_string()))?;
+    let name = format_search_name(item);
+
+    match action {
+        MenuAction::Details => {
+            let details = client.items().get(user_id, id)?;
+            println!("{}", item::format_item(&details, &ColorConfig::new(false)));
+        }
+        MenuAction::Played | MenuAction::Unplayed => {
+            let action = if action == MenuAction::Played {
+                Action::Played
+            } else {
+                Action::Unplayed
+            };
+            action.apply(client, user_id, id)?;
+            println!("Marked {name} {}", action.description());
+        }
+        MenuAction::Refresh => {
+            client.items().refresh(id, &RefreshOptions::default())?;
+            println!("Refreshing metadata for {name}");
+        }
+        MenuAction::Open => {
+            let server_id = client.system().info()?.id.unwrap_or_default();
+            let url = web_url(client.api_url(), &server_id, id);
+            open_url(&url)?;
+            println!("Opened {url}");
+        }
+        MenuAction::PrintId => println!("{id}"),
+    }
+
+    Ok(())
+}
+
+/// The item's page in the Emby web app.
+fn web_url(api_url: &str, server_id: &str, id: &str) -> String {
+    format!("{api_url}/web/index.html#!/item?id={id}&serverId={server_id}")
+}
+
+fn open_url(url: &str) -> Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    let status = process::Command::new(opener)
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    // explorer exits with 1 even when it opened the URL
+    if status.success() || cfg!(windows) {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{opener} failed to open {url} ({status})")).into())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Idle,
+    Searching,
+    Failed(String),
+}
+
+/// What a key press asks the picker loop to do.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Quit,
+    Chosen(MenuAction),
+}
+
+/// The picker's state: the query, its results and, once an item has been
+/// chosen, the selected menu entry.
+struct Picker {
+    query: String,
+    /// The query changed since the last search
+    search_pending: bool,
+    results: Vec<BaseItemDto>,
+    selected: usize,
+    menu: Option<usize>,
+    status: Status,
+}
+
+impl Picker {
+    fn new(query: String) -> Self {
+        Self {
+            search_pending: !query.is_empty(),
+            query,
+            results: Vec::new(),
+            selected: 0,
+            menu: None,
+            status: Status::Idle,
+        }
+    }
+
+    /// Show new results. Any open menu is closed, since it was for one of the
+    /// old results.
+    fn set_results(&mut self, results: Vec<BaseItemDto>) {
+        self.results = results;
+        self.selected = 0;
+        self.menu = None;
+        self.status = Status::Idle;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Outcome::Quit;
+        }
+
+        if let Some(entry) = self.menu {
+            match key.code {
+                KeyCode::Up => self.menu = Some(entry.saturating_sub(1)),
+                KeyCode::Down => self.menu = Some((entry + 1).min(MENU.len() - 1)),
+                KeyCode::Enter => return Outcome::Chosen(MENU[entry].0),
+                KeyCode::Esc | KeyCode::Left | KeyCode::Backspace => self.menu = None,
+                _ => {}
+            }
+            return Outcome::Continue;
+        }
+
+        match key.code {
+            KeyCode::Esc => return Outcome::Quit,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.results.len().saturating_sub(1));
+            }
+            // Choosing a result settles on the results shown, so drop a search
+            // for what was typed since
+            KeyCode::Enter | KeyCode::Right if !self.results.is_empty() => {
+                self.menu = Some(0);
+                self.search_pending = false;
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search_pending = true;
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.search_pending = true;
+            }
+            _ => {}
+        }
+
+        Outcome::Continue
+    }
+
+    /// The screen's lines, with the index of the highlighted one.
+    fn lines(&self, height: usize) -> (Vec<String>, Option<usize>) {
+        let mut lines = vec![format!("Search: {}", self.query)];
+
+        if let Some(entry) = self.menu {
+            lines.push(format_search_name(&self.results[self.selected]));
+            lines.push(String::new());
+            let first = lines.len();
+            lines.extend(MENU.iter().map(|(_, label)| format!("  {label}")));
+            lines.push(String::new());
+            lines.push("Up/Down move | Enter run | Esc back".to_string());
+            return (lines, Some(first + entry));
+        }
+
+        lines.push(match &self.status {
+            Status::Searching => "Searching...".to_string(),
+            Status::Failed(e) => format!("Error: {e}"),
+            Status::Idle if self.query.trim().is_empty() => "Type to search".to_string(),
+            Status::Idle if self.results.is_empty() => "No results found".to_string(),
+            Status::Idle => match self.results.len() {
+                1 => "1 result".to_string(),
+                n => format!("{n} results"),
+            },
+        });
+
+        // Scroll so the selected result stays in view, leaving room for the
+        // header and help lines
+        let rows = height.saturating_sub(4).max(1);
+        let offset = self.selected.saturating_sub(rows - 1);
+        let first = lines.len();
+        lines.extend(self.results.iter().skip(offset).take(rows).map(result_line));
+        lines.push(String::new());
+        lines.push("Up/Down move | Enter choose | Esc quit".to_string());
+
+        let highlighted = (!self.results.is_empty()).then_some(first + self.selected - offset);
+        (lines, highlighted)
+    }
+}
+
+fn result_line(item: &BaseItemDto) -> String {
+    let media_type = item.media_type.as_deref().unwrap_or("");
+    let name = format_search_name(item);
+    match item.production_year {
+        Some(year) => format!("  {media_type:<TYPE_WIDTH$} {name} ({year})"),
+        None => format!("  {media_type:<TYPE_WIDTH$} {name}"),
+    }
+}
+
+fn render(out: &mut impl Write, screen: &mut Screen, picker: &Picker) -> Result<()> {
+    let (width, height) = terminal::size()?;
+    let (lines, highlighted) = picker.lines(usize::from(height));
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let style = if Some(i) == highlighted {
+                Style::Selected
+            } else {
+                Style::Normal
+            };
+            Line::new(text, style)
+        })
+        .collect();
+
+    screen.draw(out, lines, (width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn picker_with_results(count: usize) -> Picker {
+        let mut picker = Picker::new("heat".to_string());
+        picker.set_results(
+            (0..count)
+                .map(|i| BaseItemDto {
+                    name: Some(format!("Item {i}")),
+                    media_type: Some("Movie".to_string()),
+                    ..BaseItemDto::default()
+                })
+                .collect(),
+        );
+        picker
+    }
+
+    #[test]
+    fn typing_edits_query() {
+        let mut picker = Picker::new(String::new());
+        assert!(!picker.search_pending);
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char('h'))),
+            Outcome::Continue
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char('i'))),
+            Outcome::Continue
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Backspace)),
+            Outcome::Continue
+        );
+        assert_eq!(picker.query, "h");
+        assert!(picker.search_pending);
+    }
+
+    #[test]
+    fn opening_menu_drops_pending_search() {
+        let mut picker = picker_with_results(2);
+        picker.handle_key(key(KeyCode::Char('x')));
+        picker.handle_key(key(KeyCode::Enter));
+        assert_eq!(picker.menu, Some(0));
+        assert!(!picker.search_pending);
+    }
+
+    #[test]
+    fn new_results_close_menu() {
+        let mut picker = picker_with_results(2);
+        picker.handle_key(key(KeyCode::Down));
+        picker.handle_key(key(KeyCode::Enter));
+        picker.set_results(Vec::new());
+
+        assert_eq!(picker.menu, None);
+        let (lines, highlighted) = picker.lines(10);
+        assert_eq!(lines[1], "No results found");
+        assert_eq!(highlighted, None);
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), Outcome::Continue);
+    }
+
+    #[test]
+    fn selection_stays_in_results() {
+        let mut picker = picker_with_results(2);
+        picker.handle_key(key(KeyCode::Up));
+        assert_eq!(picker.selected, 0);
+        picker.handle_key(key(KeyCode::Down));
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(picker.selected, 1);
+    }
+
+    #[test]
+    fn enter_opens_menu_and_chooses_action() {
+        let mut picker = picker_with_results(3);
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), Outcome::Continue);
+        assert_eq!(picker.menu, Some(0));
+
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            Outcome::Chosen(MenuAction::Played)
+        );
+        assert_eq!(picker.selected, 1);
+    }
+
+    #[test]
+    fn escape_leaves_menu_then_quits() {
+        let mut picker = picker_with_results(1);
+        picker.handle_key(key(KeyCode::Enter));
+        assert_eq!(picker.handle_key(key(KeyCode::Esc)), Outcome::Continue);
+        assert_eq!(picker.menu, None);
+        assert_eq!(picker.handle_key(key(KeyCode::Esc)), Outcome::Quit);
+    }
+
+    #[test]
+    fn enter_without_results_does_nothing() {
+        let mut picker = Picker::new("zzz".to_string());
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), Outcome::Continue);
+        assert_eq!(picker.menu, None);
+    }
+
+    #[test]
+    fn lines_scroll_to_selection() {
+        let mut picker = picker_with_results(20);
+        for _ in 0..10 {
+            picker.handle_key(key(KeyCode::Down));
+        }
+
+        // 8 rows: header, status, 4 results, blank, help
+        let (lines, highlighted) = picker.lines(8);
+        assert_eq!(lines[1], "20 results");
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[highlighted.unwrap()], "  Movie      Item 10");
+    }
+
+    #[test]
+    fn test_web_url() {
+        assert_eq!(
+            web_url("http://emby.local:8096", "srv1", "item-001"),
+            "http://emby.local:8096/web/index.html#!/item?id=item-001&serverId=srv1"
+        );
+    }
+}
@@ -1,28 +1,29 @@
-use std::io::{self, Write};
+use std::io;
 use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
-use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
-use crossterm::{cursor, execute};
+use crossterm::terminal;
 
-use crate::cli::PlayingArgs;
+use crate::api::{EventStream, Subscription};
+use crate::cli::{GlobalArgs, PlayingArgs};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::types::Session;
-use crate::error::Result;
+use crate::commands::tui::{Line, Screen, Style, Target, TerminalGuard};
+use crate::emby::types::{ServerEvent, Session};
+use crate::error::{Error, Result};
 use crate::format::color::ColorConfig;
+use crate::format::output::{self, OutputFormat};
 use crate::format::playing;
 
-pub fn run(args: &PlayingArgs) -> Result<()> {
+pub fn run(global: &GlobalArgs, args: &PlayingArgs) -> Result<()> {
     if let Some(interval) = args.watch {
-        run_watch(args, interval)
+        run_watch(global, args, interval)
     } else {
-        run_once(args)
+        run_once(global, args)
     }
 }
 
-fn run_once(args: &PlayingArgs) -> Result<()> {
-    let config = Config::load()?;
+fn run_once(global: &GlobalArgs, args: &PlayingArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
     let sessions = fetch_sessions(&client, args)?;
 
@@ -37,7 +38,13 @@ fn run_once(args: &PlayingArgs) -> Result<()> {
 
     let entries = playing::build_entries(&sessions);
 
-    if args.json {
+    let format = if args.json {
+        OutputFormat::Json
+    } else {
+        global.output
+    };
+
+    if format != OutputFormat::Table {
         let json: Vec<serde_json::Value> = entries
             .iter()
             .map(|e| {
@@ -61,17 +68,12 @@ fn run_once(args: &PlayingArgs) -> Result<()> {
                     "album": e.album,
                     "album_artist": e.album_artist,
                     "album_track": e.album_track,
+                    "transcode": e.transcode,
                 })
             })
             .collect();
 
-        let colors = ColorConfig::new(args.plain);
-        if colors.enabled {
-            let json_str = serde_json::to_string_pretty(&json)?;
-            println!("{json_str}");
-        } else {
-            println!("{}", serde_json::to_string_pretty(&json)?);
-        }
+        println!("{}", output::render_objects(format, &json)?);
         return Ok(());
     }
 
@@ -81,71 +83,102 @@ fn run_once(args: &PlayingArgs) -> Result<()> {
     Ok(())
 }
 
-fn run_watch(args: &PlayingArgs, interval: u64) -> Result<()> {
-    let config = Config::load()?;
+fn run_watch(global: &GlobalArgs, args: &PlayingArgs, interval: u64) -> Result<()> {
+    if global.output != OutputFormat::Table {
+        return Err(Error::Usage(
+            "Watch mode only supports table output".to_string(),
+        ));
+    }
+
+    let config = global.load_config()?;
     let client = Client::new(&config);
 
+    // Redraw as soon as sessions change, polling as well in case the
+    // server's WebSocket is unavailable or the connection drops
+    let mut events = client.events().subscribe(&[Subscription::Sessions]).ok();
+
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
-    let _guard = TerminalGuard;
+    let _guard = TerminalGuard::enter(Target::Stdout)?;
 
     let colors = ColorConfig::new(args.plain);
+    let mut screen = Screen::default();
+    let mut body = String::new();
+    let mut wake = Wake::Refresh;
 
     loop {
-        execute!(
-            stdout,
-            cursor::MoveTo(0, 0),
-            terminal::Clear(terminal::ClearType::All)
-        )?;
-
-        match fetch_sessions(&client, args) {
-            Ok(sessions) => {
-                let entries = playing::build_entries(&sessions);
-                let output = playing::format_text(&entries, &colors);
-                for line in output.lines() {
-                    write!(stdout, "{line}\r\n")?;
+        let sessions = match wake {
+            Wake::Quit => break,
+            Wake::Refresh => Some(fetch_sessions(&client, args)),
+            Wake::Sessions(sessions) => Some(Ok(filter_sessions(sessions, args))),
+            Wake::Resize => None,
+        };
+        if let Some(sessions) = sessions {
+            body = match sessions {
+                Ok(sessions) => {
+                    let entries = playing::build_entries(&sessions);
+                    playing::format_text(&entries, &colors)
                 }
-            }
-            Err(e) => {
-                write!(stdout, "Error fetching sessions: {e}\r\n")?;
-            }
+                Err(e) => format!("Error fetching sessions: {e}"),
+            };
         }
 
-        write!(
-            stdout,
-            "\r\nRefreshing every {interval}s | Press q or Ctrl+C to exit\r\n"
-        )?;
-        stdout.flush()?;
+        let mode = if events.is_some() {
+            "Live updates".to_string()
+        } else {
+            format!("Refreshing every {interval}s")
+        };
+        let mut lines: Vec<Line> = body
+            .lines()
+            .map(|line| Line::new(line, Style::Normal))
+            .collect();
+        lines.push(Line::blank());
+        lines.push(Line::new(
+            format!("{mode} | Press q or Ctrl+C to exit"),
+            Style::Normal,
+        ));
+
+        // Sessions are pushed every few seconds, so only redraw what changed
+        screen.draw(&mut stdout, lines, terminal::size()?)?;
 
-        if wait_for_quit(interval) {
-            break;
-        }
+        wake = wait(interval, &mut events);
     }
 
     Ok(())
 }
 
 fn fetch_sessions(client: &Client, args: &PlayingArgs) -> Result<Vec<Session>> {
-    let sessions: Vec<Session> = client.get("/Sessions")?;
+    Ok(filter_sessions(client.sessions().list()?, args))
+}
 
+fn filter_sessions(sessions: Vec<Session>, args: &PlayingArgs) -> Vec<Session> {
     if args.users.is_empty() {
-        Ok(sessions)
+        sessions
     } else {
-        Ok(sessions
+        sessions
             .into_iter()
             .filter(|s| {
                 s.user_name
                     .as_ref()
                     .is_some_and(|name| args.users.iter().any(|u| u == name))
             })
-            .collect())
+            .collect()
     }
 }
 
-/// Poll for quit keys (`q` or Ctrl+C) during the wait interval.
-/// Returns `true` if the user wants to quit.
-fn wait_for_quit(interval_secs: u64) -> bool {
+/// Why [`wait`] returned.
+#[derive(Debug)]
+enum Wake {
+    Quit,
+    Refresh,
+    /// The server pushed the current sessions.
+    Sessions(Vec<Session>),
+    /// The terminal was resized, so redraw without fetching.
+    Resize,
+}
+
+/// Wait for the interval to pass, a quit key (`q` or Ctrl+C), a resize or a
+/// session change from `events`. Stops listening to `events` if the connection is lost.
+fn wait(interval_secs: u64, events: &mut Option<EventStream>) -> Wake {
     let total = Duration::from_secs(interval_secs);
     let poll_interval = Duration::from_millis(250);
     let mut elapsed = Duration::ZERO;
@@ -155,27 +188,68 @@ fn wait_for_quit(interval_secs: u64) -> bool {
         let wait = remaining.min(poll_interval);
 
         if event::poll(wait).unwrap_or(false) {
-            if let Ok(Event::Key(key)) = event::read() {
-                if key.code == KeyCode::Char('q')
-                    || (key.code == KeyCode::Char('c')
-                        && key.modifiers.contains(KeyModifiers::CONTROL))
+            match event::read() {
+                Ok(Event::Key(key))
+                    if key.code == KeyCode::Char('q')
+                        || (key.code == KeyCode::Char('c')
+                            && key.modifiers.contains(KeyModifiers::CONTROL)) =>
                 {
-                    return true;
+                    return Wake::Quit;
+                }
+                Ok(Event::Resize(..)) => return Wake::Resize,
+                _ => {}
+            }
+        }
+
+        while let Some(event) = events.as_ref().and_then(|e| e.next_timeout(Duration::ZERO)) {
+            match event {
+                Ok(event) => {
+                    if let Some(wake) = session_update(event) {
+                        return wake;
+                    }
                 }
+                Err(_) => *events = None,
             }
         }
 
         elapsed += wait;
     }
 
-    false
+    Wake::Refresh
 }
 
-struct TerminalGuard;
+/// What to do about a server event in watch mode, `None` to ignore it.
+fn session_update(event: ServerEvent) -> Option<Wake> {
+    match event.message_type.as_str() {
+        "Sessions" => serde_json::from_value(event.data).ok().map(Wake::Sessions),
+        "PlaybackStart" | "PlaybackStopped" | "SessionEnded" => Some(Wake::Refresh),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(message_type: &str, data: serde_json::Value) -> ServerEvent {
+        ServerEvent {
+            message_type: message_type.to_string(),
+            data,
+        }
+    }
 
-impl Drop for TerminalGuard {
-    fn drop(&mut self) {
-        let _ = terminal::disable_raw_mode();
-        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    #[test]
+    fn session_updates() {
+        let pushed = session_update(event(
+            "Sessions",
+            serde_json::json!([{"Id": "abc", "UserName": "josh"}]),
+        ));
+        assert!(matches!(pushed, Some(Wake::Sessions(s)) if s[0].id.as_deref() == Some("abc")));
+
+        assert!(matches!(
+            session_update(event("PlaybackStopped", serde_json::Value::Null)),
+            Some(Wake::Refresh)
+        ));
+        assert!(session_update(event("LibraryChanged", serde_json::json!({}))).is_none());
     }
 }
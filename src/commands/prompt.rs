@@ -0,0 +1,88 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::error::{Error, Result};
+
+/// Print `label` to stderr and read a line from stdin.
+pub fn prompt(label: &str) -> Result<String> {
+    eprint!("{label}");
+    io::stderr().flush()?;
+    read_stdin_line()
+}
+
+pub fn read_stdin_line() -> Result<String> {
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Ask a yes/no question, defaulting to no. Fails unless stdin is a terminal,
+/// so scripts have to pass `--yes` explicitly.
+pub fn confirm(question: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(Error::Usage(format!(
+            "{question} Pass --yes to confirm, stdin is not a terminal"
+        )));
+    }
+
+    let answer = prompt(&format!("{question} [y/N] "))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Read a password from the terminal without echoing it.
+pub fn prompt_password(label: &str) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        return Err(Error::Usage(
+            "Cannot prompt for a password, stdin is not a terminal (use --password-stdin)"
+                .to_string(),
+        ));
+    }
+
+    eprint!("{label}");
+    io::stderr().flush()?;
+
+    let password = {
+        terminal::enable_raw_mode()?;
+        let _guard = RawModeGuard;
+        read_password_keys()?
+    };
+
+    eprintln!();
+    Ok(password)
+}
+
+fn read_password_keys() -> Result<String> {
+    let mut password = String::new();
+
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Enter => return Ok(password),
+            KeyCode::Backspace => {
+                password.pop();
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled").into());
+            }
+            KeyCode::Char(c) => password.push(c),
+            _ => {}
+        }
+    }
+}
+
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
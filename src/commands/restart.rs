@@ -1,10 +1,10 @@
+use crate::cli::GlobalArgs;
 use crate::client::Client;
-use crate::config::Config;
 use crate::error::Result;
 
-pub fn run() -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    client.post("/System/Restart", None)?;
+    client.system().restart()?;
     Ok(())
 }
@@ -1,66 +1,250 @@
-use crate::cli::ScanArgs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::RefreshOptions;
+use crate::cli::{GlobalArgs, ScanArgs};
 use crate::client::Client;
-use crate::config::Config;
+use crate::emby::tasks;
 use crate::emby::types::VirtualFolder;
 use crate::error::{Error, Result};
+use crate::format::progress::{self, ProgressLine};
 
-pub fn run(args: &ScanArgs) -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs, args: &ScanArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
 
-    let recursive = !args.no_recursive;
+    if !args.paths.is_empty() {
+        client
+            .library()
+            .media_updated(&args.paths, &args.update_type)?;
+        for path in &args.paths {
+            println!(
+                "Notified Emby of {} path {path}",
+                args.update_type.to_lowercase()
+            );
+        }
+        return Ok(());
+    }
 
-    // Normalize library type names
-    let target_types: Vec<String> = args
-        .libraries
-        .iter()
-        .map(|s| match s.as_str() {
+    let folders = client.library().virtual_folders()?;
+    let matching = select_libraries(&folders, &args.libraries)?;
+    let timeout = args.timeout.map(Duration::from_secs);
+
+    let options = RefreshOptions {
+        recursive: !args.no_recursive,
+        metadata_refresh_mode: args.metadata_refresh_mode.clone(),
+        image_refresh_mode: args.image_refresh_mode.clone(),
+        replace_all_metadata: args.replace_all_metadata,
+        replace_all_images: args.replace_all_images,
+    };
+
+    // Refreshing libraries doesn't start the scan task, so run the task itself
+    // when it would do the same thing and follow that instead
+    if args.wait && matching.len() == folders.len() && options == RefreshOptions::default() {
+        let scan_task = tasks::find_scan_task(&client)?;
+        let task_id = scan_task
+            .id
+            .as_deref()
+            .ok_or_else(|| Error::Server("Server did not return a task ID".to_string()))?;
+        client.scheduled_tasks().run(task_id)?;
+        println!("Scanning all libraries");
+
+        let mut line = ProgressLine::new();
+        tasks::wait_for_task(&client, &scan_task, timeout, |task| {
+            let percent = task.current_progress_percentage.unwrap_or(0.0);
+            line.draw(&format!(
+                "Scanning libraries {}",
+                progress::progress_bar(percent, 40)
+            ));
+        })?;
+        line.finish();
+        println!("Library scan completed");
+        return Ok(());
+    }
+
+    for folder in &matching {
+        let id = folder
+            .item_id
+            .as_deref()
+            .ok_or_else(|| Error::Server("Server did not return a library ID".to_string()))?;
+        let collection_type = folder.collection_type.as_deref().unwrap_or("mixed");
+
+        client.items().refresh(id, &options)?;
+        println!("Scanning for {collection_type} in library ID {id}");
+    }
+
+    if args.wait {
+        let ids: Vec<&str> = matching
+            .iter()
+            .filter_map(|f| f.item_id.as_deref())
+            .collect();
+        wait_for_refresh(&client, &ids, timeout)?;
+        println!("Library scan completed");
+    }
+
+    Ok(())
+}
+
+/// Poll the libraries with the given IDs until none of them is being
+/// refreshed, drawing the progress of the furthest behind.
+///
+/// Emby only marks a library as refreshing once its queued refresh starts,
+/// so one that's never seen refreshing counts as done after
+/// [`tasks::START_POLLS`] polls.
+fn wait_for_refresh(client: &Client, ids: &[&str], timeout: Option<Duration>) -> Result<()> {
+    let started = Instant::now();
+    let mut line = ProgressLine::new();
+    let mut seen_active = false;
+    let mut polls = 0;
+
+    loop {
+        let folders = client.library().virtual_folders()?;
+        polls += 1;
+
+        let progress = folders
+            .iter()
+            .filter(|f| {
+                f.item_id.as_deref().is_some_and(|id| ids.contains(&id))
+                    && f.refresh_status.as_deref() == Some("Active")
+            })
+            .map(|f| f.refresh_progress.unwrap_or(0.0))
+            .reduce(f64::min);
+
+        match progress {
+            Some(percent) => {
+                seen_active = true;
+                line.draw(&format!(
+                    "Scanning libraries {}",
+                    progress::progress_bar(percent, 40)
+                ));
+            }
+            None if seen_active || polls >= tasks::START_POLLS => {
+                line.finish();
+                return Ok(());
+            }
+            None => {}
+        }
+
+        if timeout.is_some_and(|t| started.elapsed() >= t) {
+            line.finish();
+            return Err(Error::Task(
+                "Timed out waiting for the library scan".to_string(),
+            ));
+        }
+
+        thread::sleep(tasks::POLL_INTERVAL);
+    }
+}
+
+/// Pick the libraries named by `targets`, in server order.
+///
+/// Each target is `all`, a collection type (`shows` and `tv` are aliases for
+/// `tvshows`), or a library's display name or ID.
+fn select_libraries<'a>(
+    folders: &'a [VirtualFolder],
+    targets: &[String],
+) -> Result<Vec<&'a VirtualFolder>> {
+    let mut selected = vec![false; folders.len()];
+
+    for target in targets {
+        let collection_type = match target.to_lowercase().as_str() {
             "shows" | "tv" => "tvshows".to_string(),
             other => other.to_string(),
-        })
-        .collect();
+        };
+
+        let mut found = false;
+        for (folder, selected) in folders.iter().zip(selected.iter_mut()) {
+            let is_match = collection_type == "all"
+                || folder.collection_type.as_deref() == Some(collection_type.as_str())
+                || folder
+                    .name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(target))
+                || folder.item_id.as_deref() == Some(target.as_str());
 
-    let is_all = target_types.iter().any(|t| t == "all");
+            if is_match {
+                *selected = true;
+                found = true;
+            }
+        }
 
-    // Fetch virtual folders (libraries)
-    let folders: Vec<VirtualFolder> = client.get("/Library/VirtualFolders")?;
+        if !found {
+            let available: Vec<&str> = folders.iter().filter_map(|f| f.name.as_deref()).collect();
+            return Err(Error::NotFound(format!(
+                "No library matches '{target}' (available: {})",
+                available.join(", ")
+            )));
+        }
+    }
 
-    // Filter to supported collection types
-    let supported = ["movies", "tvshows", "music"];
     let matching: Vec<&VirtualFolder> = folders
         .iter()
-        .filter(|f| {
-            f.collection_type
-                .as_deref()
-                .is_some_and(|ct| supported.contains(&ct))
-        })
-        .filter(|f| {
-            is_all
-                || f.collection_type
-                    .as_deref()
-                    .is_some_and(|ct| target_types.iter().any(|t| t == ct))
-        })
+        .zip(selected)
+        .filter_map(|(folder, selected)| selected.then_some(folder))
         .collect();
 
     if matching.is_empty() {
-        return Err(Error::Config("No libraries found".to_string()));
+        return Err(Error::NotFound("No libraries found".to_string()));
     }
 
-    let body = serde_json::json!({
-        "Recursive": recursive,
-        "MetadataRefreshMode": args.metadata_refresh_mode,
-        "ImageRefreshMode": args.image_refresh_mode,
-        "ReplaceAllMetadata": args.replace_all_metadata,
-        "ReplaceAllImages": args.replace_all_images,
-    });
+    Ok(matching)
+}
 
-    for folder in &matching {
-        let id = folder.item_id.as_deref().unwrap_or("");
-        let collection_type = folder.collection_type.as_deref().unwrap_or("unknown");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        client.post(&format!("/Items/{id}/Refresh"), Some(&body))?;
-        println!("Scanning for {collection_type} in library ID {id}");
+    fn folder(name: &str, collection_type: Option<&str>, id: &str) -> VirtualFolder {
+        VirtualFolder {
+            name: Some(name.to_string()),
+            collection_type: collection_type.map(String::from),
+            item_id: Some(id.to_string()),
+            locations: None,
+            refresh_status: None,
+            refresh_progress: None,
+        }
     }
 
-    Ok(())
+    fn folders() -> Vec<VirtualFolder> {
+        vec![
+            folder("Movies", Some("movies"), "lib-001"),
+            folder("TV Shows", Some("tvshows"), "lib-002"),
+            folder("Home Videos", Some("homevideos"), "lib-003"),
+            folder("Mixed", None, "lib-004"),
+        ]
+    }
+
+    fn ids(folders: &[&VirtualFolder]) -> Vec<String> {
+        folders.iter().filter_map(|f| f.item_id.clone()).collect()
+    }
+
+    fn targets(targets: &[&str]) -> Vec<String> {
+        targets.iter().map(|t| (*t).to_string()).collect()
+    }
+
+    #[test]
+    fn select_libraries_all_includes_every_type() {
+        let folders = folders();
+        let selected = select_libraries(&folders, &targets(&["all"])).unwrap();
+        assert_eq!(ids(&selected), ["lib-001", "lib-002", "lib-003", "lib-004"]);
+    }
+
+    #[test]
+    fn select_libraries_by_type_name_and_id() {
+        let folders = folders();
+        let selected =
+            select_libraries(&folders, &targets(&["mixed", "tv", "homevideos"])).unwrap();
+        assert_eq!(ids(&selected), ["lib-002", "lib-003", "lib-004"]);
+
+        let selected = select_libraries(&folders, &targets(&["lib-001", "movies"])).unwrap();
+        assert_eq!(ids(&selected), ["lib-001"]);
+    }
+
+    #[test]
+    fn select_libraries_unknown_target() {
+        let folders = folders();
+        let err = select_libraries(&folders, &targets(&["books"])).unwrap_err();
+        assert!(err.to_string().contains("No library matches 'books'"));
+        assert!(err.to_string().contains("Movies, TV Shows"));
+    }
 }
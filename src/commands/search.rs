@@ -1,55 +1,260 @@
-use crate::cli::SearchArgs;
+use crate::api::SearchOptions;
+use crate::cli::{
+    self, GlobalArgs, ItemType, PersonType, Resolution, SearchArgs, SearchField, SearchSort,
+};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::ticks;
-use crate::emby::types::{BaseItemDto, QueryResultBaseItemDto};
-use crate::error::Result;
-use crate::format::table;
-
-pub fn run(args: &SearchArgs) -> Result<()> {
-    let config = Config::load()?;
+use crate::emby::types::BaseItemDto;
+use crate::emby::{libraries, ticks, users};
+use crate::error::{Error, Result};
+use crate::format::output;
+
+const DEFAULT_FIELDS: [SearchField; 4] = [
+    SearchField::Type,
+    SearchField::Name,
+    SearchField::Year,
+    SearchField::Id,
+];
+
+pub fn run(global: &GlobalArgs, args: &SearchArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let limit = args.limit.to_string();
-    let response: QueryResultBaseItemDto = client.get_with_query(
-        "/Items",
-        &[
-            ("SearchTerm", args.query.as_str()),
-            ("Recursive", "true"),
-            ("Limit", &limit),
-            ("Fields", "ProductionYear,PremiereDate,SeriesName"),
-            ("ExcludeItemTypes", "Folder,UserView,CollectionFolder"),
-        ],
-    )?;
-
-    let items = response.items.unwrap_or_default();
-
-    if items.is_empty() {
-        println!("No results found");
-        return Ok(());
-    }
 
-    let rows: Vec<Vec<String>> = items
-        .iter()
-        .map(|item| {
-            let media_type = item.media_type.as_deref().unwrap_or("").to_string();
-            let name = format_search_name(item);
-            let year = item
-                .production_year
-                .map_or_else(String::new, |y| y.to_string());
-            let id = item.id.as_deref().unwrap_or("").to_string();
-
-            vec![media_type, name, year, id]
-        })
-        .collect();
-
-    println!(
-        "{}",
-        table::build_table(&["Type", "Name", "Year", "ID"], rows)
-    );
+    let fields = if args.fields.is_empty() {
+        &DEFAULT_FIELDS[..]
+    } else {
+        &args.fields
+    };
+    let options = search_options(&client, args, fields)?;
+    let headers: Vec<&str> = fields.iter().map(|f| header(*f)).collect();
+
+    let pages = client
+        .items()
+        .search_pages(&options)
+        .map(|page| page.map(|items| items.iter().map(|item| row(item, fields)).collect()));
+
+    output::print_pages(global.output, &headers, pages, "No results found")?;
 
     Ok(())
 }
 
+fn search_options(
+    client: &Client,
+    args: &SearchArgs,
+    fields: &[SearchField],
+) -> Result<SearchOptions> {
+    let years = match args.year.as_deref() {
+        Some(year) => parse_years(year).ok_or_else(|| {
+            Error::Usage(format!(
+                "Invalid year '{year}', expected e.g. 1995, 1990-1999 or 1990s"
+            ))
+        })?,
+        None => Vec::new(),
+    };
+
+    let parent_id = match args.library.as_deref() {
+        Some(library) => Some(
+            libraries::find_library(client, library)?
+                .item_id
+                .ok_or_else(|| Error::Server("Server did not return a library ID".to_string()))?,
+        ),
+        None => None,
+    };
+
+    let is_played = match (args.played, args.unplayed) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    };
+    let user_id = match is_played {
+        Some(_) => Some(users::resolve_user_id(client, args.user.as_deref())?),
+        None => None,
+    };
+
+    let (is_4k, is_hd) = match args.resolution {
+        Some(Resolution::Uhd) => (Some(true), None),
+        Some(Resolution::Hd) => (Some(false), Some(true)),
+        Some(Resolution::Sd) => (None, Some(false)),
+        None => (None, None),
+    };
+
+    let (sort_by, sort_order) = match args.sort {
+        Some(sort) => {
+            let (sort_by, descending) = sort_by(sort);
+            let order = if descending ^ args.reverse {
+                "Descending"
+            } else {
+                "Ascending"
+            };
+            (Some(sort_by.to_string()), Some(order.to_string()))
+        }
+        None => (None, None),
+    };
+
+    Ok(SearchOptions {
+        search_term: args.query.clone().unwrap_or_default(),
+        limit: cli::result_limit(args.limit, args.all),
+        fields: Some(emby_fields(fields)),
+        include_item_types: join(args.types.iter().map(|t| item_type(*t))),
+        exclude_item_types: args
+            .types
+            .is_empty()
+            .then(|| "Folder,UserView,CollectionFolder".to_string()),
+        genres: args.genres.clone(),
+        years,
+        official_ratings: args.parental_ratings.clone(),
+        studios: args.studios.clone(),
+        parent_id,
+        user_id,
+        is_played,
+        person: args.person.clone(),
+        person_types: join(args.person_types.iter().map(|t| person_type(*t))),
+        is_4k,
+        is_hd,
+        sort_by,
+        sort_order,
+        ..SearchOptions::default()
+    })
+}
+
+/// Parse `1995`, `1990-1999` or `1990s` into the years they cover.
+fn parse_years(value: &str) -> Option<Vec<u32>> {
+    let (first, last) = if let Some(decade) = value.strip_suffix('s') {
+        let first: u32 = decade.parse().ok().filter(|y| y % 10 == 0)?;
+        (first, first.checked_add(9)?)
+    } else if let Some((first, last)) = value.split_once('-') {
+        (first.trim().parse().ok()?, last.trim().parse().ok()?)
+    } else {
+        let year = value.trim().parse().ok()?;
+        (year, year)
+    };
+
+    let valid = (1800..=3000).contains(&first) && (first..=3000).contains(&last);
+    valid.then(|| (first..=last).collect())
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> Option<String> {
+    let values: Vec<&str> = values.collect();
+    (!values.is_empty()).then(|| values.join(","))
+}
+
+/// The `IncludeItemTypes` name Emby uses for an item type.
+pub(crate) fn item_type(item_type: ItemType) -> &'static str {
+    match item_type {
+        ItemType::Movie => "Movie",
+        ItemType::Series => "Series",
+        ItemType::Season => "Season",
+        ItemType::Episode => "Episode",
+        ItemType::Audio => "Audio",
+        ItemType::Album => "MusicAlbum",
+        ItemType::Artist => "MusicArtist",
+        ItemType::MusicVideo => "MusicVideo",
+        ItemType::Video => "Video",
+        ItemType::Book => "Book",
+        ItemType::Photo => "Photo",
+        ItemType::Collection => "BoxSet",
+    }
+}
+
+fn person_type(person_type: PersonType) -> &'static str {
+    match person_type {
+        PersonType::Actor => "Actor",
+        PersonType::Director => "Director",
+        PersonType::Writer => "Writer",
+        PersonType::Producer => "Producer",
+        PersonType::GuestStar => "GuestStar",
+        PersonType::Composer => "Composer",
+    }
+}
+
+/// Emby's `SortBy` for a sort key, and whether it sorts descending by
+/// default. Ties are broken by name.
+fn sort_by(sort: SearchSort) -> (&'static str, bool) {
+    match sort {
+        SearchSort::Name => ("SortName", false),
+        SearchSort::DateAdded => ("DateCreated,SortName", true),
+        SearchSort::Premiere => ("PremiereDate,SortName", true),
+        SearchSort::Rating => ("CommunityRating,SortName", true),
+        SearchSort::Runtime => ("Runtime,SortName", true),
+    }
+}
+
+/// The `Fields` to request so every selected column can be filled in.
+fn emby_fields(fields: &[SearchField]) -> String {
+    let mut names = vec!["ProductionYear", "PremiereDate", "SeriesName"];
+    for field in fields {
+        let name = match field {
+            SearchField::Rating => "OfficialRating",
+            SearchField::Score => "CommunityRating",
+            SearchField::Genres => "Genres",
+            SearchField::Studios => "Studios",
+            SearchField::Added => "DateCreated",
+            SearchField::Path => "Path",
+            _ => continue,
+        };
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names.join(",")
+}
+
+fn header(field: SearchField) -> &'static str {
+    match field {
+        SearchField::Type => "Type",
+        SearchField::Name => "Name",
+        SearchField::Year => "Year",
+        SearchField::Id => "ID",
+        SearchField::Rating => "Rating",
+        SearchField::Score => "Score",
+        SearchField::Runtime => "Runtime",
+        SearchField::Genres => "Genres",
+        SearchField::Studios => "Studios",
+        SearchField::Added => "Added",
+        SearchField::Premiered => "Premiered",
+        SearchField::Path => "Path",
+    }
+}
+
+fn row(item: &BaseItemDto, fields: &[SearchField]) -> Vec<String> {
+    fields.iter().map(|field| cell(item, *field)).collect()
+}
+
+fn cell(item: &BaseItemDto, field: SearchField) -> String {
+    let text = |value: Option<&str>| value.unwrap_or("").to_string();
+    match field {
+        SearchField::Type => text(item.media_type.as_deref()),
+        SearchField::Name => format_search_name(item),
+        SearchField::Year => item
+            .production_year
+            .map_or_else(String::new, |y| y.to_string()),
+        SearchField::Id => text(item.id.as_deref()),
+        SearchField::Rating => text(item.official_rating.as_deref()),
+        SearchField::Score => item
+            .community_rating
+            .map_or_else(String::new, |r| format!("{r:.1}")),
+        SearchField::Runtime => item.run_time_ticks.map_or_else(String::new, |t| {
+            ticks::seconds_to_time(ticks::ticks_to_seconds(t))
+        }),
+        SearchField::Genres => item.genres.as_deref().unwrap_or_default().join(", "),
+        SearchField::Studios => item
+            .studios
+            .iter()
+            .flatten()
+            .filter_map(|s| s.name.as_deref())
+            .collect::<Vec<_>>()
+            .join(", "),
+        SearchField::Added => item
+            .date_created
+            .as_deref()
+            .map_or_else(String::new, ticks::format_premiere_date),
+        SearchField::Premiered => item
+            .premiere_date
+            .as_deref()
+            .map_or_else(String::new, ticks::format_premiere_date),
+        SearchField::Path => text(item.path.as_deref()),
+    }
+}
+
 pub(crate) fn format_search_name(item: &BaseItemDto) -> String {
     let name = item.name.as_deref().unwrap_or("");
     let media_type = item.media_type.as_deref().unwrap_or("");
@@ -72,21 +277,9 @@ mod tests {
 
     fn item(media_type: &str) -> BaseItemDto {
         BaseItemDto {
-            id: None,
             name: Some("Test Name".to_string()),
             media_type: Some(media_type.to_string()),
-            series_name: None,
-            production_year: None,
-            premiere_date: None,
-            date_created: None,
-            index_number: None,
-            parent_index_number: None,
-            run_time_ticks: None,
-            overview: None,
-            container: None,
-            official_rating: None,
-            album: None,
-            album_artist: None,
+            ..BaseItemDto::default()
         }
     }
 
@@ -111,4 +304,26 @@ mod tests {
         let h = item("Movie");
         assert_eq!(format_search_name(&h), "Test Name");
     }
+
+    #[test]
+    fn test_parse_years() {
+        assert_eq!(parse_years("1995"), Some(vec![1995]));
+        assert_eq!(parse_years("1998-2001"), Some(vec![1998, 1999, 2000, 2001]));
+        assert_eq!(
+            parse_years("1990s").map(|y| (y.len(), y[9])),
+            Some((10, 1999))
+        );
+        assert_eq!(parse_years("1995s"), None);
+        assert_eq!(parse_years("2001-1998"), None);
+        assert_eq!(parse_years("ninety"), None);
+        assert_eq!(parse_years("4294967290s"), None);
+    }
+
+    #[test]
+    fn emby_fields_adds_columns() {
+        assert_eq!(
+            emby_fields(&[SearchField::Name, SearchField::Genres, SearchField::Added]),
+            "ProductionYear,PremiereDate,SeriesName,Genres,DateCreated"
+        );
+    }
 }
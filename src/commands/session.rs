@@ -0,0 +1,220 @@
+use crate::api::PlaystateCommand;
+use crate::cli::{GlobalArgs, SessionArgs, SessionCommand, SessionTarget};
+use crate::client::Client;
+use crate::emby::ticks;
+use crate::emby::types::Session;
+use crate::error::{Error, Result};
+
+pub fn run(global: &GlobalArgs, args: &SessionArgs) -> Result<()> {
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let sessions = client.sessions();
+
+    match &args.command {
+        SessionCommand::Pause(target) => for_each_session(&client, target, true, "Paused", |id| {
+            sessions.playing(id, PlaystateCommand::Pause)
+        }),
+        SessionCommand::Resume(target) => {
+            for_each_session(&client, target, true, "Resumed", |id| {
+                sessions.playing(id, PlaystateCommand::Unpause)
+            })
+        }
+        SessionCommand::Stop(target) => for_each_session(&client, target, true, "Stopped", |id| {
+            sessions.playing(id, PlaystateCommand::Stop)
+        }),
+        SessionCommand::Next(target) => {
+            for_each_session(&client, target, true, "Skipped forward", |id| {
+                sessions.playing(id, PlaystateCommand::NextTrack)
+            })
+        }
+        SessionCommand::Previous(target) => {
+            for_each_session(&client, target, true, "Skipped back", |id| {
+                sessions.playing(id, PlaystateCommand::PreviousTrack)
+            })
+        }
+        SessionCommand::Seek(seek_args) => {
+            let seconds = ticks::parse_time(&seek_args.position).ok_or_else(|| {
+                Error::Usage(format!(
+                    "Invalid position '{}'. Use HH:MM:SS, MM:SS or seconds",
+                    seek_args.position
+                ))
+            })?;
+            let position = ticks::seconds_to_ticks(seconds).ok_or_else(|| {
+                Error::Usage(format!("Position '{}' is too large", seek_args.position))
+            })?;
+            let action = format!("Seeked to {}", ticks::seconds_to_time(seconds));
+            for_each_session(&client, &seek_args.target, true, &action, |id| {
+                sessions.seek(id, position)
+            })
+        }
+        SessionCommand::Volume(volume_args) => {
+            let action = format!("Set volume to {}", volume_args.volume);
+            for_each_session(&client, &volume_args.target, true, &action, |id| {
+                sessions.set_volume(id, volume_args.volume)
+            })
+        }
+        SessionCommand::Message(message_args) => {
+            let timeout_ms = message_args
+                .timeout
+                .map(|t| {
+                    t.checked_mul(1000)
+                        .ok_or_else(|| Error::Usage(format!("Timeout {t} is too large")))
+                })
+                .transpose()?;
+            for_each_session(&client, &message_args.target, false, "Sent message", |id| {
+                sessions.send_message(id, &message_args.header, &message_args.text, timeout_ms)
+            })
+        }
+    }
+}
+
+/// Resolve the target sessions and apply `action` to each, printing `label`
+/// with the session's user and device.
+fn for_each_session(
+    client: &Client,
+    target: &SessionTarget,
+    playing_only: bool,
+    label: &str,
+    action: impl Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let sessions = client.sessions().list()?;
+    let matching = select_sessions(&sessions, target, playing_only)?;
+
+    for session in matching {
+        let id = session.id.as_deref().unwrap_or("");
+        action(id)?;
+        println!(
+            "{label}: {} on {} (session {id})",
+            session.user_name.as_deref().unwrap_or("Unknown"),
+            session.device_name.as_deref().unwrap_or("Unknown"),
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) fn select_sessions<'a>(
+    sessions: &'a [Session],
+    target: &SessionTarget,
+    playing_only: bool,
+) -> Result<Vec<&'a Session>> {
+    let matching: Vec<&Session> = sessions
+        .iter()
+        .filter(|s| s.id.is_some())
+        .filter(|s| !playing_only || s.now_playing_item.is_some())
+        .filter(|s| matches_target(s, target))
+        .collect();
+
+    match matching.len() {
+        0 if playing_only => Err(Error::NotFound(
+            "No matching session is playing anything".to_string(),
+        )),
+        0 => Err(Error::NotFound("No matching session found".to_string())),
+        1 => Ok(matching),
+        _ if target.all => Ok(matching),
+        n => {
+            let ids: Vec<String> = matching
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{} ({}@{})",
+                        s.id.as_deref().unwrap_or(""),
+                        s.user_name.as_deref().unwrap_or("Unknown"),
+                        s.device_name.as_deref().unwrap_or("Unknown"),
+                    )
+                })
+                .collect();
+            Err(Error::Usage(format!(
+                "{n} sessions match, narrow it down or pass --all: {}",
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+fn matches_target(session: &Session, target: &SessionTarget) -> bool {
+    let eq =
+        |value: Option<&str>, wanted: &str| value.is_some_and(|v| v.eq_ignore_ascii_case(wanted));
+
+    target
+        .id
+        .as_deref()
+        .is_none_or(|id| session.id.as_deref() == Some(id))
+        && target
+            .user
+            .as_deref()
+            .is_none_or(|user| eq(session.user_name.as_deref(), user))
+        && target.device.as_deref().is_none_or(|device| {
+            eq(session.device_name.as_deref(), device)
+                || session.device_id.as_deref() == Some(device)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, user: &str, device: &str, playing: bool) -> Session {
+        serde_json::from_value(serde_json::json!({
+            "Id": id,
+            "UserName": user,
+            "DeviceId": format!("{device}-id"),
+            "DeviceName": device,
+            "NowPlayingItem": if playing { serde_json::json!({"Name": "x"}) } else { serde_json::Value::Null },
+        }))
+        .unwrap()
+    }
+
+    fn target(user: Option<&str>, device: Option<&str>, all: bool) -> SessionTarget {
+        SessionTarget {
+            id: None,
+            user: user.map(String::from),
+            device: device.map(String::from),
+            all,
+        }
+    }
+
+    fn sessions() -> Vec<Session> {
+        vec![
+            session("s1", "josh", "Apple TV", true),
+            session("s2", "josh", "iPhone", true),
+            session("s3", "bob", "Browser", false),
+        ]
+    }
+
+    #[test]
+    fn select_by_user_and_device() {
+        let sessions = sessions();
+        let found = select_sessions(
+            &sessions,
+            &target(Some("JOSH"), Some("iphone"), false),
+            true,
+        )
+        .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id.as_deref(), Some("s2"));
+    }
+
+    #[test]
+    fn select_by_device_id() {
+        let sessions = sessions();
+        let found =
+            select_sessions(&sessions, &target(None, Some("Apple TV-id"), false), true).unwrap();
+        assert_eq!(found[0].id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn select_ambiguous_requires_all() {
+        let sessions = sessions();
+        assert!(select_sessions(&sessions, &target(Some("josh"), None, false), true).is_err());
+        let found = select_sessions(&sessions, &target(Some("josh"), None, true), true).unwrap();
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn select_idle_only_for_messages() {
+        let sessions = sessions();
+        assert!(select_sessions(&sessions, &target(Some("bob"), None, false), true).is_err());
+        assert!(select_sessions(&sessions, &target(Some("bob"), None, false), false).is_ok());
+    }
+}
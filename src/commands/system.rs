@@ -1,12 +1,12 @@
+use crate::cli::GlobalArgs;
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::types::SystemInfo;
 use crate::error::Result;
+use crate::format::output::{self, OutputFormat};
 
-pub fn run() -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let info: SystemInfo = client.get("/System/Info")?;
+    let info = client.system().info()?;
 
     let version = info.version.as_deref().unwrap_or("Unknown");
     let server_name = info.server_name.as_deref().unwrap_or("Unknown");
@@ -20,6 +20,18 @@ pub fn run() -> Result<()> {
         "No"
     };
 
+    if global.output != OutputFormat::Table {
+        let json = serde_json::json!({
+            "version": version,
+            "url": client.api_url(),
+            "server_name": server_name,
+            "operating_system": os,
+            "update_available": info.has_update_available.unwrap_or(false),
+        });
+        println!("{}", output::render_objects(global.output, &[json])?);
+        return Ok(());
+    }
+
     println!("Emby Version:     {version}");
     println!("Emby URL:         {}", client.api_url());
     println!("Server Name:      {server_name}");
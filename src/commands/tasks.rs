@@ -1,39 +1,45 @@
-use crate::cli::{TasksArgs, TasksCommand};
+use std::time::Duration;
+
+use crate::cli::{
+    GlobalArgs, TasksArgs, TasksCommand, TasksRunArgs, TasksTriggersArgs, TriggerKind,
+    TriggersAddArgs, TriggersCommand, Weekday,
+};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::ticks;
-use crate::emby::types::TaskInfo;
-use crate::error::Result;
-use crate::format::table;
+use crate::emby::types::{TaskInfo, TaskTriggerInfo};
+use crate::emby::{tasks, ticks, time};
+use crate::error::{Error, Result};
+use crate::format::output;
+use crate::format::progress::{self, ProgressLine};
 
-pub fn run(args: &TasksArgs) -> Result<()> {
+pub fn run(global: &GlobalArgs, args: &TasksArgs) -> Result<()> {
     match &args.command {
-        Some(TasksCommand::Run(run_args)) => run_task(&run_args.id),
-        None => list_tasks(args.all),
+        Some(TasksCommand::Run(run_args)) => run_task(global, run_args),
+        Some(TasksCommand::Stop(stop_args)) => stop_task(global, &stop_args.task),
+        Some(TasksCommand::Triggers(triggers_args)) => triggers(global, triggers_args),
+        None => list_tasks(global, args.all),
     }
 }
 
-fn list_tasks(show_all: bool) -> Result<()> {
-    let config = Config::load()?;
+fn list_tasks(global: &GlobalArgs, show_all: bool) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let tasks: Vec<TaskInfo> = client.get("/ScheduledTasks")?;
+    let tasks = client.scheduled_tasks().list()?;
 
     let tasks: Vec<&TaskInfo> = tasks
         .iter()
         .filter(|t| show_all || !t.is_hidden.unwrap_or(false))
         .collect();
 
-    if tasks.is_empty() {
-        println!("No scheduled tasks found");
-        return Ok(());
-    }
-
     let rows: Vec<Vec<String>> = tasks
         .iter()
         .map(|t| {
             let category = t.category.as_deref().unwrap_or("").to_string();
             let name = t.name.as_deref().unwrap_or("").to_string();
             let state = t.state.as_deref().unwrap_or("").to_string();
+            let progress = t
+                .current_progress_percentage
+                .filter(|_| tasks::is_running(t))
+                .map_or_else(String::new, |p| format!("{p:.0}%"));
             let (last_run, last_status) = t
                 .last_execution_result
                 .as_ref()
@@ -48,25 +54,294 @@ fn list_tasks(show_all: bool) -> Result<()> {
                 .unwrap_or_default();
             let id = t.id.as_deref().unwrap_or("").to_string();
 
-            vec![category, name, state, last_run, last_status, id]
+            vec![category, name, state, progress, last_run, last_status, id]
         })
         .collect();
 
-    println!(
-        "{}",
-        table::build_table(
-            &["Category", "Name", "State", "Last Run", "Last Status", "ID"],
-            rows
-        )
-    );
+    output::print_rows(
+        global.output,
+        &[
+            "Category",
+            "Name",
+            "State",
+            "Progress",
+            "Last Run",
+            "Last Status",
+            "ID",
+        ],
+        rows,
+        "No scheduled tasks found",
+    )?;
 
     Ok(())
 }
 
-fn run_task(id: &str) -> Result<()> {
-    let config = Config::load()?;
+fn run_task(global: &GlobalArgs, args: &TasksRunArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    client.post(&format!("/ScheduledTasks/Running/{id}"), None)?;
-    println!("Task {id} started");
+
+    // The task as it was before this run, so a quick run isn't mistaken for the last one
+    let task = tasks::find_task(&client, &args.task)?;
+    let id = task.id.as_deref().unwrap_or("");
+    let name = task.name.as_deref().unwrap_or(id);
+
+    client.scheduled_tasks().run(id)?;
+    println!("Task {name} started");
+
+    if args.wait {
+        let mut line = ProgressLine::new();
+        tasks::wait_for_task(
+            &client,
+            &task,
+            args.timeout.map(Duration::from_secs),
+            |task| {
+                let percent = task.current_progress_percentage.unwrap_or(0.0);
+                line.draw(&format!("{name} {}", progress::progress_bar(percent, 40)));
+            },
+        )?;
+        line.finish();
+        println!("Task {name} completed");
+    }
+
     Ok(())
 }
+
+fn stop_task(global: &GlobalArgs, query: &str) -> Result<()> {
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+
+    let task = tasks::find_task(&client, query)?;
+    let id = task.id.as_deref().unwrap_or("");
+    client.scheduled_tasks().stop(id)?;
+    println!("Task {} stopped", task.name.as_deref().unwrap_or(id));
+    Ok(())
+}
+
+fn triggers(global: &GlobalArgs, args: &TasksTriggersArgs) -> Result<()> {
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+
+    let task = tasks::find_task(&client, &args.task)?;
+    let id = task.id.as_deref().unwrap_or("");
+    let name = task.name.as_deref().unwrap_or(id);
+    let mut triggers = task.triggers.clone().unwrap_or_default();
+
+    match &args.command {
+        None => {
+            let rows: Vec<Vec<String>> = triggers
+                .iter()
+                .enumerate()
+                .map(|(i, trigger)| {
+                    vec![
+                        (i + 1).to_string(),
+                        trigger_kind(trigger).to_string(),
+                        describe_schedule(trigger),
+                        trigger
+                            .max_runtime_ticks
+                            .map(|t| time::format_duration(ticks::ticks_to_seconds(t)))
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect();
+
+            output::print_rows(
+                global.output,
+                &["#", "Type", "Schedule", "Max Runtime"],
+                rows,
+                &format!("Task {name} has no triggers"),
+            )?;
+        }
+        Some(TriggersCommand::Add(add_args)) => {
+            let trigger = build_trigger(add_args)?;
+            let description = describe_trigger(&trigger);
+            triggers.push(trigger);
+            client.scheduled_tasks().set_triggers(id, &triggers)?;
+            println!("Added {description} trigger to {name}");
+        }
+        Some(TriggersCommand::Remove(remove_args)) => {
+            let number = remove_args.number;
+            if number == 0 || number > triggers.len() {
+                return Err(Error::NotFound(format!(
+                    "Task '{name}' has no trigger #{number} ({} triggers)",
+                    triggers.len()
+                )));
+            }
+
+            let removed = triggers.remove(number - 1);
+            client.scheduled_tasks().set_triggers(id, &triggers)?;
+            println!(
+                "Removed {} trigger #{number} from {name}",
+                describe_trigger(&removed)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn build_trigger(args: &TriggersAddArgs) -> Result<TaskTriggerInfo> {
+    let time_of_day = args
+        .at
+        .as_deref()
+        .map(|at| {
+            time::parse_time_of_day(at).ok_or_else(|| {
+                Error::Usage(format!("Invalid time of day '{at}', expected e.g. 03:00"))
+            })
+        })
+        .transpose()?;
+    let duration = |value: &str| {
+        time::parse_duration(value)
+            .and_then(|s| u64::try_from(s).ok())
+            .filter(|s| *s > 0)
+            .and_then(ticks::seconds_to_ticks)
+            .ok_or_else(|| {
+                Error::Usage(format!(
+                    "Invalid duration '{value}', expected e.g. 30m, 6h or 1d"
+                ))
+            })
+    };
+    let interval_ticks = args.every.as_deref().map(duration).transpose()?;
+    let max_runtime_ticks = args.max_runtime.as_deref().map(duration).transpose()?;
+
+    let trigger_type = match args.kind {
+        TriggerKind::Daily => "DailyTrigger",
+        TriggerKind::Weekly => "WeeklyTrigger",
+        TriggerKind::Interval => "IntervalTrigger",
+        TriggerKind::Startup => "StartupTrigger",
+    };
+    let uses_time = matches!(args.kind, TriggerKind::Daily | TriggerKind::Weekly);
+
+    Ok(TaskTriggerInfo {
+        trigger_type: trigger_type.to_string(),
+        time_of_day_ticks: time_of_day
+            .filter(|_| uses_time)
+            .and_then(ticks::seconds_to_ticks),
+        interval_ticks: interval_ticks.filter(|_| args.kind == TriggerKind::Interval),
+        day_of_week: args
+            .day
+            .filter(|_| args.kind == TriggerKind::Weekly)
+            .map(|day| weekday_name(day).to_string()),
+        system_event: None,
+        max_runtime_ticks,
+    })
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Sunday => "Sunday",
+        Weekday::Monday => "Monday",
+        Weekday::Tuesday => "Tuesday",
+        Weekday::Wednesday => "Wednesday",
+        Weekday::Thursday => "Thursday",
+        Weekday::Friday => "Friday",
+        Weekday::Saturday => "Saturday",
+    }
+}
+
+/// The trigger type without its `Trigger` suffix, e.g. `Daily`.
+fn trigger_kind(trigger: &TaskTriggerInfo) -> &str {
+    trigger
+        .trigger_type
+        .strip_suffix("Trigger")
+        .unwrap_or(&trigger.trigger_type)
+}
+
+/// Describe when a trigger fires, e.g. `Sunday at 03:00` or `every 6h`.
+fn describe_schedule(trigger: &TaskTriggerInfo) -> String {
+    let at = trigger
+        .time_of_day_ticks
+        .map(|t| time::format_time_of_day(ticks::ticks_to_seconds(t)));
+
+    match trigger.trigger_type.as_str() {
+        "DailyTrigger" => at.map(|at| format!("at {at}")).unwrap_or_default(),
+        "WeeklyTrigger" => format!(
+            "{} at {}",
+            trigger.day_of_week.as_deref().unwrap_or("?"),
+            at.unwrap_or_default()
+        ),
+        "IntervalTrigger" => trigger
+            .interval_ticks
+            .map(|t| {
+                format!(
+                    "every {}",
+                    time::format_duration(ticks::ticks_to_seconds(t))
+                )
+            })
+            .unwrap_or_default(),
+        "StartupTrigger" => "on startup".to_string(),
+        "SystemEventTrigger" => trigger
+            .system_event
+            .as_deref()
+            .map(|event| format!("on {event}"))
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn describe_trigger(trigger: &TaskTriggerInfo) -> String {
+    let kind = trigger_kind(trigger).to_lowercase();
+    match describe_schedule(trigger) {
+        schedule if schedule.is_empty() => kind,
+        schedule => format!("{kind} ({schedule})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_args(kind: TriggerKind) -> TriggersAddArgs {
+        TriggersAddArgs {
+            kind,
+            at: None,
+            day: None,
+            every: None,
+            max_runtime: None,
+        }
+    }
+
+    #[test]
+    fn build_weekly_trigger() {
+        let trigger = build_trigger(&TriggersAddArgs {
+            at: Some("03:30".to_string()),
+            day: Some(Weekday::Sunday),
+            max_runtime: Some("2h".to_string()),
+            ..add_args(TriggerKind::Weekly)
+        })
+        .unwrap();
+
+        assert_eq!(trigger.trigger_type, "WeeklyTrigger");
+        assert_eq!(trigger.time_of_day_ticks, Some(126_000_000_000));
+        assert_eq!(trigger.day_of_week.as_deref(), Some("Sunday"));
+        assert_eq!(trigger.max_runtime_ticks, Some(72_000_000_000));
+        assert_eq!(describe_trigger(&trigger), "weekly (Sunday at 03:30)");
+    }
+
+    #[test]
+    fn build_interval_trigger() {
+        let trigger = build_trigger(&TriggersAddArgs {
+            every: Some("6h".to_string()),
+            ..add_args(TriggerKind::Interval)
+        })
+        .unwrap();
+
+        assert_eq!(trigger.interval_ticks, Some(216_000_000_000));
+        assert_eq!(trigger.time_of_day_ticks, None);
+        assert_eq!(describe_trigger(&trigger), "interval (every 6h)");
+    }
+
+    #[test]
+    fn build_trigger_rejects_bad_values() {
+        let bad_time = TriggersAddArgs {
+            at: Some("25:00".to_string()),
+            ..add_args(TriggerKind::Daily)
+        };
+        assert!(build_trigger(&bad_time).is_err());
+
+        let bad_interval = TriggersAddArgs {
+            every: Some("0m".to_string()),
+            ..add_args(TriggerKind::Interval)
+        };
+        assert!(build_trigger(&bad_interval).is_err());
+    }
+}
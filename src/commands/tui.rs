@@ -0,0 +1,228 @@
+use std::io::{self, Write};
+
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+
+use crate::error::Result;
+
+/// The stream a full screen command draws on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Stdout,
+    /// Leaves stdout free for the command's result, like `pick`'s "Print ID"
+    Stderr,
+}
+
+/// Switches to the alternate screen in raw mode with the cursor hidden,
+/// restoring the terminal when dropped, even on errors and panics.
+pub struct TerminalGuard {
+    target: Target,
+}
+
+impl TerminalGuard {
+    pub fn enter(target: Target) -> Result<Self> {
+        // Created first so a failure part way through still restores
+        let guard = Self { target };
+        match target {
+            Target::Stdout => execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?,
+            Target::Stderr => execute!(io::stderr(), EnterAlternateScreen, cursor::Hide)?,
+        }
+        terminal::enable_raw_mode()?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = match self.target {
+            Target::Stdout => execute!(io::stdout(), cursor::Show, LeaveAlternateScreen),
+            Target::Stderr => execute!(io::stderr(), cursor::Show, LeaveAlternateScreen),
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Normal,
+    Title,
+    Selected,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Line {
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+
+    pub fn blank() -> Self {
+        Self::new(String::new(), Style::Normal)
+    }
+}
+
+/// Redraws only the lines that changed since the last frame, so refreshing
+/// doesn't flicker. Everything is repainted when the terminal is resized.
+#[derive(Default)]
+pub struct Screen {
+    lines: Vec<Line>,
+    size: Option<(u16, u16)>,
+}
+
+impl Screen {
+    /// Draw `lines` on a terminal of `size` columns and rows, cutting off
+    /// whatever doesn't fit.
+    pub fn draw(
+        &mut self,
+        out: &mut impl Write,
+        mut lines: Vec<Line>,
+        size: (u16, u16),
+    ) -> Result<()> {
+        let (width, height) = size;
+        if self.size != Some(size) {
+            queue!(out, terminal::Clear(ClearType::All))?;
+            self.lines.clear();
+            self.size = Some(size);
+        }
+        lines.truncate(usize::from(height));
+
+        for (i, line) in lines.iter().enumerate() {
+            if self.lines.get(i) == Some(line) {
+                continue;
+            }
+
+            let row = u16::try_from(i).unwrap_or(u16::MAX);
+            let text = truncate(&line.text, usize::from(width));
+            queue!(out, cursor::MoveTo(0, row))?;
+            match line.style {
+                Style::Normal => queue!(out, Print(text))?,
+                Style::Title => queue!(
+                    out,
+                    SetAttribute(Attribute::Bold),
+                    Print(text),
+                    SetAttribute(Attribute::Reset)
+                )?,
+                Style::Selected => queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(text),
+                    SetAttribute(Attribute::Reset)
+                )?,
+            }
+            queue!(out, terminal::Clear(ClearType::UntilNewLine))?;
+        }
+
+        for i in lines.len()..self.lines.len() {
+            let row = u16::try_from(i).unwrap_or(u16::MAX);
+            queue!(
+                out,
+                cursor::MoveTo(0, row),
+                terminal::Clear(ClearType::CurrentLine)
+            )?;
+        }
+
+        out.flush()?;
+        self.lines = lines;
+        Ok(())
+    }
+}
+
+/// Cut `text` to `width` visible characters so it doesn't wrap. ANSI color
+/// codes don't count towards the width, and colored text that's cut is
+/// reset so the color doesn't run on.
+fn truncate(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut visible = 0;
+    let mut colored = false;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            result.push(c);
+            for c in chars.by_ref() {
+                result.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            colored = true;
+            continue;
+        }
+
+        if visible == width {
+            if colored {
+                result.push_str("\x1b[0m");
+            }
+            break;
+        }
+        result.push(c);
+        visible += 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(screen: &mut Screen, lines: Vec<Line>, size: (u16, u16)) -> String {
+        let mut out = Vec::new();
+        screen.draw(&mut out, lines, size).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn screen_redraws_changed_lines() {
+        let mut screen = Screen::default();
+        let lines = vec![
+            Line::new("one", Style::Normal),
+            Line::new("two", Style::Normal),
+        ];
+
+        let first = draw(&mut screen, lines.clone(), (80, 24));
+        assert!(first.contains("one") && first.contains("two"));
+
+        let mut changed = lines.clone();
+        changed[1].text = "three".to_string();
+        let second = draw(&mut screen, changed.clone(), (80, 24));
+        assert!(!second.contains("one"));
+        assert!(second.contains("three"));
+
+        // A resize repaints everything
+        let resized = draw(&mut screen, changed, (100, 24));
+        assert!(resized.contains("one") && resized.contains("three"));
+    }
+
+    #[test]
+    fn screen_cuts_off_lines_that_dont_fit() {
+        let mut screen = Screen::default();
+        let lines = vec![
+            Line::new("first line", Style::Normal),
+            Line::new("second", Style::Normal),
+        ];
+
+        let out = draw(&mut screen, lines, (5, 1));
+        assert!(out.contains("first"));
+        assert!(!out.contains("first line"));
+        assert!(!out.contains("second"));
+    }
+
+    #[test]
+    fn truncate_skips_color_codes() {
+        assert_eq!(truncate("Heat", 10), "Heat");
+        assert_eq!(truncate("Heat (1995)", 4), "Heat");
+        assert_eq!(
+            truncate("\x1b[1mHeat\x1b[0m (1995)", 6),
+            "\x1b[1mHeat\x1b[0m (\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cli::{GlobalArgs, Rating, UserDataArgs};
+use crate::client::Client;
+use crate::commands::search::format_search_name;
+use crate::emby::users;
+use crate::error::Result;
+
+/// A change to a user's data for an item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Played,
+    Unplayed,
+    Favorite,
+    Unfavorite,
+    Rate(Rating),
+}
+
+impl Action {
+    pub(crate) fn apply(self, client: &Client, user_id: &str, id: &str) -> Result<()> {
+        let user_library = client.user_library();
+        match self {
+            Self::Played => user_library.mark_played(user_id, id),
+            Self::Unplayed => user_library.mark_unplayed(user_id, id),
+            Self::Favorite => user_library.add_favorite(user_id, id),
+            Self::Unfavorite => user_library.remove_favorite(user_id, id),
+            Self::Rate(Rating::Like) => user_library.set_like(user_id, id, true),
+            Self::Rate(Rating::Dislike) => user_library.set_like(user_id, id, false),
+            Self::Rate(Rating::Clear) => user_library.clear_like(user_id, id),
+        }
+    }
+
+    /// Completes "Marked ... {description}", e.g. "as played".
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Self::Played => "as played",
+            Self::Unplayed => "as unplayed",
+            Self::Favorite => "as a favorite",
+            Self::Unfavorite => "as not a favorite",
+            Self::Rate(Rating::Like) => "as liked",
+            Self::Rate(Rating::Dislike) => "as disliked",
+            Self::Rate(Rating::Clear) => "as neither liked nor disliked",
+        }
+    }
+}
+
+pub fn run(global: &GlobalArgs, args: &UserDataArgs, action: Action) -> Result<()> {
+    let config = global.load_config()?;
+    let client = Client::new(&config);
+    let user_id = users::resolve_user_id(&client, args.user.as_deref())?;
+
+    for id in &args.ids {
+        let item = client.items().get(&user_id, id)?;
+        let name = format_search_name(&item);
+
+        if args.recursive && item.is_folder.unwrap_or(false) {
+            let children = client.items().children(&user_id, id)?;
+            let child_ids: Vec<&str> = children.iter().filter_map(|c| c.id.as_deref()).collect();
+            for child_id in &child_ids {
+                action.apply(&client, &user_id, child_id)?;
+            }
+            println!(
+                "Marked {} in {name} {}",
+                count(child_ids.len()),
+                action.description()
+            );
+        } else {
+            action.apply(&client, &user_id, id)?;
+            println!("Marked {name} {}", action.description());
+        }
+    }
+
+    Ok(())
+}
+
+fn count(items: usize) -> String {
+    match items {
+        1 => "1 item".to_string(),
+        n => format!("{n} items"),
+    }
+}
@@ -1,18 +1,37 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::cli::{
+    GlobalArgs, UsersApplyArgs, UsersArgs, UsersCommand, UsersCreateArgs, UsersDeleteArgs,
+    UsersPolicyArgs, UsersRenameArgs, UsersSetPasswordArgs,
+};
 use crate::client::Client;
-use crate::config::Config;
-use crate::emby::types::User;
-use crate::error::Result;
-use crate::format::table;
+use crate::commands::prompt;
+use crate::emby::types::{User, UserPolicy};
+use crate::emby::users::{self, PolicyNames, PolicySpec, PolicyUpdate};
+use crate::error::{Error, Result};
+use crate::format::output;
 
-pub fn run() -> Result<()> {
-    let config = Config::load()?;
+pub fn run(global: &GlobalArgs, args: &UsersArgs) -> Result<()> {
+    let config = global.load_config()?;
     let client = Client::new(&config);
-    let users: Vec<User> = client.get("/Users")?;
 
-    if users.is_empty() {
-        println!("No users found");
-        return Ok(());
+    match &args.command {
+        None => list_users(global, &client),
+        Some(UsersCommand::Create(create_args)) => create_user(&client, create_args),
+        Some(UsersCommand::Delete(delete_args)) => delete_user(&client, delete_args),
+        Some(UsersCommand::Rename(rename_args)) => rename_user(&client, rename_args),
+        Some(UsersCommand::SetPassword(password_args)) => set_password(&client, password_args),
+        Some(UsersCommand::Policy(policy_args)) => policy(global, &client, policy_args),
+        Some(UsersCommand::Apply(apply_args)) => apply(&client, apply_args),
     }
+}
+
+fn list_users(global: &GlobalArgs, client: &Client) -> Result<()> {
+    let users = client.users().list()?;
 
     let rows: Vec<Vec<String>> = users
         .iter()
@@ -29,7 +48,486 @@ pub fn run() -> Result<()> {
         })
         .collect();
 
-    println!("{}", table::build_table(&["Name", "ID", "Admin"], rows));
+    output::print_rows(
+        global.output,
+        &["Name", "ID", "Admin"],
+        rows,
+        "No users found",
+    )?;
+
+    Ok(())
+}
+
+fn create_user(client: &Client, args: &UsersCreateArgs) -> Result<()> {
+    let copy_from = args
+        .copy_from
+        .as_deref()
+        .map(|name| users::find_user(client, name))
+        .transpose()?;
+
+    // Ask for the password before creating anything, so a cancelled prompt
+    // doesn't leave a user behind
+    let password = if args.password_stdin {
+        Some(prompt::read_stdin_line()?)
+    } else if args.password {
+        Some(prompt::prompt_password("Password: ")?)
+    } else {
+        None
+    };
+
+    let user = client
+        .users()
+        .create(&args.name, copy_from.as_ref().and_then(|u| u.id.as_deref()))?;
+    let id = user_id(&user)?;
+
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        client.users().set_password(id, &password)?;
+    }
+
+    println!("Created user {} ({id})", args.name);
+    Ok(())
+}
+
+fn delete_user(client: &Client, args: &UsersDeleteArgs) -> Result<()> {
+    let user = users::find_user(client, &args.user)?;
+    let id = user_id(&user)?;
+    let name = user.name.as_deref().unwrap_or(id);
+
+    if !args.yes && !prompt::confirm(&format!("Delete user {name}?"))? {
+        println!("Not deleting {name}");
+        return Ok(());
+    }
+
+    client.users().delete(id)?;
+    println!("Deleted user {name}");
+    Ok(())
+}
+
+fn rename_user(client: &Client, args: &UsersRenameArgs) -> Result<()> {
+    let found = users::find_user(client, &args.user)?;
+    let id = user_id(&found)?;
 
+    // Update the full user so nothing but the name changes
+    let mut user = client.users().get(id)?;
+    let old_name = user.name.replace(args.new_name.clone()).unwrap_or_default();
+    client.users().update(id, &user)?;
+
+    println!("Renamed user {old_name} to {}", args.new_name);
     Ok(())
 }
+
+fn set_password(client: &Client, args: &UsersSetPasswordArgs) -> Result<()> {
+    let user = users::find_user(client, &args.user)?;
+    let id = user_id(&user)?;
+    let name = user.name.as_deref().unwrap_or(id);
+
+    let password = if args.remove {
+        String::new()
+    } else if args.password_stdin {
+        prompt::read_stdin_line()?
+    } else {
+        prompt::prompt_password(&format!("New password for {name}: "))?
+    };
+
+    if password.is_empty() && !args.remove {
+        return Err(Error::Usage(
+            "Password is empty, pass --remove to remove the password".to_string(),
+        ));
+    }
+
+    client.users().set_password(id, &password)?;
+
+    if args.remove {
+        println!("Removed password for {name}");
+    } else {
+        println!("Set password for {name}");
+    }
+    Ok(())
+}
+
+fn policy(global: &GlobalArgs, client: &Client, args: &UsersPolicyArgs) -> Result<()> {
+    let user = users::find_user(client, &args.user)?;
+    let id = user_id(&user)?;
+    let name = user.name.as_deref().unwrap_or(id);
+    let mut policy = user.policy.clone().unwrap_or_default();
+    let names = PolicyNames::fetch(client)?;
+
+    let update = policy_spec(args).resolve(&names)?;
+
+    if update == PolicyUpdate::default() {
+        let rows = vec![
+            vec![
+                "Enabled".to_string(),
+                (!policy.is_disabled.unwrap_or(false)).to_string(),
+            ],
+            vec![
+                "Admin".to_string(),
+                policy.is_administrator.unwrap_or(false).to_string(),
+            ],
+            vec![
+                "Remote Access".to_string(),
+                policy.enable_remote_access.unwrap_or(true).to_string(),
+            ],
+            vec!["Libraries".to_string(), names.libraries(&policy)],
+            vec![
+                "Max Bitrate".to_string(),
+                users::describe_bitrate(policy.remote_client_bitrate_limit),
+            ],
+            vec![
+                "Max Parental Rating".to_string(),
+                names.rating(policy.max_parental_rating),
+            ],
+        ];
+
+        return output::print_rows(global.output, &["Setting", "Value"], rows, "");
+    }
+
+    let changes = update.apply(&mut policy, &names);
+    if changes.is_empty() {
+        println!("Policy for {name} is already up to date");
+        return Ok(());
+    }
+
+    client.users().update_policy(id, &policy)?;
+
+    println!("Updated policy for {name}");
+    for change in changes {
+        println!("  {change}");
+    }
+    Ok(())
+}
+
+fn policy_spec(args: &UsersPolicyArgs) -> PolicySpec {
+    PolicySpec {
+        enabled: args.enabled,
+        admin: args.admin,
+        remote_access: args.remote_access,
+        libraries: if args.all_libraries {
+            Some(vec!["all".to_string()])
+        } else {
+            args.libraries.clone()
+        },
+        max_bitrate: args.max_bitrate.clone(),
+        max_parental_rating: args.max_parental_rating.clone(),
+    }
+}
+
+/// The desired users, as read by `emby users apply`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UsersFile {
+    #[serde(default)]
+    users: Vec<UserSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserSpec {
+    name: String,
+    #[serde(default)]
+    policy: PolicySpec,
+}
+
+/// A change `emby users apply` will make.
+#[derive(Debug)]
+enum Action<'a> {
+    Create {
+        name: &'a str,
+        update: PolicyUpdate,
+        changes: Vec<String>,
+    },
+    Update {
+        user: &'a User,
+        policy: UserPolicy,
+        changes: Vec<String>,
+    },
+    Delete {
+        user: &'a User,
+    },
+}
+
+fn apply(client: &Client, args: &UsersApplyArgs) -> Result<()> {
+    let file = read_users_file(&args.file)?;
+    let existing = client.users().list()?;
+    let names = PolicyNames::fetch(client)?;
+
+    // Never prune the account the CLI is logged in as
+    let keep = client.user_id();
+    let actions = plan(&existing, &file.users, &names, args.prune, keep)?;
+
+    if actions.is_empty() {
+        println!("Users are up to date");
+        return Ok(());
+    }
+
+    print_plan(&actions);
+
+    if args.dry_run {
+        println!("Dry run, nothing changed");
+        return Ok(());
+    }
+
+    let deletes = actions
+        .iter()
+        .filter(|a| matches!(a, Action::Delete { .. }))
+        .count();
+    let count = match deletes {
+        1 => "1 user".to_string(),
+        n => format!("{n} users"),
+    };
+    if deletes > 0 && !args.yes && !prompt::confirm(&format!("Delete {count}?"))? {
+        println!("Not changing any users");
+        return Ok(());
+    }
+
+    for action in actions {
+        match action {
+            Action::Create { name, update, .. } => {
+                let user = client.users().create(name, None)?;
+                let id = user_id(&user)?;
+                let mut policy = user.policy.clone().unwrap_or_default();
+                if !update.apply(&mut policy, &names).is_empty() {
+                    client.users().update_policy(id, &policy)?;
+                }
+                println!("Created {name}");
+            }
+            Action::Update { user, policy, .. } => {
+                client.users().update_policy(user_id(user)?, &policy)?;
+                println!("Updated {}", display_name(user));
+            }
+            Action::Delete { user } => {
+                client.users().delete(user_id(user)?)?;
+                println!("Deleted {}", display_name(user));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_users_file(path: &Path) -> Result<UsersFile> {
+    let is_stdin = path.as_os_str() == "-";
+    let contents = if is_stdin {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path)
+            .map_err(|e| Error::Usage(format!("Failed to read '{}': {e}", path.display())))?
+    };
+
+    let is_toml = if is_stdin {
+        !contents.trim_start().starts_with('{')
+    } else {
+        path.extension().is_some_and(|ext| ext == "toml")
+    };
+
+    let parsed = if is_toml {
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    };
+
+    parsed.map_err(|e| Error::Usage(format!("Failed to parse '{}': {e}", path.display())))
+}
+
+/// Work out what to change so the server's users match `specs`.
+///
+/// Pruning never deletes administrators, so the server can't be left
+/// without one; they have to be deleted with `emby users delete`.
+fn plan<'a>(
+    existing: &'a [User],
+    specs: &'a [UserSpec],
+    names: &PolicyNames,
+    prune: bool,
+    keep: Option<&str>,
+) -> Result<Vec<Action<'a>>> {
+    let mut actions = Vec::new();
+
+    for (i, spec) in specs.iter().enumerate() {
+        if specs[..i]
+            .iter()
+            .any(|s| s.name.eq_ignore_ascii_case(&spec.name))
+        {
+            return Err(Error::Usage(format!(
+                "User '{}' is listed more than once",
+                spec.name
+            )));
+        }
+
+        let update = spec.policy.resolve(names)?;
+
+        if let Some(user) = users::find_in(existing, &spec.name) {
+            let mut policy = user.policy.clone().unwrap_or_default();
+            let changes = update.apply(&mut policy, names);
+            if !changes.is_empty() {
+                actions.push(Action::Update {
+                    user,
+                    policy,
+                    changes,
+                });
+            }
+        } else {
+            let changes = update.apply(&mut UserPolicy::default(), names);
+            actions.push(Action::Create {
+                name: &spec.name,
+                update,
+                changes,
+            });
+        }
+    }
+
+    if prune {
+        for user in existing {
+            let listed = specs.iter().any(|spec| {
+                user.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(&spec.name))
+            });
+            let is_admin = user
+                .policy
+                .as_ref()
+                .and_then(|p| p.is_administrator)
+                .unwrap_or(false);
+            if !listed && !is_admin && (keep.is_none() || user.id.as_deref() != keep) {
+                actions.push(Action::Delete { user });
+            }
+        }
+    }
+
+    Ok(actions)
+}
+
+fn print_plan(actions: &[Action]) {
+    let (mut creates, mut updates, mut deletes) = (0, 0, 0);
+
+    for action in actions {
+        let (line, changes) = match action {
+            Action::Create { name, changes, .. } => {
+                creates += 1;
+                (format!("+ create {name}"), changes.as_slice())
+            }
+            Action::Update { user, changes, .. } => {
+                updates += 1;
+                (
+                    format!("~ update {}", display_name(user)),
+                    changes.as_slice(),
+                )
+            }
+            Action::Delete { user } => {
+                deletes += 1;
+                (format!("- delete {}", display_name(user)), [].as_slice())
+            }
+        };
+
+        println!("{line}");
+        for change in changes {
+            println!("    {change}");
+        }
+    }
+
+    println!("{creates} to create, {updates} to update, {deletes} to delete");
+}
+
+fn display_name(user: &User) -> &str {
+    user.name
+        .as_deref()
+        .or(user.id.as_deref())
+        .unwrap_or_default()
+}
+
+fn user_id(user: &User) -> Result<&str> {
+    user.id
+        .as_deref()
+        .ok_or_else(|| Error::Server("Server did not return a user ID".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing() -> Vec<User> {
+        serde_json::from_value(serde_json::json!([
+            {"Name": "josh", "Id": "abc123", "Policy": {"IsAdministrator": true}},
+            {"Name": "bob", "Id": "def456", "Policy": {"IsAdministrator": false}},
+            {"Name": "carol", "Id": "ghi789", "Policy": {}}
+        ]))
+        .unwrap()
+    }
+
+    fn specs(toml: &str) -> Vec<UserSpec> {
+        toml::from_str::<UsersFile>(toml).unwrap().users
+    }
+
+    fn summary(actions: &[Action]) -> Vec<String> {
+        actions
+            .iter()
+            .map(|a| match a {
+                Action::Create { name, changes, .. } => format!("create {name} {changes:?}"),
+                Action::Update { user, changes, .. } => {
+                    format!("update {} {changes:?}", display_name(user))
+                }
+                Action::Delete { user } => format!("delete {}", display_name(user)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plan_creates_updates_and_skips_unchanged() {
+        let existing = existing();
+        let specs = specs(
+            r#"
+            [[users]]
+            name = "JOSH"
+            policy = { admin = true }
+
+            [[users]]
+            name = "bob"
+            policy = { admin = true }
+
+            [[users]]
+            name = "alice"
+            "#,
+        );
+
+        let actions = plan(&existing, &specs, &PolicyNames::default(), false, None).unwrap();
+        assert_eq!(
+            summary(&actions),
+            [r#"update bob ["admin: false -> true"]"#, "create alice []"]
+        );
+    }
+
+    #[test]
+    fn plan_prunes_unlisted_users_except_current() {
+        let existing = existing();
+        let specs = specs("[[users]]\nname = \"bob\"\n");
+
+        let actions = plan(
+            &existing,
+            &specs,
+            &PolicyNames::default(),
+            true,
+            Some("abc123"),
+        )
+        .unwrap();
+        assert_eq!(summary(&actions), ["delete carol"]);
+    }
+
+    #[test]
+    fn plan_never_prunes_administrators() {
+        let existing = existing();
+        let specs = specs("[[users]]\nname = \"carol\"\n");
+
+        let actions = plan(&existing, &specs, &PolicyNames::default(), true, None).unwrap();
+        assert_eq!(summary(&actions), ["delete bob"]);
+    }
+
+    #[test]
+    fn plan_rejects_duplicates() {
+        let existing = existing();
+        let specs = specs("[[users]]\nname = \"bob\"\n[[users]]\nname = \"Bob\"\n");
+
+        let err = plan(&existing, &specs, &PolicyNames::default(), false, None).unwrap_err();
+        assert!(err.to_string().contains("listed more than once"));
+    }
+}
@@ -1,25 +1,42 @@
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use crate::client::HttpOptions;
 use crate::error::{Error, Result};
 
 #[derive(Debug)]
 pub struct Config {
     pub api_url: String,
     pub api_key: String,
+    /// Set when the token came from `emby login` rather than an API key.
+    pub user_id: Option<String>,
+    pub http: HttpOptions,
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        if let (Ok(api_key), Ok(api_url)) = (env::var("EMBY_API_KEY"), env::var("EMBY_API_URL")) {
-            if !api_key.is_empty() && !api_url.is_empty() {
-                return Ok(Self { api_url, api_key });
+    /// Load the config from the environment or the config file.
+    ///
+    /// When `profile` is given, it is looked up in the config file's
+    /// `profiles` object and `EMBY_API_URL`/`EMBY_API_KEY` are ignored.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        if profile.is_none() {
+            if let (Ok(api_key), Ok(api_url)) = (env::var("EMBY_API_KEY"), env::var("EMBY_API_URL"))
+            {
+                if !api_key.is_empty() && !api_url.is_empty() {
+                    return Ok(Self {
+                        api_url,
+                        api_key,
+                        user_id: None,
+                        http: HttpOptions::default(),
+                    });
+                }
             }
         }
 
         let path = config_path();
-        let contents = fs::read_to_string(&path).map_err(|_| {
+        let json = read_config(&path)?.ok_or_else(|| {
             Error::Config(format!(
                 "Config '{}' doesn't exist\n{}",
                 path.display(),
@@ -27,39 +44,248 @@ impl Config {
             ))
         })?;
 
-        let json: serde_json::Value = serde_json::from_str(&contents)
-            .map_err(|e| Error::Config(format!("Failed to parse '{}': {e}", path.display())))?;
-
-        let api_url = json
-            .get("api_url")
-            .and_then(|v| v.as_str())
-            .filter(|s| !s.is_empty())
-            .map(String::from)
-            .ok_or_else(|| {
-                Error::Config(format!(
-                    "Must set 'api_url' in {}\n{}",
-                    path.display(),
-                    configure_help(&path)
-                ))
-            })?;
-
-        let api_key = json
-            .get("api_key")
-            .and_then(|v| v.as_str())
-            .filter(|s| !s.is_empty())
-            .map(String::from)
-            .ok_or_else(|| {
-                Error::Config(format!(
-                    "Must set 'api_key' in {}\n{}",
+        let (name, section) = select_profile(&json, profile, &path)?;
+        let location = match name {
+            Some(name) => format!("profile '{name}' in {}", path.display()),
+            None => path.display().to_string(),
+        };
+
+        let api_url = required_string(section, "api_url", &location, &path)?;
+
+        // A token from `emby login` takes precedence over an API key
+        let user_id = optional_string(section, "user_id");
+        let api_key = match optional_string(section, "access_token") {
+            Some(token) => token,
+            None => required_string(section, "api_key", &location, &path)?,
+        };
+
+        Ok(Self {
+            api_url,
+            api_key,
+            user_id,
+            http: http_options(section, &location)?,
+        })
+    }
+}
+
+/// Read `connect_timeout`, `read_timeout` (in seconds) and `retries`,
+/// falling back to the defaults for any that aren't set.
+fn http_options(section: &serde_json::Value, location: &str) -> Result<HttpOptions> {
+    let mut http = HttpOptions::default();
+
+    if let Some(seconds) = optional_u64(section, "connect_timeout", location)? {
+        http.connect_timeout = Duration::from_secs(seconds);
+    }
+    if let Some(seconds) = optional_u64(section, "read_timeout", location)? {
+        http.read_timeout = Duration::from_secs(seconds);
+    }
+    if let Some(retries) = optional_u64(section, "retries", location)? {
+        http.retries = u32::try_from(retries).unwrap_or(u32::MAX);
+    }
+
+    Ok(http)
+}
+
+/// Settings read by `emby login` before any credentials exist.
+pub fn load_api_url(profile: Option<&str>) -> Result<Option<String>> {
+    let path = config_path();
+    let Some(json) = read_config(&path)? else {
+        return Ok(None);
+    };
+
+    let api_url = match select_profile(&json, profile, &path) {
+        Ok((_, section)) => optional_string(section, "api_url"),
+        // Logging in to a profile that doesn't exist yet is fine
+        Err(_) if profile.is_some() => None,
+        Err(e) => return Err(e),
+    };
+
+    Ok(api_url)
+}
+
+/// Store an access token from `emby login` in the config file, creating the
+/// file (and the profile, if one is selected) as needed.
+pub fn save_login(
+    profile: Option<&str>,
+    api_url: &str,
+    access_token: &str,
+    user_id: &str,
+) -> Result<PathBuf> {
+    update_section(profile, |section| {
+        section.insert("api_url".to_string(), api_url.into());
+        section.insert("access_token".to_string(), access_token.into());
+        section.insert("user_id".to_string(), user_id.into());
+    })
+}
+
+/// Remove the access token stored by `emby login` from the config file.
+pub fn clear_login(profile: Option<&str>) -> Result<PathBuf> {
+    update_section(profile, |section| {
+        section.remove("access_token");
+        section.remove("user_id");
+    })
+}
+
+fn update_section(
+    profile: Option<&str>,
+    update: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>),
+) -> Result<PathBuf> {
+    let path = config_path();
+    let mut json = read_config(&path)?.unwrap_or_else(|| serde_json::json!({}));
+
+    let name = match profile {
+        Some(name) => {
+            let is_flat =
+                json.get("profiles").is_none() && json.as_object().is_some_and(|o| !o.is_empty());
+            if is_flat {
+                return Err(Error::Config(format!(
+                    "Profile '{name}' not found, {} doesn't define any 'profiles'",
+                    path.display()
+                )));
+            }
+            Some(name.to_string())
+        }
+        None => select_profile(&json, None, &path)?.0.map(String::from),
+    };
+
+    let invalid = || Error::Config(format!("'{}' must contain a JSON object", path.display()));
+    let mut section = json.as_object_mut().ok_or_else(invalid)?;
+
+    if let Some(name) = name {
+        section = section
+            .entry("profiles")
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(invalid)?
+            .entry(name)
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(invalid)?;
+    }
+
+    update(section);
+
+    write_config(&path, &json)?;
+    Ok(path)
+}
+
+fn read_config(path: &Path) -> Result<Option<serde_json::Value>> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(None);
+    };
+
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| Error::Config(format!("Failed to parse '{}': {e}", path.display())))
+}
+
+fn write_config(path: &Path, json: &serde_json::Value) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, format!("{}\n", serde_json::to_string_pretty(json)?))?;
+
+    // The file holds credentials, keep it private
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+/// Pick the config object to read settings from.
+///
+/// A config file is either a single `{api_url, api_key}` object, or holds
+/// named objects under `profiles` with an optional `default` profile name.
+/// Returns the selected profile name (if any) along with its object.
+fn select_profile<'a>(
+    json: &'a serde_json::Value,
+    profile: Option<&'a str>,
+    path: &Path,
+) -> Result<(Option<&'a str>, &'a serde_json::Value)> {
+    let Some(profiles) = json.get("profiles") else {
+        return match profile {
+            Some(name) => Err(Error::Config(format!(
+                "Profile '{name}' not found, {} doesn't define any 'profiles'",
+                path.display()
+            ))),
+            None => Ok((None, json)),
+        };
+    };
+
+    let profiles = profiles.as_object().ok_or_else(|| {
+        Error::Config(format!(
+            "'profiles' in {} must be an object",
+            path.display()
+        ))
+    })?;
+
+    let available = || profiles.keys().cloned().collect::<Vec<_>>().join(", ");
+
+    // With no explicit or default profile, a lone profile is unambiguous
+    let mut keys = profiles.keys();
+    let name = match profile.or_else(|| json.get("default").and_then(|v| v.as_str())) {
+        Some(name) => name,
+        None => match (keys.next(), keys.next()) {
+            (Some(only), None) => only.as_str(),
+            _ => {
+                return Err(Error::Config(format!(
+                    "No profile selected, set 'default' in {} or pass --profile (available: {})",
                     path.display(),
-                    configure_help(&path)
-                ))
-            })?;
+                    available()
+                )));
+            }
+        },
+    };
+
+    profiles
+        .get(name)
+        .map(|section| (Some(name), section))
+        .ok_or_else(|| {
+            Error::Config(format!(
+                "Profile '{name}' not found in {} (available: {})",
+                path.display(),
+                available()
+            ))
+        })
+}
+
+fn optional_string(section: &serde_json::Value, key: &str) -> Option<String> {
+    section
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
 
-        Ok(Self { api_url, api_key })
+fn optional_u64(section: &serde_json::Value, key: &str, location: &str) -> Result<Option<u64>> {
+    match section.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value.as_u64().map(Some).ok_or_else(|| {
+            Error::Config(format!(
+                "'{key}' in {location} must be a whole number, got {value}"
+            ))
+        }),
     }
 }
 
+fn required_string(
+    section: &serde_json::Value,
+    key: &str,
+    location: &str,
+    path: &Path,
+) -> Result<String> {
+    optional_string(section, key).ok_or_else(|| {
+        Error::Config(format!(
+            "Must set '{key}' in {location}\n{}",
+            configure_help(path)
+        ))
+    })
+}
+
 fn config_path() -> PathBuf {
     if let Ok(path) = env::var("EMBY_CONFIG") {
         return PathBuf::from(path);
@@ -83,7 +309,11 @@ Create `{}' with:
     --arg api_url "http://emby.local:8096" \
     '$ARGS.named' > "{}"
 
-  chmod 600 "{}""#,
+  chmod 600 "{}"
+
+Or log in with a username and password:
+
+  emby login --server "http://emby.local:8096""#,
         path.display(),
         path.display(),
         path.display()
@@ -108,4 +338,65 @@ mod tests {
         assert!(help.contains("jq --null-input"));
         assert!(help.contains("/tmp/test-config.json"));
     }
+
+    #[test]
+    fn select_profile_flat_config() {
+        let json = serde_json::json!({"api_url": "http://a", "api_key": "k"});
+        let (name, section) = select_profile(&json, None, Path::new("c.json")).unwrap();
+        assert_eq!(name, None);
+        assert_eq!(section["api_url"], "http://a");
+    }
+
+    #[test]
+    fn select_profile_flat_config_rejects_profile() {
+        let json = serde_json::json!({"api_url": "http://a", "api_key": "k"});
+        assert!(select_profile(&json, Some("prod"), Path::new("c.json")).is_err());
+    }
+
+    #[test]
+    fn select_profile_uses_default() {
+        let json = serde_json::json!({
+            "default": "staging",
+            "profiles": {
+                "prod": {"api_url": "http://prod"},
+                "staging": {"api_url": "http://staging"}
+            }
+        });
+        let (name, section) = select_profile(&json, None, Path::new("c.json")).unwrap();
+        assert_eq!(name, Some("staging"));
+        assert_eq!(section["api_url"], "http://staging");
+
+        let (name, _) = select_profile(&json, Some("prod"), Path::new("c.json")).unwrap();
+        assert_eq!(name, Some("prod"));
+    }
+
+    #[test]
+    fn select_profile_single_profile_without_default() {
+        let json = serde_json::json!({"profiles": {"home": {"api_url": "http://home"}}});
+        let (name, _) = select_profile(&json, None, Path::new("c.json")).unwrap();
+        assert_eq!(name, Some("home"));
+    }
+
+    #[test]
+    fn select_profile_ambiguous_without_default() {
+        let json = serde_json::json!({"profiles": {"a": {}, "b": {}}});
+        let err = select_profile(&json, None, Path::new("c.json")).unwrap_err();
+        assert!(err.to_string().contains("available: a, b"));
+    }
+
+    #[test]
+    fn http_options_from_config() {
+        let json = serde_json::json!({"connect_timeout": 5, "retries": 0});
+        let http = http_options(&json, "c.json").unwrap();
+        assert_eq!(http.connect_timeout, Duration::from_secs(5));
+        assert_eq!(http.read_timeout, HttpOptions::default().read_timeout);
+        assert_eq!(http.retries, 0);
+
+        let json = serde_json::json!({"read_timeout": "30s"});
+        let err = http_options(&json, "c.json").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'read_timeout' in c.json must be a whole number")
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use crate::client::Client;
+use crate::emby::time;
+use crate::emby::types::Device;
+use crate::error::{Error, Result};
+
+/// Find a device by ID or case-insensitive name. Several devices can share a
+/// name, in which case the ID is needed to pick one.
+pub fn find_device(client: &Client, query: &str) -> Result<Device> {
+    select_device(client.devices().list()?, query)
+}
+
+fn select_device(mut devices: Vec<Device>, query: &str) -> Result<Device> {
+    if let Some(index) = devices.iter().position(|d| d.id.as_deref() == Some(query)) {
+        return Ok(devices.swap_remove(index));
+    }
+
+    let mut matches: Vec<Device> = devices
+        .into_iter()
+        .filter(|d| {
+            d.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(query))
+        })
+        .collect();
+
+    match matches.len() {
+        0 => Err(Error::NotFound(format!("Device '{query}' not found"))),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let ids: Vec<&str> = matches.iter().filter_map(|d| d.id.as_deref()).collect();
+            Err(Error::Usage(format!(
+                "'{query}' matches several devices, use an ID: {}",
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+/// Devices last active before `cutoff` (seconds since the Unix epoch).
+/// Devices without a last activity date are never considered stale.
+pub fn stale_devices(devices: &[Device], cutoff: i64) -> Vec<&Device> {
+    devices
+        .iter()
+        .filter(|d| last_activity(d).is_some_and(|date| date < cutoff))
+        .collect()
+}
+
+/// When the device was last active, in seconds since the Unix epoch.
+pub fn last_activity(device: &Device) -> Option<i64> {
+    device
+        .date_last_activity
+        .as_deref()
+        .and_then(time::parse_timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, name: &str, last_activity: Option<&str>) -> Device {
+        Device {
+            name: Some(name.to_string()),
+            ip_address: None,
+            last_user_name: None,
+            app_name: None,
+            app_version: None,
+            date_last_activity: last_activity.map(str::to_string),
+            id: Some(id.to_string()),
+        }
+    }
+
+    #[test]
+    fn select_device_by_id_or_name() {
+        let devices = || {
+            vec![
+                device("1", "Apple TV", None),
+                device("2", "iPhone", None),
+                device("3", "iPhone", None),
+            ]
+        };
+
+        assert_eq!(
+            select_device(devices(), "apple tv").unwrap().id.unwrap(),
+            "1"
+        );
+        assert_eq!(select_device(devices(), "3").unwrap().id.unwrap(), "3");
+
+        let err = select_device(devices(), "iphone").unwrap_err().to_string();
+        assert!(
+            err.contains("matches several devices, use an ID: 2, 3"),
+            "{err}"
+        );
+
+        let err = select_device(devices(), "Roku").unwrap_err().to_string();
+        assert!(err.contains("Device 'Roku' not found"), "{err}");
+    }
+
+    #[test]
+    fn stale_devices_skips_unknown_activity() {
+        let devices = vec![
+            device("1", "Old", Some("2024-01-01T00:00:00.0000000Z")),
+            device("2", "New", Some("2024-06-15T10:30:00.0000000Z")),
+            device("3", "Unknown", None),
+        ];
+        let cutoff = time::parse_timestamp("2024-06-01").unwrap();
+
+        let stale: Vec<&str> = stale_devices(&devices, cutoff)
+            .iter()
+            .filter_map(|d| d.id.as_deref())
+            .collect();
+        assert_eq!(stale, ["1"]);
+    }
+}
@@ -0,0 +1,28 @@
+use crate::client::Client;
+use crate::emby::types::VirtualFolder;
+use crate::error::{Error, Result};
+
+/// Find a library by ID or case-insensitive name.
+pub fn find_library(client: &Client, name_or_id: &str) -> Result<VirtualFolder> {
+    let folders = client.library().virtual_folders()?;
+
+    if let Some(folder) = find_in(&folders, name_or_id) {
+        return Ok(folder.clone());
+    }
+
+    let available: Vec<&str> = folders.iter().filter_map(|f| f.name.as_deref()).collect();
+    Err(Error::NotFound(format!(
+        "Library '{name_or_id}' not found (available: {})",
+        available.join(", ")
+    )))
+}
+
+/// Find a library by ID or case-insensitive name in an already fetched list.
+pub fn find_in<'a>(folders: &'a [VirtualFolder], name_or_id: &str) -> Option<&'a VirtualFolder> {
+    folders.iter().find(|f| {
+        f.item_id.as_deref() == Some(name_or_id)
+            || f.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name_or_id))
+    })
+}
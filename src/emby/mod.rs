@@ -1,3 +1,7 @@
+pub mod devices;
+pub mod libraries;
+pub mod tasks;
 pub mod ticks;
+pub mod time;
 pub mod types;
 pub mod users;
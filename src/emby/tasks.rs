@@ -0,0 +1,167 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::Client;
+use crate::emby::types::TaskInfo;
+use crate::error::{Error, Result};
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Polls to wait for a task or refresh to start before giving up on it.
+pub const START_POLLS: u32 = 10;
+
+/// Key of the built-in "Scan media library" task.
+pub const SCAN_LIBRARY_KEY: &str = "RefreshLibrary";
+
+/// Find the "Scan media library" task by key, falling back to its name.
+pub fn find_scan_task(client: &Client) -> Result<TaskInfo> {
+    client
+        .scheduled_tasks()
+        .list()?
+        .into_iter()
+        .find(|t| {
+            t.key.as_deref() == Some(SCAN_LIBRARY_KEY)
+                || t.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case("Scan media library"))
+        })
+        .ok_or_else(|| Error::NotFound("Scan media library task not found".to_string()))
+}
+
+/// Find a task by ID or name. Names are matched case-insensitively, and a
+/// prefix is enough when it matches a single task.
+pub fn find_task(client: &Client, query: &str) -> Result<TaskInfo> {
+    select_task(client.scheduled_tasks().list()?, query)
+}
+
+fn select_task(mut tasks: Vec<TaskInfo>, query: &str) -> Result<TaskInfo> {
+    let query_lower = query.to_lowercase();
+    let name = |task: &TaskInfo| task.name.as_deref().unwrap_or("").to_lowercase();
+
+    if let Some(index) = tasks
+        .iter()
+        .position(|t| t.id.as_deref() == Some(query) || name(t) == query_lower)
+    {
+        return Ok(tasks.swap_remove(index));
+    }
+
+    let mut matches: Vec<TaskInfo> = tasks
+        .into_iter()
+        .filter(|t| name(t).starts_with(&query_lower))
+        .collect();
+
+    match matches.len() {
+        0 => Err(Error::NotFound(format!("No task matches '{query}'"))),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let names: Vec<&str> = matches.iter().filter_map(|t| t.name.as_deref()).collect();
+            Err(Error::Usage(format!(
+                "'{query}' matches several tasks: {}",
+                names.join(", ")
+            )))
+        }
+    }
+}
+
+/// Poll a task until it has run and finished, calling `on_progress` after
+/// every poll.
+///
+/// `previous` is the task as fetched before it was started. The task counts
+/// as finished once it's no longer running and either was seen running or
+/// has a newer last execution result, so a run that completes between polls
+/// isn't missed. Returns an error if the task didn't complete successfully,
+/// hasn't started after [`START_POLLS`] polls, or `timeout` elapses first.
+pub fn wait_for_task(
+    client: &Client,
+    previous: &TaskInfo,
+    timeout: Option<Duration>,
+    mut on_progress: impl FnMut(&TaskInfo),
+) -> Result<TaskInfo> {
+    let id = previous.id.as_deref().unwrap_or("");
+    let name = previous.name.as_deref().unwrap_or(id);
+    let previous_end = last_end_time(previous);
+    let started = Instant::now();
+    let mut seen_running = false;
+    let mut polls = 0;
+
+    loop {
+        let task = client.scheduled_tasks().get(id)?;
+        polls += 1;
+        on_progress(&task);
+
+        if is_running(&task) {
+            seen_running = true;
+        } else if seen_running || last_end_time(&task) != previous_end {
+            return match last_status(&task) {
+                "Completed" => Ok(task),
+                status => Err(Error::Task(format!(
+                    "Task '{name}' finished with status {status}"
+                ))),
+            };
+        } else if polls >= START_POLLS {
+            return Err(Error::Task(format!("Task '{name}' didn't start")));
+        }
+
+        if timeout.is_some_and(|t| started.elapsed() >= t) {
+            return Err(Error::Task(format!("Timed out waiting for task '{name}'")));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+pub fn is_running(task: &TaskInfo) -> bool {
+    matches!(task.state.as_deref(), Some("Running" | "Cancelling"))
+}
+
+fn last_end_time(task: &TaskInfo) -> Option<&str> {
+    task.last_execution_result
+        .as_ref()
+        .and_then(|r| r.end_time_utc.as_deref())
+}
+
+fn last_status(task: &TaskInfo) -> &str {
+    task.last_execution_result
+        .as_ref()
+        .and_then(|r| r.status.as_deref())
+        .unwrap_or("Unknown")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks() -> Vec<TaskInfo> {
+        serde_json::from_value(serde_json::json!([
+            {"Name": "Scan media library", "Id": "task-001"},
+            {"Name": "Download subtitles", "Id": "task-002"},
+            {"Name": "Download missing images", "Id": "task-003"}
+        ]))
+        .unwrap()
+    }
+
+    fn select(query: &str) -> Result<Option<String>> {
+        select_task(tasks(), query).map(|t| t.id)
+    }
+
+    #[test]
+    fn select_task_by_id_name_or_prefix() {
+        assert_eq!(select("task-002").unwrap().as_deref(), Some("task-002"));
+        assert_eq!(
+            select("SCAN MEDIA LIBRARY").unwrap().as_deref(),
+            Some("task-001")
+        );
+        assert_eq!(select("scan").unwrap().as_deref(), Some("task-001"));
+        assert_eq!(select("download s").unwrap().as_deref(), Some("task-002"));
+    }
+
+    #[test]
+    fn select_task_ambiguous_or_missing() {
+        let err = select("download").unwrap_err().to_string();
+        assert!(err.contains("matches several tasks"));
+        assert!(err.contains("Download subtitles, Download missing images"));
+
+        let err = select("backup").unwrap_err().to_string();
+        assert!(err.contains("No task matches 'backup'"));
+    }
+}
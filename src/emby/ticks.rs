@@ -3,6 +3,28 @@ pub fn ticks_to_seconds(ticks: u64) -> u64 {
     (ticks + 5_000_000) / 10_000_000
 }
 
+/// Convert seconds to Emby ticks, `None` if they don't fit in a `u64`.
+pub fn seconds_to_ticks(seconds: u64) -> Option<u64> {
+    seconds.checked_mul(10_000_000)
+}
+
+/// Parse a time like `1:02:03`, `02:03` or `123` into seconds.
+pub fn parse_time(s: &str) -> Option<u64> {
+    let parts: Vec<u64> = s
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match parts.as_slice() {
+        [seconds] => Some(*seconds),
+        [minutes, seconds] if *seconds < 60 => minutes.checked_mul(60)?.checked_add(*seconds),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            hours.checked_mul(3600)?.checked_add(minutes * 60 + seconds)
+        }
+        _ => None,
+    }
+}
+
 /// Format seconds as `HH:MM:SS` or `MM:SS` if under an hour.
 pub fn seconds_to_time(total_seconds: u64) -> String {
     let hours = total_seconds / 3600;
@@ -54,6 +76,41 @@ pub fn format_premiere_date(date_str: &str) -> String {
     format!("{month_name} {day}, {year}")
 }
 
+/// Format bits per second, e.g. `8.0 Mbps` or `320 kbps`.
+pub fn format_bitrate(bps: u64) -> String {
+    if bps >= 1_000_000 {
+        #[allow(clippy::cast_precision_loss)]
+        let mbps = bps as f64 / 1_000_000.0;
+        format!("{mbps:.1} Mbps")
+    } else {
+        format!("{} kbps", bps / 1000)
+    }
+}
+
+/// Parse a bitrate like `8M`, `1500k` or `20000000` into bits per second,
+/// `none` meaning no limit (0).
+pub fn parse_bitrate(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    if s == "none" {
+        return Some(0);
+    }
+
+    let (number, multiplier) = match s.char_indices().last()? {
+        (i, 'k') => (&s[..i], 1_000.0),
+        (i, 'm') => (&s[..i], 1_000_000.0),
+        (i, 'g') => (&s[..i], 1_000_000_000.0),
+        _ => (s.as_str(), 1.0),
+    };
+
+    let value: f64 = number.parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some((value * multiplier).round() as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,6 +125,12 @@ mod tests {
         assert_eq!(ticks_to_seconds(14_999_999), 1);
     }
 
+    #[test]
+    fn test_seconds_to_ticks() {
+        assert_eq!(seconds_to_ticks(90), Some(900_000_000));
+        assert_eq!(seconds_to_ticks(u64::MAX / 1000), None);
+    }
+
     #[test]
     fn test_seconds_to_time() {
         assert_eq!(seconds_to_time(0), "00:00");
@@ -78,6 +141,17 @@ mod tests {
         assert_eq!(seconds_to_time(3661), "01:01:01");
     }
 
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("90"), Some(90));
+        assert_eq!(parse_time("01:30"), Some(90));
+        assert_eq!(parse_time("1:01:01"), Some(3661));
+        assert_eq!(parse_time("1:75"), None);
+        assert_eq!(parse_time("abc"), None);
+        assert_eq!(parse_time("99999999999999999:00:00"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
     #[test]
     fn test_format_episode_code() {
         assert_eq!(format_episode_code(Some(1), Some(2)), "S01E02");
@@ -99,4 +173,21 @@ mod tests {
             "Jun 30, 2022"
         );
     }
+
+    #[test]
+    fn test_format_bitrate() {
+        assert_eq!(format_bitrate(8_000_000), "8.0 Mbps");
+        assert_eq!(format_bitrate(320_000), "320 kbps");
+    }
+
+    #[test]
+    fn test_parse_bitrate() {
+        assert_eq!(parse_bitrate("8M"), Some(8_000_000));
+        assert_eq!(parse_bitrate("1500k"), Some(1_500_000));
+        assert_eq!(parse_bitrate("1.5m"), Some(1_500_000));
+        assert_eq!(parse_bitrate("20000000"), Some(20_000_000));
+        assert_eq!(parse_bitrate("none"), Some(0));
+        assert_eq!(parse_bitrate("fast"), None);
+        assert_eq!(parse_bitrate("-1M"), None);
+    }
 }
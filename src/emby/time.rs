@@ -0,0 +1,276 @@
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current time as seconds since the Unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// Parse an Emby timestamp like `2024-06-15T10:30:00.0000000Z` into seconds
+/// since the Unix epoch. Also accepts `2024-06-15 10:30`, a bare date, and a
+/// `+02:00` style offset; timestamps without an offset are taken as UTC.
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (date, rest) = s.split_at(s.find(['T', ' ']).unwrap_or(s.len()));
+
+    let mut date_parts = date.split('-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+    // Bounding the year keeps the arithmetic below from overflowing
+    if date_parts.next().is_some()
+        || !(0..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+    {
+        return None;
+    }
+
+    let rest = rest.get(1..).unwrap_or("");
+    let (time, offset) = split_offset(rest)?;
+
+    let mut seconds = 0;
+    if !time.is_empty() {
+        // Drop fractional seconds
+        let time = time.split('.').next()?;
+        let parts: Vec<i64> = time
+            .split(':')
+            .map(|p| p.parse().ok())
+            .collect::<Option<_>>()?;
+        seconds = match parts.as_slice() {
+            [h, m] if *h < 24 && *m < 60 => h * 3600 + m * 60,
+            [h, m, s] if *h < 24 && *m < 60 && *s < 60 => h * 3600 + m * 60 + s,
+            _ => return None,
+        };
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + seconds - offset)
+}
+
+/// Parse a `--since`/`--until` style value: either a duration ago such as
+/// `30s`, `15m`, `2h`, `7d` or `1w`, or a timestamp for [`parse_timestamp`].
+pub fn parse_since(s: &str, now: i64) -> Option<i64> {
+    if let Some(seconds) = parse_duration(s) {
+        return now.checked_sub(seconds);
+    }
+
+    parse_timestamp(s)
+}
+
+/// Parse a duration like `30s`, `15m`, `2h`, `7d` or `1w` into seconds.
+/// Negative durations are rejected.
+pub fn parse_duration(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let unit = s.chars().last()?;
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return None,
+    };
+
+    let value: i64 = s[..s.len() - 1].parse().ok()?;
+    if value < 0 {
+        return None;
+    }
+    value.checked_mul(multiplier)
+}
+
+/// Format a duration in seconds as e.g. `1h30m`, `6h` or `45s`.
+pub fn format_duration(seconds: u64) -> String {
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+
+    let mut remaining = seconds;
+    let mut formatted = String::new();
+    for (size, unit) in [(86_400, 'd'), (3600, 'h'), (60, 'm'), (1, 's')] {
+        let count = remaining / size;
+        remaining %= size;
+        if count > 0 {
+            let _ = write!(formatted, "{count}{unit}");
+        }
+    }
+
+    formatted
+}
+
+/// Parse a time of day like `03:00` or `23:30:15` into seconds after midnight.
+pub fn parse_time_of_day(s: &str) -> Option<u64> {
+    let parts: Vec<u64> = s
+        .trim()
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match parts.as_slice() {
+        [h, m] if *h < 24 && *m < 60 => Some(h * 3600 + m * 60),
+        [h, m, s] if *h < 24 && *m < 60 && *s < 60 => Some(h * 3600 + m * 60 + s),
+        _ => None,
+    }
+}
+
+/// Format seconds after midnight as `03:00`, or `03:00:15` with seconds.
+pub fn format_time_of_day(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600 % 24, seconds % 3600 / 60, seconds % 60);
+    if seconds == 0 {
+        format!("{hours:02}:{minutes:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Format seconds since the Unix epoch as `2024-06-15T10:30:00Z`.
+pub fn format_iso(timestamp: i64) -> String {
+    let (date, time) = split_timestamp(timestamp);
+    format!("{date}T{time}Z")
+}
+
+/// Format seconds since the Unix epoch as `2024-06-15 10:30:00` (UTC).
+pub fn format_datetime(timestamp: i64) -> String {
+    let (date, time) = split_timestamp(timestamp);
+    format!("{date} {time}")
+}
+
+fn split_timestamp(timestamp: i64) -> (String, String) {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+    )
+}
+
+/// Split a trailing `Z` or `+HH:MM`/`-HH:MM` offset from a time, returning
+/// the offset in seconds east of UTC.
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(time) = time.strip_suffix('Z') {
+        return Some((time, 0));
+    }
+
+    let Some(index) = time.rfind(['+', '-']) else {
+        return Some((time, 0));
+    };
+
+    let sign = if time[index..].starts_with('-') {
+        -1
+    } else {
+        1
+    };
+    let (hours, minutes) = time[index + 1..].split_once(':')?;
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    if hours >= 24 || minutes >= 60 {
+        return None;
+    }
+
+    Some((&time[..index], sign * (hours * 3600 + minutes * 60)))
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_timestamp("2024-06-15T10:30:00.0000000Z"),
+            Some(1_718_447_400)
+        );
+        assert_eq!(parse_timestamp("2024-06-15 10:30"), Some(1_718_447_400));
+        assert_eq!(
+            parse_timestamp("2024-06-15T12:30:00+02:00"),
+            Some(1_718_447_400)
+        );
+        assert_eq!(parse_timestamp("2024-06-15"), Some(1_718_409_600));
+        assert_eq!(parse_timestamp("2024-13-01"), None);
+        assert_eq!(parse_timestamp("99999999999999999-01-01"), None);
+        assert_eq!(parse_timestamp("2024-06-15T10:30+99999999999999:00"), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("90s", 1000), Some(910));
+        assert_eq!(parse_since("2h", 10_000), Some(2800));
+        assert_eq!(parse_since("1970-01-02", 10_000), Some(86_400));
+        assert_eq!(parse_since("2x", 10_000), None);
+        assert_eq!(parse_since("-5m", 10_000), None);
+        assert_eq!(parse_since("9223372036854775807s", -10), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("0s"), Some(0));
+        assert_eq!(parse_duration("15m"), Some(900));
+        assert_eq!(parse_duration("1w"), Some(604_800));
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("-9223372036854775808s"), None);
+        assert_eq!(parse_duration("9223372036854775807w"), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_iso(1_718_447_400), "2024-06-15T10:30:00Z");
+        assert_eq!(format_datetime(1_718_447_400), "2024-06-15 10:30:00");
+        assert_eq!(format_iso(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(5400), "1h30m");
+        assert_eq!(format_duration(2 * 86_400 + 3600), "2d1h");
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        assert_eq!(parse_time_of_day("03:00"), Some(10_800));
+        assert_eq!(parse_time_of_day("23:30:15"), Some(84_615));
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("3pm"), None);
+        assert_eq!(format_time_of_day(10_800), "03:00");
+        assert_eq!(format_time_of_day(84_615), "23:30:15");
+    }
+}
@@ -1,4 +1,4 @@
-#![allow(dead_code)]
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
@@ -7,18 +7,22 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Session {
+    pub id: Option<String>,
     pub user_name: Option<String>,
+    pub device_id: Option<String>,
     pub device_name: Option<String>,
     pub client: Option<String>,
     pub remote_end_point: Option<String>,
     pub now_playing_item: Option<NowPlayingItem>,
     pub play_state: Option<PlayState>,
+    pub transcoding_info: Option<TranscodingInfo>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NowPlayingItem {
     pub name: Option<String>,
+    pub id: Option<String>,
     #[serde(rename = "Type")]
     pub media_type: Option<String>,
     pub series_name: Option<String>,
@@ -31,6 +35,23 @@ pub struct NowPlayingItem {
     pub production_year: Option<u32>,
     pub album: Option<String>,
     pub album_artist: Option<String>,
+    pub media_streams: Option<Vec<MediaStream>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaStream {
+    #[serde(rename = "Type")]
+    pub stream_type: Option<String>,
+    pub index: Option<i32>,
+    pub codec: Option<String>,
+    pub display_title: Option<String>,
+    pub language: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub bit_rate: Option<u64>,
+    pub channels: Option<u32>,
+    pub is_default: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -39,16 +60,42 @@ pub struct PlayState {
     pub position_ticks: Option<u64>,
     pub is_paused: Option<bool>,
     pub play_method: Option<String>,
+    pub audio_stream_index: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TranscodingInfo {
+    pub audio_codec: Option<String>,
+    pub video_codec: Option<String>,
+    pub container: Option<String>,
+    pub is_video_direct: Option<bool>,
+    pub is_audio_direct: Option<bool>,
+    pub bitrate: Option<u64>,
+    pub framerate: Option<f64>,
+    pub completion_percentage: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub audio_channels: Option<u32>,
+    pub transcode_reasons: Option<Vec<String>>,
+    pub video_decoder_is_hardware: Option<bool>,
+    pub video_decoder_hw_accel: Option<String>,
+    pub video_encoder_is_hardware: Option<bool>,
+    pub video_encoder_hw_accel: Option<String>,
 }
 
 // --- Virtual Folders (Libraries) ---
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct VirtualFolder {
     pub name: Option<String>,
     pub collection_type: Option<String>,
     pub item_id: Option<String>,
+    pub locations: Option<Vec<String>>,
+    /// `Active` while the library is being refreshed
+    pub refresh_status: Option<String>,
+    pub refresh_progress: Option<f64>,
 }
 
 // --- System Info ---
@@ -56,26 +103,77 @@ pub struct VirtualFolder {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct SystemInfo {
+    pub id: Option<String>,
     pub version: Option<String>,
     pub server_name: Option<String>,
     pub operating_system_display_name: Option<String>,
     pub has_update_available: Option<bool>,
 }
 
+// --- Events ---
+
+/// A message pushed over the server's WebSocket, e.g. `Sessions` with the
+/// current sessions or `LibraryChanged` with the items that were added.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServerEvent {
+    pub message_type: String,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub data: serde_json::Value,
+}
+
 // --- Users ---
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct User {
     pub name: Option<String>,
     pub id: Option<String>,
     pub policy: Option<UserPolicy>,
+    pub has_password: Option<bool>,
+    /// Fields not modeled above, kept so the user can be sent back unchanged.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Emby replaces the whole policy on update, so fields not modeled here are
+/// kept in `other` and sent back as they were.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct UserPolicy {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_administrator: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_remote_access: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_all_folders: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_folders: Option<Vec<String>>,
+    /// Bits per second, 0 for no limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_client_bitrate_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_parental_rating: Option<i32>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ParentalRating {
+    pub name: String,
+    pub value: i32,
+}
+
+// --- Authentication ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AuthenticationResult {
+    pub user: Option<User>,
+    pub access_token: Option<String>,
 }
 
 // --- Activity Log ---
@@ -89,6 +187,7 @@ pub struct ActivityLogResponse {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ActivityLogEntry {
+    pub id: Option<i64>,
     pub name: Option<String>,
     pub overview: Option<String>,
     pub short_overview: Option<String>,
@@ -100,13 +199,14 @@ pub struct ActivityLogEntry {
 
 // --- BaseItemDto (shared by latest, next-up, upcoming) ---
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct BaseItemDto {
     pub name: Option<String>,
     pub id: Option<String>,
     #[serde(rename = "Type")]
     pub media_type: Option<String>,
+    pub is_folder: Option<bool>,
     pub series_name: Option<String>,
     pub index_number: Option<u32>,
     pub parent_index_number: Option<u32>,
@@ -119,6 +219,68 @@ pub struct BaseItemDto {
     pub official_rating: Option<String>,
     pub album: Option<String>,
     pub album_artist: Option<String>,
+    pub original_title: Option<String>,
+    pub taglines: Option<Vec<String>>,
+    pub community_rating: Option<f64>,
+    pub genres: Option<Vec<String>>,
+    pub studios: Option<Vec<NameIdPair>>,
+    pub people: Option<Vec<BaseItemPerson>>,
+    /// External IDs by provider, e.g. `Imdb` or `Tmdb`
+    pub provider_ids: Option<BTreeMap<String, String>>,
+    pub path: Option<String>,
+    /// File size in bytes
+    pub size: Option<u64>,
+    /// Bits per second
+    pub bitrate: Option<u64>,
+    pub media_sources: Option<Vec<MediaSourceInfo>>,
+    pub user_data: Option<UserItemDataDto>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NameIdPair {
+    pub name: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BaseItemPerson {
+    pub name: Option<String>,
+    pub id: Option<String>,
+    /// The character played, for actors
+    pub role: Option<String>,
+    /// `Actor`, `Director`, `Writer`, ...
+    #[serde(rename = "Type")]
+    pub person_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaSourceInfo {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub path: Option<String>,
+    pub container: Option<String>,
+    pub size: Option<u64>,
+    pub bitrate: Option<u64>,
+    pub run_time_ticks: Option<u64>,
+    pub media_streams: Option<Vec<MediaStream>>,
+}
+
+/// Playback state of an item for a single user.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserItemDataDto {
+    pub played: Option<bool>,
+    pub play_count: Option<u32>,
+    pub is_favorite: Option<bool>,
+    pub playback_position_ticks: Option<u64>,
+    pub played_percentage: Option<f64>,
+    pub last_played_date: Option<String>,
+    /// The user's rating, 0-10
+    pub rating: Option<f64>,
+    pub likes: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -134,6 +296,7 @@ pub struct QueryResultBaseItemDto {
 #[serde(rename_all = "PascalCase")]
 pub struct TaskInfo {
     pub name: Option<String>,
+    pub key: Option<String>,
     pub state: Option<String>,
     pub current_progress_percentage: Option<f64>,
     pub id: Option<String>,
@@ -141,6 +304,28 @@ pub struct TaskInfo {
     pub description: Option<String>,
     pub category: Option<String>,
     pub is_hidden: Option<bool>,
+    pub triggers: Option<Vec<TaskTriggerInfo>>,
+}
+
+/// When a scheduled task runs. Sent back as-is when updating a task's
+/// triggers, so unset fields are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskTriggerInfo {
+    /// `DailyTrigger`, `WeeklyTrigger`, `IntervalTrigger`, `StartupTrigger`
+    /// or `SystemEventTrigger`
+    #[serde(rename = "Type")]
+    pub trigger_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_of_day_ticks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_ticks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_runtime_ticks: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -167,5 +352,6 @@ pub struct Device {
     pub last_user_name: Option<String>,
     pub app_name: Option<String>,
     pub app_version: Option<String>,
+    pub date_last_activity: Option<String>,
     pub id: Option<String>,
 }
@@ -1,23 +1,25 @@
+use serde::{Deserialize, Deserializer};
+
 use crate::client::Client;
-use crate::emby::types::User;
+use crate::emby::libraries;
+use crate::emby::ticks::{format_bitrate, parse_bitrate};
+use crate::emby::types::{ParentalRating, User, UserPolicy, VirtualFolder};
 use crate::error::{Error, Result};
 
 /// Resolve a user ID from an optional user name.
 /// If a name is given, find by case-insensitive match.
-/// If None, return the first admin user's ID.
+/// If None, return the logged-in user's ID, or the first admin user's ID.
 pub fn resolve_user_id(client: &Client, user_name: Option<&str>) -> Result<String> {
-    let users: Vec<User> = client.get("/Users")?;
+    if let (None, Some(id)) = (user_name, client.user_id()) {
+        return Ok(id.to_string());
+    }
+
+    let users = client.users().list()?;
 
     if let Some(name) = user_name {
-        users
-            .iter()
-            .find(|u| {
-                u.name
-                    .as_deref()
-                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
-            })
+        find_in(&users, name)
             .and_then(|u| u.id.clone())
-            .ok_or_else(|| Error::Config(format!("User '{name}' not found")))
+            .ok_or_else(|| Error::NotFound(format!("User '{name}' not found")))
     } else {
         users
             .iter()
@@ -28,6 +30,392 @@ pub fn resolve_user_id(client: &Client, user_name: Option<&str>) -> Result<Strin
                     .unwrap_or(false)
             })
             .and_then(|u| u.id.clone())
-            .ok_or_else(|| Error::Config("No admin user found".to_string()))
+            .ok_or_else(|| Error::NotFound("No admin user found".to_string()))
+    }
+}
+
+/// Find a user by ID or case-insensitive name.
+pub fn find_user(client: &Client, name_or_id: &str) -> Result<User> {
+    find_in(&client.users().list()?, name_or_id)
+        .cloned()
+        .ok_or_else(|| Error::NotFound(format!("User '{name_or_id}' not found")))
+}
+
+/// Find a user by ID or case-insensitive name in an already fetched list.
+pub fn find_in<'a>(users: &'a [User], name_or_id: &str) -> Option<&'a User> {
+    users.iter().find(|u| {
+        u.id.as_deref() == Some(name_or_id)
+            || u.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name_or_id))
+    })
+}
+
+/// Which libraries a user can access.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryAccess {
+    All,
+    /// Library item IDs
+    Only(Vec<String>),
+}
+
+/// Policy settings to change, `None` leaves a setting as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyUpdate {
+    pub enabled: Option<bool>,
+    pub admin: Option<bool>,
+    pub remote_access: Option<bool>,
+    pub libraries: Option<LibraryAccess>,
+    /// Bits per second, 0 for no limit
+    pub max_bitrate: Option<u64>,
+    /// `Some(None)` removes the limit
+    pub max_parental_rating: Option<Option<i32>>,
+}
+
+impl PolicyUpdate {
+    /// Apply the update to `policy`, returning a description of each setting
+    /// that actually changed, e.g. `admin: false -> true`.
+    pub fn apply(&self, policy: &mut UserPolicy, names: &PolicyNames) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut change = |setting: &str, from: String, to: String| {
+            if from != to {
+                changes.push(format!("{setting}: {from} -> {to}"));
+            }
+        };
+
+        if let Some(enabled) = self.enabled {
+            change(
+                "enabled",
+                (!policy.is_disabled.unwrap_or(false)).to_string(),
+                enabled.to_string(),
+            );
+            policy.is_disabled = Some(!enabled);
+        }
+        if let Some(admin) = self.admin {
+            change(
+                "admin",
+                policy.is_administrator.unwrap_or(false).to_string(),
+                admin.to_string(),
+            );
+            policy.is_administrator = Some(admin);
+        }
+        if let Some(remote_access) = self.remote_access {
+            change(
+                "remote access",
+                policy.enable_remote_access.unwrap_or(true).to_string(),
+                remote_access.to_string(),
+            );
+            policy.enable_remote_access = Some(remote_access);
+        }
+        if let Some(libraries) = &self.libraries {
+            let from = names.libraries(policy);
+            match libraries {
+                LibraryAccess::All => {
+                    policy.enable_all_folders = Some(true);
+                    policy.enabled_folders = Some(Vec::new());
+                }
+                LibraryAccess::Only(ids) => {
+                    policy.enable_all_folders = Some(false);
+                    policy.enabled_folders = Some(ids.clone());
+                }
+            }
+            change("libraries", from, names.libraries(policy));
+        }
+        if let Some(max_bitrate) = self.max_bitrate {
+            change(
+                "max bitrate",
+                describe_bitrate(policy.remote_client_bitrate_limit),
+                describe_bitrate(Some(max_bitrate)),
+            );
+            policy.remote_client_bitrate_limit = Some(max_bitrate);
+        }
+        if let Some(rating) = self.max_parental_rating {
+            change(
+                "max parental rating",
+                names.rating(policy.max_parental_rating),
+                names.rating(rating),
+            );
+            policy.max_parental_rating = rating;
+        }
+
+        changes
+    }
+}
+
+/// Policy settings by name, as given on the command line or in a users file.
+/// `None` leaves a setting as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySpec {
+    pub enabled: Option<bool>,
+    pub admin: Option<bool>,
+    pub remote_access: Option<bool>,
+    /// Library names or IDs, or `"all"`
+    #[serde(default, deserialize_with = "deserialize_libraries")]
+    pub libraries: Option<Vec<String>>,
+    /// A bitrate for [`parse_bitrate`]
+    #[serde(default, deserialize_with = "deserialize_bitrate")]
+    pub max_bitrate: Option<String>,
+    /// A parental rating name, or `"none"`
+    pub max_parental_rating: Option<String>,
+}
+
+impl PolicySpec {
+    /// Look up library IDs and rating values to turn the spec into an update.
+    pub fn resolve(&self, names: &PolicyNames) -> Result<PolicyUpdate> {
+        let libraries = self
+            .libraries
+            .as_ref()
+            .map(|libraries| match libraries.as_slice() {
+                [all] if all.eq_ignore_ascii_case("all") => Ok(LibraryAccess::All),
+                _ => libraries
+                    .iter()
+                    .map(|l| names.library_id(l))
+                    .collect::<Result<Vec<_>>>()
+                    .map(LibraryAccess::Only),
+            })
+            .transpose()?;
+
+        let max_bitrate = self
+            .max_bitrate
+            .as_deref()
+            .map(|b| {
+                parse_bitrate(b).ok_or_else(|| {
+                    Error::Usage(format!(
+                        "Invalid bitrate '{b}', expected e.g. 8M, 1500k or none"
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let max_parental_rating = self
+            .max_parental_rating
+            .as_deref()
+            .map(|r| names.rating_value(r))
+            .transpose()?;
+
+        Ok(PolicyUpdate {
+            enabled: self.enabled,
+            admin: self.admin,
+            remote_access: self.remote_access,
+            libraries,
+            max_bitrate,
+            max_parental_rating,
+        })
+    }
+}
+
+/// Accept `libraries = "all"` as well as a list.
+fn deserialize_libraries<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Libraries {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(
+        Option::<Libraries>::deserialize(deserializer)?.map(|l| match l {
+            Libraries::One(library) => vec![library],
+            Libraries::Many(libraries) => libraries,
+        }),
+    )
+}
+
+/// Accept a bitrate in bits per second as well as a string like `8M`.
+fn deserialize_bitrate<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bitrate {
+        Number(u64),
+        Text(String),
+    }
+
+    Ok(
+        Option::<Bitrate>::deserialize(deserializer)?.map(|b| match b {
+            Bitrate::Number(bps) => bps.to_string(),
+            Bitrate::Text(text) => text,
+        }),
+    )
+}
+
+/// Libraries and parental ratings, for turning policy values into names and back.
+#[derive(Debug, Default)]
+pub struct PolicyNames {
+    pub libraries: Vec<VirtualFolder>,
+    pub ratings: Vec<ParentalRating>,
+}
+
+impl PolicyNames {
+    pub fn fetch(client: &Client) -> Result<Self> {
+        Ok(Self {
+            libraries: client.library().virtual_folders()?,
+            ratings: client.localization().parental_ratings()?,
+        })
+    }
+
+    /// Look up a library's item ID by its name or ID.
+    pub fn library_id(&self, name_or_id: &str) -> Result<String> {
+        libraries::find_in(&self.libraries, name_or_id)
+            .and_then(|f| f.item_id.clone())
+            .ok_or_else(|| Error::NotFound(format!("Library '{name_or_id}' not found")))
+    }
+
+    /// Look up a parental rating's value by name, `none` meaning no limit.
+    pub fn rating_value(&self, name: &str) -> Result<Option<i32>> {
+        if name.eq_ignore_ascii_case("none") {
+            return Ok(None);
+        }
+
+        self.ratings
+            .iter()
+            .find(|r| r.name.eq_ignore_ascii_case(name))
+            .map(|r| Some(r.value))
+            .ok_or_else(|| Error::NotFound(format!("Parental rating '{name}' not found")))
+    }
+
+    /// Describe the libraries a policy allows, e.g. `all` or `Movies, Music`.
+    pub fn libraries(&self, policy: &UserPolicy) -> String {
+        if policy.enable_all_folders.unwrap_or(true) {
+            return "all".to_string();
+        }
+
+        let names: Vec<&str> = policy
+            .enabled_folders
+            .iter()
+            .flatten()
+            .map(|id| {
+                self.libraries
+                    .iter()
+                    .find(|f| f.item_id.as_deref() == Some(id.as_str()))
+                    .and_then(|f| f.name.as_deref())
+                    .unwrap_or(id)
+            })
+            .collect();
+
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    /// Describe a parental rating value, e.g. `PG-13` or `none`.
+    pub fn rating(&self, value: Option<i32>) -> String {
+        match value {
+            None => "none".to_string(),
+            Some(value) => self
+                .ratings
+                .iter()
+                .find(|r| r.value == value)
+                .map_or_else(|| value.to_string(), |r| r.name.clone()),
+        }
+    }
+}
+
+/// Describe a bitrate limit, e.g. `8.0 Mbps` or `none`.
+pub fn describe_bitrate(bps: Option<u64>) -> String {
+    match bps {
+        None | Some(0) => "none".to_string(),
+        Some(bps) => format_bitrate(bps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> PolicyNames {
+        PolicyNames {
+            libraries: serde_json::from_value(serde_json::json!([
+                {"Name": "Movies", "ItemId": "lib-001"},
+                {"Name": "Kids", "ItemId": "lib-002"}
+            ]))
+            .unwrap(),
+            ratings: serde_json::from_value(serde_json::json!([
+                {"Name": "PG", "Value": 5},
+                {"Name": "PG-13", "Value": 7}
+            ]))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn policy_update_reports_changes() {
+        let names = names();
+        let mut policy = UserPolicy {
+            is_administrator: Some(false),
+            max_parental_rating: Some(5),
+            ..UserPolicy::default()
+        };
+
+        let update = PolicyUpdate {
+            admin: Some(false),
+            enabled: Some(false),
+            libraries: Some(LibraryAccess::Only(vec!["lib-002".to_string()])),
+            max_bitrate: Some(8_000_000),
+            max_parental_rating: Some(Some(7)),
+            ..PolicyUpdate::default()
+        };
+
+        assert_eq!(
+            update.apply(&mut policy, &names),
+            [
+                "enabled: true -> false",
+                "libraries: all -> Kids",
+                "max bitrate: none -> 8.0 Mbps",
+                "max parental rating: PG -> PG-13",
+            ]
+        );
+        assert_eq!(policy.is_disabled, Some(true));
+        assert_eq!(policy.enable_all_folders, Some(false));
+        assert_eq!(policy.enabled_folders, Some(vec!["lib-002".to_string()]));
+
+        // Applying the same update again changes nothing
+        assert!(update.apply(&mut policy, &names).is_empty());
+    }
+
+    #[test]
+    fn policy_names_lookups() {
+        let names = names();
+        assert_eq!(names.library_id("kids").unwrap(), "lib-002");
+        assert!(names.library_id("Books").is_err());
+        assert_eq!(names.rating_value("pg-13").unwrap(), Some(7));
+        assert_eq!(names.rating_value("none").unwrap(), None);
+        assert!(names.rating_value("X").is_err());
+    }
+
+    #[test]
+    fn policy_spec_resolves_names() {
+        let spec: PolicySpec = serde_json::from_value(serde_json::json!({
+            "admin": true,
+            "libraries": ["movies", "lib-002"],
+            "max_bitrate": 8_000_000,
+            "max_parental_rating": "PG"
+        }))
+        .unwrap();
+
+        let update = spec.resolve(&names()).unwrap();
+        assert_eq!(update.admin, Some(true));
+        assert_eq!(
+            update.libraries,
+            Some(LibraryAccess::Only(vec![
+                "lib-001".to_string(),
+                "lib-002".to_string()
+            ]))
+        );
+        assert_eq!(update.max_bitrate, Some(8_000_000));
+        assert_eq!(update.max_parental_rating, Some(Some(5)));
+
+        let spec: PolicySpec =
+            serde_json::from_value(serde_json::json!({"libraries": "all"})).unwrap();
+        assert_eq!(
+            spec.resolve(&names()).unwrap().libraries,
+            Some(LibraryAccess::All)
+        );
     }
 }
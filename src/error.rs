@@ -1,12 +1,53 @@
 use std::io;
 
+/// Exit codes, documented in the README and the man page. clap also exits
+/// with 2 when it rejects the arguments.
+pub mod exit_code {
+    pub const FAILURE: i32 = 1;
+    pub const USAGE: i32 = 2;
+    pub const CONFIG: i32 = 3;
+    pub const AUTH: i32 = 4;
+    pub const NOT_FOUND: i32 = 5;
+    pub const UNREACHABLE: i32 = 6;
+    pub const SERVER: i32 = 7;
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    /// Missing or invalid configuration, or not logged in.
     #[error("{0}")]
     Config(String),
 
+    /// Invalid arguments or input, e.g. a malformed duration.
+    #[error("{0}")]
+    Usage(String),
+
+    /// A user, device, library, task or item that doesn't exist.
+    #[error("{0}")]
+    NotFound(String),
+
+    /// The server rejected the credentials (HTTP 401 or 403).
+    #[error("{0}")]
+    Auth(String),
+
+    /// The server couldn't be reached or didn't answer in time.
+    #[error("Could not reach the Emby server ({0}), check api_url and that the server is running")]
+    Unreachable(ureq::Error),
+
+    /// The server failed (HTTP 5xx) or returned something unusable.
+    #[error("{0}")]
+    Server(String),
+
+    /// A task or library scan failed, didn't start or timed out while
+    /// waiting for it (exit code 1).
+    #[error("{0}")]
+    Task(String),
+
     #[error("HTTP request failed: {0}")]
-    Http(#[from] ureq::Error),
+    Http(ureq::Error),
+
+    #[error("WebSocket error: {0}")]
+    WebSocket(Box<tungstenite::Error>),
 
     #[error("Failed to parse JSON: {0}")]
     Json(#[from] serde_json::Error),
@@ -15,4 +56,112 @@ pub enum Error {
     Io(#[from] io::Error),
 }
 
+impl Error {
+    /// The process exit code for this error, see [`exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => exit_code::USAGE,
+            Error::Config(_) => exit_code::CONFIG,
+            Error::Auth(_) => exit_code::AUTH,
+            Error::NotFound(_) => exit_code::NOT_FOUND,
+            Error::Unreachable(_) => exit_code::UNREACHABLE,
+            Error::Server(_) => exit_code::SERVER,
+            Error::Task(_)
+            | Error::Http(_)
+            | Error::WebSocket(_)
+            | Error::Json(_)
+            | Error::Io(_) => exit_code::FAILURE,
+        }
+    }
+
+    /// Whether a request may succeed if tried again: the server couldn't be
+    /// reached, timed out, or failed with a 5xx status (e.g. while restarting).
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Error::Unreachable(_) | Error::Server(_))
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::StatusCode(401) => Error::Auth(
+                "Authentication failed (HTTP 401), check api_key or run `emby login`".to_string(),
+            ),
+            ureq::Error::StatusCode(403) => Error::Auth(
+                "Access denied (HTTP 403), this needs an administrator's API key or login"
+                    .to_string(),
+            ),
+            ureq::Error::StatusCode(404) => {
+                Error::NotFound("Not found on the server (HTTP 404)".to_string())
+            }
+            ureq::Error::StatusCode(status) if status >= 500 => Error::Server(format!(
+                "Emby server error (HTTP {status}), check the server logs"
+            )),
+            ureq::Error::Io(_)
+            | ureq::Error::Timeout(_)
+            | ureq::Error::ConnectionFailed
+            | ureq::Error::HostNotFound
+            | ureq::Error::BodyStalled => Error::Unreachable(error),
+            ureq::Error::Json(e) => Error::Json(e),
+            _ => Error::Http(error),
+        }
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(error: tungstenite::Error) -> Self {
+        match error {
+            // The handshake was refused, e.g. with 401 for a bad API key
+            tungstenite::Error::Http(response) => {
+                Error::from(ureq::Error::StatusCode(response.status().as_u16()))
+            }
+            tungstenite::Error::Io(e) => Error::Unreachable(ureq::Error::Io(e)),
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+                Error::Unreachable(ureq::Error::Io(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "connection closed",
+                )))
+            }
+            _ => Error::WebSocket(Box::new(error)),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_errors_by_status() {
+        assert_eq!(Error::from(ureq::Error::StatusCode(401)).exit_code(), 4);
+        assert_eq!(Error::from(ureq::Error::StatusCode(403)).exit_code(), 4);
+        assert_eq!(Error::from(ureq::Error::StatusCode(404)).exit_code(), 5);
+        assert_eq!(Error::from(ureq::Error::StatusCode(503)).exit_code(), 7);
+        assert_eq!(Error::from(ureq::Error::StatusCode(400)).exit_code(), 1);
+        assert_eq!(Error::from(ureq::Error::ConnectionFailed).exit_code(), 6);
+    }
+
+    #[test]
+    fn websocket_errors() {
+        let refused = tungstenite::http::Response::builder()
+            .status(401)
+            .body(None)
+            .unwrap();
+        assert_eq!(
+            Error::from(tungstenite::Error::Http(Box::new(refused))).exit_code(),
+            4
+        );
+        assert!(Error::from(tungstenite::Error::ConnectionClosed).is_transient());
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(Error::from(ureq::Error::StatusCode(503)).is_transient());
+        assert!(Error::from(ureq::Error::StatusCode(500)).is_transient());
+        assert!(Error::from(ureq::Error::ConnectionFailed).is_transient());
+        assert!(!Error::from(ureq::Error::StatusCode(404)).is_transient());
+        assert!(!Error::from(ureq::Error::StatusCode(401)).is_transient());
+    }
+}
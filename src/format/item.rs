@@ -0,0 +1,315 @@
+use crate::emby::ticks;
+use crate::emby::types::{BaseItemDto, MediaSourceInfo, UserItemDataDto};
+use crate::format::color::ColorConfig;
+use crate::format::text::wrap_text;
+
+/// Width of the label column, including the space after the label.
+const LABEL_WIDTH: usize = 11;
+
+/// Format a single item as labeled lines, e.g. `Genres:    Action, Crime`.
+/// Fields the server didn't return are left out.
+pub fn format_item(item: &BaseItemDto, colors: &ColorConfig) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut field = |label: &str, values: Vec<String>| {
+        push_field(&mut lines, colors, label, &values);
+    };
+
+    field("Name:", opt(item.name.as_deref()));
+    if item.original_title.is_some() && item.original_title != item.name {
+        field("Original:", opt(item.original_title.as_deref()));
+    }
+    field("Type:", opt(item.media_type.as_deref()));
+    field("ID:", opt(item.id.as_deref()));
+
+    if item.media_type.as_deref() == Some("Episode") {
+        field("Series:", opt(item.series_name.as_deref()));
+        field(
+            "Episode:",
+            vec![ticks::format_episode_code(
+                item.parent_index_number,
+                item.index_number,
+            )],
+        );
+    }
+    field("Album:", opt(item.album.as_deref()));
+    field("Artist:", opt(item.album_artist.as_deref()));
+
+    field("Year:", opt(item.production_year.map(|y| y.to_string())));
+    field(
+        "Premiered:",
+        opt(item
+            .premiere_date
+            .as_deref()
+            .map(ticks::format_premiere_date)),
+    );
+    field(
+        "Added:",
+        opt(item
+            .date_created
+            .as_deref()
+            .map(ticks::format_premiere_date)),
+    );
+    field(
+        "Runtime:",
+        opt(item
+            .run_time_ticks
+            .map(|t| ticks::seconds_to_time(ticks::ticks_to_seconds(t)))),
+    );
+    field("Rating:", opt(item.official_rating.as_deref()));
+    field(
+        "Score:",
+        opt(item.community_rating.map(|r| format!("{r:.1}"))),
+    );
+    field("Genres:", joined(item.genres.iter().flatten().cloned()));
+    field(
+        "Studios:",
+        joined(item.studios.iter().flatten().filter_map(|s| s.name.clone())),
+    );
+    field(
+        "Providers:",
+        joined(
+            item.provider_ids
+                .iter()
+                .flatten()
+                .map(|(provider, id)| format!("{provider} {id}")),
+        ),
+    );
+
+    field("Path:", opt(item.path.as_deref()));
+    field("Size:", opt(item.size.map(format_size)));
+    field("Bitrate:", opt(item.bitrate.map(ticks::format_bitrate)));
+
+    if let Some(user_data) = &item.user_data {
+        user_data_fields(&mut field, user_data, item.run_time_ticks);
+    }
+
+    if let Some(tagline) = item.taglines.iter().flatten().next() {
+        field("Tagline:", vec![wrap_text(tagline, 78, LABEL_WIDTH)]);
+    }
+    if let Some(overview) = item.overview.as_deref().filter(|o| !o.is_empty()) {
+        field("Summary:", vec![wrap_text(overview, 78, LABEL_WIDTH)]);
+    }
+
+    field(
+        "People:",
+        item.people
+            .iter()
+            .flatten()
+            .map(|p| {
+                let name = p.name.as_deref().unwrap_or("");
+                match (p.person_type.as_deref(), p.role.as_deref()) {
+                    (Some(kind), Some(role)) if !role.is_empty() => {
+                        format!("{name} ({kind}, as {role})")
+                    }
+                    (Some(kind), _) => format!("{name} ({kind})"),
+                    (None, _) => name.to_string(),
+                }
+            })
+            .collect(),
+    );
+
+    field(
+        "Sources:",
+        item.media_sources
+            .iter()
+            .flatten()
+            .flat_map(|source| source_lines(source, item.path.as_deref()))
+            .collect(),
+    );
+
+    lines.join("\n")
+}
+
+fn user_data_fields(
+    field: &mut impl FnMut(&str, Vec<String>),
+    user_data: &UserItemDataDto,
+    run_time_ticks: Option<u64>,
+) {
+    let mut played = vec![if user_data.played.unwrap_or(false) {
+        "yes".to_string()
+    } else {
+        "no".to_string()
+    }];
+    match user_data.play_count.unwrap_or(0) {
+        0 => {}
+        1 => played.push("1 play".to_string()),
+        count => played.push(format!("{count} plays")),
+    }
+    if let Some(date) = user_data.last_played_date.as_deref() {
+        played.push(format!("last {}", ticks::format_premiere_date(date)));
+    }
+    field("Played:", vec![played.join(", ")]);
+
+    if let Some(position) = user_data.playback_position_ticks.filter(|t| *t > 0) {
+        let mut resume = ticks::seconds_to_time(ticks::ticks_to_seconds(position));
+        if let Some(runtime) = run_time_ticks {
+            resume = format!(
+                "{resume} of {}",
+                ticks::seconds_to_time(ticks::ticks_to_seconds(runtime))
+            );
+        }
+        if let Some(percentage) = user_data.played_percentage {
+            resume = format!("{resume} ({percentage:.0}%)");
+        }
+        field("Resume:", vec![resume]);
+    }
+
+    let favorite = if user_data.is_favorite.unwrap_or(false) {
+        "yes"
+    } else {
+        "no"
+    };
+    field("Favorite:", vec![favorite.to_string()]);
+
+    if let Some(likes) = user_data.likes {
+        let rating = if likes { "like" } else { "dislike" };
+        field("Rated:", vec![rating.to_string()]);
+    }
+}
+
+/// A media source's summary line, its path when it differs from the item's,
+/// and one line per stream.
+fn source_lines(source: &MediaSourceInfo, item_path: Option<&str>) -> Vec<String> {
+    let details: Vec<String> = [
+        source.container.clone(),
+        source.size.map(format_size),
+        source.bitrate.map(ticks::format_bitrate),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let name = source.name.as_deref().unwrap_or("Default");
+    let mut lines = vec![if details.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name} ({})", details.join(", "))
+    }];
+
+    if let Some(path) = source.path.as_deref().filter(|p| Some(*p) != item_path) {
+        lines.push(format!("  {path}"));
+    }
+
+    for stream in source.media_streams.iter().flatten() {
+        let title = stream
+            .display_title
+            .as_deref()
+            .or(stream.codec.as_deref())
+            .unwrap_or("");
+        lines.push(format!(
+            "  {}: {title}",
+            stream.stream_type.as_deref().unwrap_or("Stream")
+        ));
+    }
+
+    lines
+}
+
+/// Add a labeled field, lining continuation lines up under the first value.
+/// Fields without values are skipped.
+fn push_field(lines: &mut Vec<String>, colors: &ColorConfig, label: &str, values: &[String]) {
+    let mut values = values.iter().filter(|v| !v.is_empty());
+    let Some(first) = values.next() else {
+        return;
+    };
+
+    let padding = " ".repeat(LABEL_WIDTH.saturating_sub(label.len()));
+    lines.push(format!("{}{padding}{first}", colors.blue_bold(label)));
+
+    let indent = " ".repeat(LABEL_WIDTH);
+    lines.extend(values.map(|v| format!("{indent}{v}")));
+}
+
+fn opt(value: Option<impl Into<String>>) -> Vec<String> {
+    value.map(Into::into).into_iter().collect()
+}
+
+fn joined(values: impl Iterator<Item = String>) -> Vec<String> {
+    let values: Vec<String> = values.collect();
+    if values.is_empty() {
+        Vec::new()
+    } else {
+        vec![values.join(", ")]
+    }
+}
+
+/// Format a size in bytes, e.g. `4.7 GB` or `512 MB`.
+pub fn format_size(bytes: u64) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let bytes_f = bytes as f64;
+    if bytes >= 1_000_000_000 {
+        format!("{:.1} GB", bytes_f / 1_000_000_000.0)
+    } else if bytes >= 1_000_000 {
+        format!("{} MB", bytes / 1_000_000)
+    } else {
+        format!("{} KB", bytes / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> BaseItemDto {
+        serde_json::from_value(serde_json::json!({
+            "Name": "Heat",
+            "Id": "item-001",
+            "Type": "Movie",
+            "ProductionYear": 1995,
+            "RunTimeTicks": 102_000_000_000_u64,
+            "Genres": ["Action", "Crime"],
+            "Studios": [{"Name": "Warner Bros."}],
+            "ProviderIds": {"Tmdb": "949", "Imdb": "tt0113277"},
+            "People": [
+                {"Name": "Al Pacino", "Role": "Vincent Hanna", "Type": "Actor"},
+                {"Name": "Michael Mann", "Type": "Director"}
+            ],
+            "Path": "/media/movies/Heat.mkv",
+            "MediaSources": [{
+                "Name": "Heat",
+                "Path": "/media/movies/Heat.mkv",
+                "Container": "mkv",
+                "Size": 45_200_000_000_u64,
+                "Bitrate": 35_100_000,
+                "MediaStreams": [
+                    {"Type": "Video", "DisplayTitle": "1080p H264"},
+                    {"Type": "Audio", "Codec": "aac"}
+                ]
+            }],
+            "UserData": {
+                "Played": false,
+                "PlaybackPositionTicks": 36_000_000_000_u64,
+                "PlayedPercentage": 35.3,
+                "IsFavorite": true,
+                "Likes": false
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn format_item_lists_fields() {
+        let output = format_item(&item(), &ColorConfig { enabled: false });
+
+        assert!(output.contains("Name:      Heat\n"));
+        assert!(output.contains("Genres:    Action, Crime\n"));
+        assert!(output.contains("Providers: Imdb tt0113277, Tmdb 949\n"));
+        assert!(output.contains("Resume:    01:00:00 of 02:50:00 (35%)\n"));
+        assert!(output.contains("Favorite:  yes\n"));
+        assert!(output.contains("Rated:     dislike\n"));
+        assert!(output.contains(
+            "People:    Al Pacino (Actor, as Vincent Hanna)\n           Michael Mann (Director)\n"
+        ));
+        assert!(output.ends_with(
+            "Sources:   Heat (mkv, 45.2 GB, 35.1 Mbps)\n             Video: 1080p H264\n             Audio: aac"
+        ));
+        assert!(!output.contains("Series:"));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(45_200_000_000), "45.2 GB");
+        assert_eq!(format_size(512_000_000), "512 MB");
+        assert_eq!(format_size(2048), "2 KB");
+    }
+}
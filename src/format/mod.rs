@@ -1,3 +1,7 @@
 pub mod color;
+pub mod item;
+pub mod output;
 pub mod playing;
+pub mod progress;
 pub mod table;
+pub mod text;
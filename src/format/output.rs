@@ -0,0 +1,243 @@
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+
+use serde_json::{Map, Value};
+
+use crate::error::Result;
+use crate::format::table;
+
+/// Output format selected with the global `--output` option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for humans
+    #[default]
+    Table,
+    /// A single JSON array
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+/// Print rows in the given format.
+///
+/// Table output prints `empty` when there are no rows; the machine-readable
+/// formats print an empty document instead so pipelines keep working.
+pub fn print_rows(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: Vec<Vec<String>>,
+    empty: &str,
+) -> Result<()> {
+    if format == OutputFormat::Table && rows.is_empty() {
+        println!("{empty}");
+        return Ok(());
+    }
+
+    let output = render_rows(format, headers, rows)?;
+    if !output.is_empty() {
+        println!("{output}");
+    }
+
+    Ok(())
+}
+
+/// Print rows that arrive a page at a time. NDJSON, CSV and TSV rows are
+/// printed as each page arrives; table and JSON output need every row first.
+pub fn print_pages(
+    format: OutputFormat,
+    headers: &[&str],
+    pages: impl IntoIterator<Item = Result<Vec<Vec<String>>>>,
+    empty: &str,
+) -> Result<()> {
+    let separator = match format {
+        OutputFormat::Table | OutputFormat::Json => {
+            let rows = pages.into_iter().collect::<Result<Vec<_>>>()?.concat();
+            return print_rows(format, headers, rows, empty);
+        }
+        OutputFormat::Ndjson => None,
+        OutputFormat::Csv => Some(','),
+        OutputFormat::Tsv => Some('\t'),
+    };
+
+    let mut stdout = io::stdout().lock();
+    if let Some(separator) = separator {
+        writeln!(stdout, "{}", delimited_line(headers, separator))?;
+    }
+
+    for page in pages {
+        let page = page?;
+        for row in &page {
+            let line = match separator {
+                Some(separator) => delimited_line(row, separator),
+                None => render_rows(format, headers, vec![row.clone()])?,
+            };
+            writeln!(stdout, "{line}")?;
+        }
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Render rows in the given format. JSON keys are derived from the headers,
+/// e.g. `IP Address` becomes `ip_address`.
+pub fn render_rows(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: Vec<Vec<String>>,
+) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(table::build_table(headers, rows)),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let keys: Vec<String> = headers.iter().map(|h| column_key(h)).collect();
+            let objects: Vec<Value> = rows
+                .into_iter()
+                .map(|row| {
+                    let map: Map<String, Value> = keys
+                        .iter()
+                        .cloned()
+                        .zip(row.into_iter().map(Value::String))
+                        .collect();
+                    Value::Object(map)
+                })
+                .collect();
+            render_objects(format, &objects)
+        }
+        OutputFormat::Csv => Ok(render_delimited(headers, &rows, ',')),
+        OutputFormat::Tsv => Ok(render_delimited(headers, &rows, '\t')),
+    }
+}
+
+/// Render JSON objects in the given format. For table and delimited output
+/// the keys of the first object become the columns.
+pub fn render_objects(format: OutputFormat, objects: &[Value]) -> Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(objects)?),
+        OutputFormat::Ndjson => {
+            let lines: Vec<String> = objects
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<std::result::Result<_, _>>()?;
+            Ok(lines.join("\n"))
+        }
+        OutputFormat::Table | OutputFormat::Csv | OutputFormat::Tsv => {
+            let keys: Vec<&str> = objects
+                .first()
+                .and_then(Value::as_object)
+                .map(|o| o.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            let rows: Vec<Vec<String>> = objects
+                .iter()
+                .map(|o| keys.iter().map(|k| value_to_cell(&o[*k])).collect())
+                .collect();
+            render_rows(format, &keys, rows)
+        }
+    }
+}
+
+/// Convert a column header to a JSON key, e.g. `App (Version)` to `app_version`.
+pub fn column_key(header: &str) -> String {
+    header
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn value_to_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_delimited(headers: &[&str], rows: &[Vec<String>], separator: char) -> String {
+    let mut output = delimited_line(headers, separator);
+
+    for row in rows {
+        let _ = write!(output, "\n{}", delimited_line(row, separator));
+    }
+
+    output
+}
+
+fn delimited_line(fields: &[impl AsRef<str>], separator: char) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| escape_field(f.as_ref(), separator))
+        .collect();
+    fields.join(&separator.to_string())
+}
+
+/// CSV fields are quoted per RFC 4180. TSV has no quoting, so tabs and
+/// newlines inside fields are replaced with spaces.
+fn escape_field(field: &str, separator: char) -> String {
+    if separator == '\t' {
+        return field.replace(['\t', '\n', '\r'], " ");
+    }
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["Alice".to_string(), "30".to_string()],
+            vec!["Bob, Jr.".to_string(), "25".to_string()],
+        ]
+    }
+
+    #[test]
+    fn column_key_normalizes_headers() {
+        assert_eq!(column_key("Name"), "name");
+        assert_eq!(column_key("IP Address"), "ip_address");
+        assert_eq!(column_key("App (Version)"), "app_version");
+    }
+
+    #[test]
+    fn render_json() {
+        let output = render_rows(OutputFormat::Json, &["Name", "Age"], rows()).unwrap();
+        let json: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json[0]["name"], "Alice");
+        assert_eq!(json[1]["age"], "25");
+    }
+
+    #[test]
+    fn render_ndjson() {
+        let output = render_rows(OutputFormat::Ndjson, &["Name", "Age"], rows()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"name":"Alice","age":"30"}"#);
+    }
+
+    #[test]
+    fn render_csv_quotes_fields() {
+        let output = render_rows(OutputFormat::Csv, &["Name", "Age"], rows()).unwrap();
+        assert_eq!(output, "Name,Age\nAlice,30\n\"Bob, Jr.\",25");
+    }
+
+    #[test]
+    fn render_tsv() {
+        let output = render_rows(OutputFormat::Tsv, &["Name", "Age"], rows()).unwrap();
+        assert_eq!(output, "Name\tAge\nAlice\t30\nBob, Jr.\t25");
+    }
+
+    #[test]
+    fn render_empty_json() {
+        let output = render_rows(OutputFormat::Json, &["Name"], vec![]).unwrap();
+        assert_eq!(output, "[]");
+    }
+}
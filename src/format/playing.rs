@@ -1,8 +1,12 @@
+use serde::Serialize;
+
 use crate::emby::ticks;
-use crate::emby::types::Session;
+use crate::emby::types::{MediaStream, Session, TranscodingInfo};
 use crate::format::color::ColorConfig;
+use crate::format::text::wrap_text;
 
 pub struct PlayingEntry {
+    pub session_id: String,
     pub name: String,
     pub date: String,
     pub ip_address: String,
@@ -22,6 +26,30 @@ pub struct PlayingEntry {
     pub album: String,
     pub album_artist: String,
     pub album_track: String,
+    pub transcode: Option<TranscodeEntry>,
+}
+
+/// Source vs. target details for a session that is being transcoded.
+/// Hardware decoder/encoder are `None` when done in software.
+#[derive(Debug, Serialize)]
+pub struct TranscodeEntry {
+    pub reasons: Vec<String>,
+    pub container: Option<String>,
+    pub video_direct: bool,
+    pub source_video_codec: Option<String>,
+    pub target_video_codec: Option<String>,
+    pub source_resolution: Option<String>,
+    pub target_resolution: Option<String>,
+    pub audio_direct: bool,
+    pub source_audio_codec: Option<String>,
+    pub target_audio_codec: Option<String>,
+    pub source_audio_channels: Option<u32>,
+    pub target_audio_channels: Option<u32>,
+    pub bitrate: Option<u64>,
+    pub hardware_decoder: Option<String>,
+    pub hardware_encoder: Option<String>,
+    pub progress_percent: Option<f64>,
+    pub framerate: Option<f64>,
 }
 
 /// Filter sessions to those with `NowPlayingItem`, sort by IP, map to entries.
@@ -104,6 +132,11 @@ fn format_entry(e: &PlayingEntry, colors: &ColorConfig, bar: &str) -> String {
         e.state,
         e.stream
     ));
+
+    if let Some(t) = &e.transcode {
+        lines.extend(format_transcode(t, colors));
+    }
+
     lines.push(format!("{}      {}", colors.blue_bold("Date:"), e.date));
 
     if e.media_type != "Audio" && e.rating != "None" {
@@ -121,6 +154,79 @@ fn format_entry(e: &PlayingEntry, colors: &ColorConfig, bar: &str) -> String {
     lines.join("\n")
 }
 
+fn format_transcode(t: &TranscodeEntry, colors: &ColorConfig) -> Vec<String> {
+    let indent = " ".repeat(11);
+    let mut summary: Vec<String> = Vec::new();
+
+    if let Some(percent) = t.progress_percent {
+        summary.push(format!("{percent:.0}% transcoded"));
+    }
+    if let Some(fps) = t.framerate {
+        summary.push(format!("{fps:.0} fps"));
+    }
+    if let Some(bitrate) = t.bitrate {
+        summary.push(ticks::format_bitrate(bitrate));
+    }
+    if let Some(container) = &t.container {
+        summary.push(container.clone());
+    }
+
+    let mut lines = vec![format!(
+        "{} {}",
+        colors.blue_bold("Transcode:"),
+        summary.join(", ")
+    )];
+
+    if t.source_video_codec.is_some() || t.target_video_codec.is_some() {
+        let source = join_present(&[&t.source_video_codec, &t.source_resolution]);
+        let video = if t.video_direct {
+            format!("{source} (direct)")
+        } else {
+            let target = join_present(&[&t.target_video_codec, &t.target_resolution]);
+            let decode = t
+                .hardware_decoder
+                .as_ref()
+                .map_or_else(|| "software".to_string(), |hw| format!("hardware {hw}"));
+            let encode = t
+                .hardware_encoder
+                .as_ref()
+                .map_or_else(|| "software".to_string(), |hw| format!("hardware {hw}"));
+            format!("{source} -> {target} ({decode} decode, {encode} encode)")
+        };
+        lines.push(format!("{indent}Video: {video}"));
+    }
+
+    if t.source_audio_codec.is_some() || t.target_audio_codec.is_some() {
+        let channels = |c: Option<u32>| c.map(|c| format!("{c}ch"));
+        let source = join_present(&[&t.source_audio_codec, &channels(t.source_audio_channels)]);
+        let audio = if t.audio_direct {
+            format!("{source} (direct)")
+        } else {
+            let target = join_present(&[&t.target_audio_codec, &channels(t.target_audio_channels)]);
+            format!("{source} -> {target}")
+        };
+        lines.push(format!("{indent}Audio: {audio}"));
+    }
+
+    if !t.reasons.is_empty() {
+        lines.push(format!(
+            "{indent}Reasons: {}",
+            wrap_text(&t.reasons.join(", "), 78, 20)
+        ));
+    }
+
+    lines
+}
+
+fn join_present(parts: &[&Option<String>]) -> String {
+    let present: Vec<&str> = parts.iter().filter_map(|p| p.as_deref()).collect();
+    if present.is_empty() {
+        "unknown".to_string()
+    } else {
+        present.join(" ")
+    }
+}
+
 fn map_session(session: &Session) -> PlayingEntry {
     let npi = session.now_playing_item.as_ref().unwrap();
     let play_state = session.play_state.as_ref();
@@ -151,6 +257,7 @@ fn map_session(session: &Session) -> PlayingEntry {
     let is_audio = media_type == "Audio";
 
     PlayingEntry {
+        session_id: session.id.as_deref().unwrap_or("").to_string(),
         name,
         date,
         ip_address: session
@@ -200,10 +307,79 @@ fn map_session(session: &Session) -> PlayingEntry {
         } else {
             String::new()
         },
+        transcode: session
+            .transcoding_info
+            .as_ref()
+            .map(|info| build_transcode(info, npi, play_state)),
         media_type,
     }
 }
 
+fn build_transcode(
+    info: &TranscodingInfo,
+    npi: &crate::emby::types::NowPlayingItem,
+    play_state: Option<&crate::emby::types::PlayState>,
+) -> TranscodeEntry {
+    let streams = npi.media_streams.as_deref().unwrap_or_default();
+    let source_video = streams
+        .iter()
+        .find(|s| s.stream_type.as_deref() == Some("Video"));
+
+    // Prefer the audio stream being played over the default one
+    let audio_index = play_state.and_then(|ps| ps.audio_stream_index);
+    let audio_streams = || {
+        streams
+            .iter()
+            .filter(|s| s.stream_type.as_deref() == Some("Audio"))
+    };
+    let source_audio = audio_streams()
+        .find(|s| audio_index.is_some() && s.index == audio_index)
+        .or_else(|| audio_streams().find(|s| s.is_default.unwrap_or(false)))
+        .or_else(|| audio_streams().next());
+
+    let hardware = |is_hardware: Option<bool>, accel: Option<&String>| {
+        is_hardware
+            .unwrap_or(false)
+            .then(|| accel.cloned().unwrap_or_else(|| "hardware".to_string()))
+    };
+
+    TranscodeEntry {
+        reasons: info.transcode_reasons.clone().unwrap_or_default(),
+        container: info.container.clone(),
+        video_direct: info.is_video_direct.unwrap_or(false),
+        source_video_codec: source_video.and_then(|s| s.codec.clone()),
+        target_video_codec: info.video_codec.clone(),
+        source_resolution: source_video.and_then(stream_resolution),
+        target_resolution: match (info.width, info.height) {
+            (Some(w), Some(h)) => Some(format!("{w}x{h}")),
+            _ => None,
+        },
+        audio_direct: info.is_audio_direct.unwrap_or(false),
+        source_audio_codec: source_audio.and_then(|s| s.codec.clone()),
+        target_audio_codec: info.audio_codec.clone(),
+        source_audio_channels: source_audio.and_then(|s| s.channels),
+        target_audio_channels: info.audio_channels,
+        bitrate: info.bitrate,
+        hardware_decoder: hardware(
+            info.video_decoder_is_hardware,
+            info.video_decoder_hw_accel.as_ref(),
+        ),
+        hardware_encoder: hardware(
+            info.video_encoder_is_hardware,
+            info.video_encoder_hw_accel.as_ref(),
+        ),
+        progress_percent: info.completion_percentage,
+        framerate: info.framerate,
+    }
+}
+
+fn stream_resolution(stream: &MediaStream) -> Option<String> {
+    match (stream.width, stream.height) {
+        (Some(w), Some(h)) => Some(format!("{w}x{h}")),
+        _ => None,
+    }
+}
+
 fn build_name_and_episode(
     npi: &crate::emby::types::NowPlayingItem,
     media_type: &str,
@@ -255,11 +431,9 @@ fn build_progress(
     let progress_seconds = ticks::ticks_to_seconds(position_ticks);
     let remaining_seconds = duration_seconds.saturating_sub(progress_seconds);
 
-    let progress_percent = if duration_seconds > 0 {
-        (progress_seconds * 100 + duration_seconds / 2) / duration_seconds
-    } else {
-        0
-    };
+    let progress_percent = (progress_seconds * 100 + duration_seconds / 2)
+        .checked_div(duration_seconds)
+        .unwrap_or(0);
 
     (
         duration_seconds,
@@ -278,49 +452,6 @@ fn ip_to_decimal(ip: &str) -> u64 {
     }
 }
 
-/// Word-wrap text at `max_width`, indenting continuation lines by `indent` spaces.
-fn wrap_text(text: &str, max_width: usize, indent: usize) -> String {
-    let content_width = max_width - indent;
-    let words: Vec<&str> = text.split_whitespace().collect();
-
-    if words.is_empty() {
-        return String::new();
-    }
-
-    let mut lines: Vec<String> = Vec::new();
-    let mut current_line = String::new();
-
-    for word in &words {
-        if current_line.is_empty() {
-            current_line.push_str(word);
-        } else if current_line.len() + 1 + word.len() <= content_width {
-            current_line.push(' ');
-            current_line.push_str(word);
-        } else {
-            lines.push(current_line);
-            current_line = word.to_string();
-        }
-    }
-
-    if !current_line.is_empty() {
-        lines.push(current_line);
-    }
-
-    let indent_str = " ".repeat(indent);
-    lines
-        .iter()
-        .enumerate()
-        .map(|(i, line)| {
-            if i == 0 {
-                line.clone()
-            } else {
-                format!("{indent_str}{line}")
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,21 +464,88 @@ mod tests {
     }
 
     #[test]
-    fn test_wrap_text_short() {
-        let result = wrap_text("Hello world", 78, 11);
-        assert_eq!(result, "Hello world");
+    fn test_build_progress() {
+        let session: Session = serde_json::from_value(serde_json::json!({
+            "NowPlayingItem": {"Name": "Heat", "RunTimeTicks": 36_000_000_000_u64},
+            "PlayState": {"PositionTicks": 12_000_000_000_u64}
+        }))
+        .unwrap();
+        let npi = session.now_playing_item.as_ref().unwrap();
+        assert_eq!(
+            build_progress(npi, session.play_state.as_ref()),
+            (3600, 1200, 2400, 33)
+        );
+
+        // Items without a runtime, like live TV, have no progress
+        let session: Session = serde_json::from_value(serde_json::json!({
+            "NowPlayingItem": {"Name": "News"},
+            "PlayState": {"PositionTicks": 12_000_000_000_u64}
+        }))
+        .unwrap();
+        let npi = session.now_playing_item.as_ref().unwrap();
+        assert_eq!(
+            build_progress(npi, session.play_state.as_ref()),
+            (0, 1200, 0, 0)
+        );
     }
 
-    #[test]
-    fn test_wrap_text_long() {
-        let long = "word ".repeat(20).trim().to_string();
-        let result = wrap_text(&long, 78, 11);
-        assert!(result.contains('\n'));
-        // Continuation lines should be indented
-        for (i, line) in result.lines().enumerate() {
-            if i > 0 {
-                assert!(line.starts_with("           "));
+    fn transcoding_session() -> Session {
+        serde_json::from_value(serde_json::json!({
+            "UserName": "josh",
+            "NowPlayingItem": {
+                "Name": "Dune",
+                "Type": "Movie",
+                "MediaStreams": [
+                    {"Type": "Video", "Codec": "hevc", "Width": 3840, "Height": 2160},
+                    {"Type": "Audio", "Index": 1, "Codec": "ac3", "Channels": 6},
+                    {"Type": "Audio", "Index": 2, "Codec": "truehd", "Channels": 8}
+                ]
+            },
+            "PlayState": {"PlayMethod": "Transcode", "AudioStreamIndex": 2},
+            "TranscodingInfo": {
+                "VideoCodec": "h264",
+                "AudioCodec": "aac",
+                "Container": "ts",
+                "Width": 1920,
+                "Height": 1080,
+                "AudioChannels": 2,
+                "Bitrate": 8_000_000,
+                "Framerate": 47.6,
+                "CompletionPercentage": 42.0,
+                "IsVideoDirect": false,
+                "IsAudioDirect": false,
+                "VideoDecoderIsHardware": true,
+                "VideoDecoderHwAccel": "VAAPI",
+                "VideoEncoderIsHardware": false,
+                "TranscodeReasons": ["VideoCodecNotSupported", "AudioCodecNotSupported"]
             }
-        }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_transcode() {
+        let entries = build_entries(&[transcoding_session()]);
+        let t = entries[0].transcode.as_ref().unwrap();
+        assert_eq!(t.source_video_codec.as_deref(), Some("hevc"));
+        assert_eq!(t.source_resolution.as_deref(), Some("3840x2160"));
+        assert_eq!(t.target_resolution.as_deref(), Some("1920x1080"));
+        // Uses the playing audio stream, not the first one
+        assert_eq!(t.source_audio_codec.as_deref(), Some("truehd"));
+        assert_eq!(t.hardware_decoder.as_deref(), Some("VAAPI"));
+        assert_eq!(t.hardware_encoder, None);
+    }
+
+    #[test]
+    fn test_format_transcode() {
+        let entries = build_entries(&[transcoding_session()]);
+        let colors = ColorConfig { enabled: false };
+        let output = format_text(&entries, &colors);
+        assert!(output.contains("Transcode: 42% transcoded, 48 fps, 8.0 Mbps, ts"));
+        assert!(output.contains(
+            "Video: hevc 3840x2160 -> h264 1920x1080 (hardware VAAPI decode, software encode)"
+        ));
+        assert!(output.contains("Audio: truehd 8ch -> aac 2ch"));
+        assert!(output.contains("Reasons: VideoCodecNotSupported, AudioCodecNotSupported"));
     }
 }
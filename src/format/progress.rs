@@ -0,0 +1,81 @@
+use std::io::{self, IsTerminal, Write};
+
+/// Render a progress bar like `[#########-----------]  45%`.
+pub fn progress_bar(percent: f64, width: usize) -> String {
+    let percent = percent.clamp(0.0, 100.0);
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    let filled = ((percent / 100.0) * width as f64).round() as usize;
+
+    format!(
+        "[{}{}] {percent:>3.0}%",
+        "#".repeat(filled),
+        "-".repeat(width - filled)
+    )
+}
+
+/// Redraws a single status line on stderr. Does nothing when stderr isn't a
+/// terminal, so scripts only see the final result.
+pub struct ProgressLine {
+    enabled: bool,
+    drawn: bool,
+}
+
+impl ProgressLine {
+    pub fn new() -> Self {
+        Self {
+            enabled: io::stderr().is_terminal(),
+            drawn: false,
+        }
+    }
+
+    pub fn draw(&mut self, line: &str) {
+        if self.enabled {
+            eprint!("\r\x1b[2K{line}");
+            let _ = io::stderr().flush();
+            self.drawn = true;
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if self.drawn {
+            eprintln!();
+            self.drawn = false;
+        }
+    }
+}
+
+impl Default for ProgressLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_bar_empty() {
+        assert_eq!(progress_bar(0.0, 10), "[----------]   0%");
+    }
+
+    #[test]
+    fn progress_bar_partial() {
+        assert_eq!(progress_bar(40.0, 10), "[####------]  40%");
+    }
+
+    #[test]
+    fn progress_bar_clamps() {
+        assert_eq!(progress_bar(150.0, 4), "[####] 100%");
+    }
+}
@@ -0,0 +1,66 @@
+/// Word-wrap text at `max_width`, indenting continuation lines by `indent` spaces.
+pub fn wrap_text(text: &str, max_width: usize, indent: usize) -> String {
+    let content_width = max_width - indent;
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    if words.is_empty() {
+        return String::new();
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut current_line = String::new();
+
+    for word in &words {
+        if current_line.is_empty() {
+            current_line.push_str(word);
+        } else if current_line.len() + 1 + word.len() <= content_width {
+            current_line.push(' ');
+            current_line.push_str(word);
+        } else {
+            lines.push(current_line);
+            current_line = word.to_string();
+        }
+    }
+
+    if !current_line.is_empty() {
+        lines.push(current_line);
+    }
+
+    let indent_str = " ".repeat(indent);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.clone()
+            } else {
+                format!("{indent_str}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_text_short() {
+        let result = wrap_text("Hello world", 78, 11);
+        assert_eq!(result, "Hello world");
+    }
+
+    #[test]
+    fn test_wrap_text_long() {
+        let long = "word ".repeat(20).trim().to_string();
+        let result = wrap_text(&long, 78, 11);
+        assert!(result.contains('\n'));
+        // Continuation lines should be indented
+        for (i, line) in result.lines().enumerate() {
+            if i > 0 {
+                assert!(line.starts_with("           "));
+            }
+        }
+    }
+}
@@ -1,12 +1,6 @@
-mod cli;
-mod client;
-mod commands;
-mod config;
-mod emby;
-mod error;
-mod format;
-
 use clap::Parser;
+use emby_cli::commands::user_data::Action;
+use emby_cli::{cli, commands, error};
 use std::process;
 
 fn main() {
@@ -14,25 +8,46 @@ fn main() {
 
     if let Err(e) = run(&cli) {
         eprintln!("Error: {e}");
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
 
 fn run(cli: &cli::Cli) -> error::Result<()> {
     match &cli.command {
-        cli::Command::Scan(args) => commands::scan::run(args),
-        cli::Command::Playing(args) => commands::playing::run(args),
-        cli::Command::Restart => commands::restart::run(),
-        cli::Command::System => commands::system::run(),
-        cli::Command::Users => commands::users::run(),
-        cli::Command::Devices => commands::devices::run(),
-        cli::Command::Libraries => commands::libraries::run(),
-        cli::Command::FindServer(args) => commands::find_server::run(args),
-        cli::Command::Activity(args) => commands::activity::run(args),
-        cli::Command::Latest(args) => commands::latest::run(args),
-        cli::Command::Search(args) => commands::search::run(args),
-        cli::Command::NextUp(args) => commands::next_up::run(args),
-        cli::Command::Upcoming(args) => commands::upcoming::run(args),
-        cli::Command::Tasks(args) => commands::tasks::run(args),
+        cli::Command::Scan(args) => commands::scan::run(&cli.global, args),
+        cli::Command::Playing(args) => commands::playing::run(&cli.global, args),
+        cli::Command::Session(args) => commands::session::run(&cli.global, args),
+        cli::Command::Restart => commands::restart::run(&cli.global),
+        cli::Command::System => commands::system::run(&cli.global),
+        cli::Command::Users(args) => commands::users::run(&cli.global, args),
+        cli::Command::Devices(args) => commands::devices::run(&cli.global, args),
+        cli::Command::Libraries(args) => commands::libraries::run(&cli.global, args),
+        cli::Command::FindServer(args) => commands::find_server::run(&cli.global, args),
+        cli::Command::Activity(args) => commands::activity::run(&cli.global, args),
+        cli::Command::Events(args) => commands::events::run(&cli.global, args),
+        cli::Command::Hooks(args) => commands::hooks::run(&cli.global, args),
+        cli::Command::Latest(args) => commands::latest::run(&cli.global, args),
+        cli::Command::Search(args) => commands::search::run(&cli.global, args),
+        cli::Command::Item(args) => commands::item::run(&cli.global, args),
+        cli::Command::Pick(args) => commands::pick::run(&cli.global, args),
+        cli::Command::Dashboard(args) => commands::dashboard::run(&cli.global, args),
+        cli::Command::Played(args) => commands::user_data::run(&cli.global, args, Action::Played),
+        cli::Command::Unplayed(args) => {
+            commands::user_data::run(&cli.global, args, Action::Unplayed)
+        }
+        cli::Command::Favorite(args) => {
+            commands::user_data::run(&cli.global, args, Action::Favorite)
+        }
+        cli::Command::Unfavorite(args) => {
+            commands::user_data::run(&cli.global, args, Action::Unfavorite)
+        }
+        cli::Command::Rate(args) => {
+            commands::user_data::run(&cli.global, &args.items, Action::Rate(args.rating))
+        }
+        cli::Command::NextUp(args) => commands::next_up::run(&cli.global, args),
+        cli::Command::Upcoming(args) => commands::upcoming::run(&cli.global, args),
+        cli::Command::Tasks(args) => commands::tasks::run(&cli.global, args),
+        cli::Command::Login(args) => commands::login::run(&cli.global, args),
+        cli::Command::Logout => commands::logout::run(&cli.global),
     }
 }
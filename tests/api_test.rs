@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use emby_cli::api::{LatestOptions, SearchOptions};
+use emby_cli::client::{Client, HttpOptions};
+use emby_cli::config::Config;
+
+// The typed API is exercised directly against a mock server, the way
+// external tooling would use the library.
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn load_fixture(name: &str) -> String {
+    std::fs::read_to_string(fixture_path(name)).unwrap()
+}
+
+fn client(server: &mockito::ServerGuard) -> Client {
+    Client::new(&Config {
+        api_url: server.url(),
+        api_key: "test-api-key".to_string(),
+        user_id: None,
+        http: HttpOptions {
+            retries: 0,
+            ..HttpOptions::default()
+        },
+    })
+}
+
+#[test]
+fn sessions_list() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .match_header("X-Emby-Token", "test-api-key")
+        .with_body(load_fixture("sessions.json"))
+        .create();
+
+    let sessions = client(&server).sessions().list().unwrap();
+    assert_eq!(sessions.len(), 3);
+    assert_eq!(sessions[0].user_name.as_deref(), Some("josh"));
+}
+
+#[test]
+fn items_latest_sends_options() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Users/abc123/Items/Latest")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("Limit".into(), "5".into()),
+            mockito::Matcher::UrlEncoded("IncludeItemTypes".into(), "Movie".into()),
+            mockito::Matcher::UrlEncoded("GroupItems".into(), "true".into()),
+        ]))
+        .with_body(load_fixture("latest_items.json"))
+        .create();
+
+    let items = client(&server)
+        .items()
+        .latest(
+            "abc123",
+            &LatestOptions {
+                limit: Some(5),
+                include_item_types: Some("Movie".to_string()),
+                group_items: true,
+                ..LatestOptions::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(items[0].name.as_deref(), Some("Inception"));
+}
+
+#[test]
+fn items_search() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("SearchTerm".into(), "matrix".into()),
+            mockito::Matcher::UrlEncoded("Recursive".into(), "true".into()),
+        ]))
+        .with_body(load_fixture("search_hints.json"))
+        .create();
+
+    let result = client(&server)
+        .items()
+        .search(&SearchOptions {
+            search_term: "matrix".to_string(),
+            ..SearchOptions::default()
+        })
+        .unwrap();
+
+    assert!(result.items.unwrap().len() > 1);
+}
+
+#[test]
+fn scheduled_tasks_run() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/emby/ScheduledTasks/Running/task-001")
+        .with_status(204)
+        .create();
+
+    client(&server).scheduled_tasks().run("task-001").unwrap();
+    mock.assert();
+}
+
+#[test]
+fn api_errors_are_returned() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/System/Info")
+        .with_status(500)
+        .create();
+
+    assert!(client(&server).system().info().is_err());
+}
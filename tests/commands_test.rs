@@ -1,6 +1,11 @@
 use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::PredicateBooleanExt;
 use predicates::str::contains;
+use std::io::Write;
+use std::net::TcpListener;
 use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
 
 fn fixture_path(name: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -17,6 +22,8 @@ fn emby_cmd(server: &mockito::ServerGuard) -> assert_cmd::Command {
     let mut cmd = cargo_bin_cmd!("emby");
     cmd.env("EMBY_API_URL", server.url());
     cmd.env("EMBY_API_KEY", "test-api-key");
+    // Fail fast on server errors; tests that cover retries pass --retries
+    cmd.env("EMBY_RETRIES", "0");
     cmd
 }
 
@@ -41,7 +48,7 @@ fn system_shows_info() {
         .stdout(contains("MediaServer"))
         .stdout(contains("Linux"))
         .stdout(contains("Update Available: No"))
-        .stdout(contains(&server.url()));
+        .stdout(contains(server.url()));
 }
 
 // --- users ---
@@ -68,330 +75,2092 @@ fn users_shows_table() {
         .stdout(contains("false"));
 }
 
-// --- devices ---
+fn users_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/Users")
+        .with_body(load_fixture("users.json"))
+        .with_header("content-type", "application/json")
+        .create()
+}
+
+fn policy_names_mocks(server: &mut mockito::ServerGuard) -> Vec<mockito::Mock> {
+    vec![
+        server
+            .mock("GET", "/emby/Library/VirtualFolders")
+            .with_body(load_fixture("libraries.json"))
+            .with_header("content-type", "application/json")
+            .create(),
+        server
+            .mock("GET", "/emby/Localization/ParentalRatings")
+            .with_body(r#"[{"Name": "PG", "Value": 5}, {"Name": "PG-13", "Value": 7}]"#)
+            .with_header("content-type", "application/json")
+            .create(),
+    ]
+}
 
 #[test]
-fn devices_shows_table() {
+fn users_create_with_password() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("devices.json");
-    let _mock = server
-        .mock("GET", "/emby/Devices")
-        .with_body(&body)
+    let _users = users_mock(&mut server);
+    let create = server
+        .mock("POST", "/emby/Users/New")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Name": "alice",
+            "CopyFromUserId": "def456",
+            "UserCopyOptions": ["UserPolicy", "UserConfiguration"]
+        })))
+        .with_body(r#"{"Name": "alice", "Id": "ghi789"}"#)
         .with_header("content-type", "application/json")
         .create();
+    let password = server
+        .mock("POST", "/emby/Users/ghi789/Password")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"NewPw": "hunter2"}),
+        ))
+        .with_status(204)
+        .create();
 
     emby_cmd(&server)
-        .arg("devices")
+        .args([
+            "users",
+            "create",
+            "alice",
+            "--copy-from",
+            "Bob",
+            "--password-stdin",
+        ])
+        .write_stdin("hunter2\n")
         .assert()
         .success()
-        .stdout(contains("Apple TV"))
-        .stdout(contains("192.168.1.100"))
-        .stdout(contains("Infuse"))
-        .stdout(contains("device-001"));
+        .stdout(contains("Created user alice (ghi789)"));
+
+    create.assert();
+    password.assert();
 }
 
-// --- libraries ---
+#[test]
+fn users_delete_requires_confirmation() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["users", "delete", "bob"])
+        .assert()
+        .failure()
+        .stderr(contains("Pass --yes to confirm"));
+}
 
 #[test]
-fn libraries_shows_table() {
+fn users_delete() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("libraries.json");
-    let _mock = server
-        .mock("GET", "/emby/Library/VirtualFolders")
-        .with_body(&body)
-        .with_header("content-type", "application/json")
+    let _users = users_mock(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Users/def456")
+        .with_status(204)
         .create();
 
     emby_cmd(&server)
-        .arg("libraries")
+        .args(["users", "delete", "bob", "--yes"])
         .assert()
         .success()
-        .stdout(contains("Movies"))
-        .stdout(contains("movies"))
-        .stdout(contains("lib-001"))
-        .stdout(contains("TV Shows"))
-        .stdout(contains("tvshows"));
-}
+        .stdout(contains("Deleted user bob"));
 
-// --- activity ---
+    delete.assert();
+}
 
 #[test]
-fn activity_shows_table() {
+fn users_rename_keeps_other_fields() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("activity_log.json");
-    let _mock = server
-        .mock("GET", "/emby/System/ActivityLog/Entries")
-        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "25".into()))
-        .with_body(&body)
+    let _users = users_mock(&mut server);
+    let _get = server
+        .mock("GET", "/emby/Users/def456")
+        .with_body(r#"{"Name": "bob", "Id": "def456", "ServerId": "srv-1"}"#)
         .with_header("content-type", "application/json")
         .create();
+    let update = server
+        .mock("POST", "/emby/Users/def456")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "Name": "robert",
+            "Id": "def456",
+            "ServerId": "srv-1"
+        })))
+        .with_status(204)
+        .create();
 
     emby_cmd(&server)
-        .arg("activity")
+        .args(["users", "rename", "bob", "robert"])
         .assert()
         .success()
-        .stdout(contains("josh logged in"))
-        .stdout(contains("Info"))
-        .stdout(contains("Login from 192.168.1.100"));
+        .stdout(contains("Renamed user bob to robert"));
+
+    update.assert();
 }
 
 #[test]
-fn activity_with_limit() {
+fn users_set_password_remove() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("activity_log.json");
-    let _mock = server
-        .mock("GET", "/emby/System/ActivityLog/Entries")
-        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "5".into()))
-        .with_body(&body)
-        .with_header("content-type", "application/json")
+    let _users = users_mock(&mut server);
+    let password = server
+        .mock("POST", "/emby/Users/def456/Password")
+        .match_body(mockito::Matcher::Json(
+            serde_json::json!({"Id": "def456", "ResetPassword": true}),
+        ))
+        .with_status(204)
         .create();
 
     emby_cmd(&server)
-        .args(["activity", "--limit", "5"])
+        .args(["users", "set-password", "bob", "--remove"])
         .assert()
-        .success();
-}
+        .success()
+        .stdout(contains("Removed password for bob"));
 
-// --- search ---
+    password.assert();
+}
 
 #[test]
-fn search_shows_results() {
+fn users_policy_shows_settings() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("search_hints.json");
-    let _mock = server
-        .mock("GET", "/emby/Items")
-        .match_query(mockito::Matcher::AllOf(vec![
-            mockito::Matcher::UrlEncoded("SearchTerm".into(), "friends".into()),
-            mockito::Matcher::UrlEncoded("Recursive".into(), "true".into()),
-            mockito::Matcher::UrlEncoded("Limit".into(), "25".into()),
-        ]))
-        .with_body(&body)
-        .with_header("content-type", "application/json")
-        .create();
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
 
     emby_cmd(&server)
-        .args(["search", "friends"])
+        .args(["users", "policy", "bob", "--output", "csv"])
         .assert()
         .success()
-        .stdout(contains("Friends - S03E02 - The One Where No One's Ready"))
-        .stdout(contains("Queen - Bohemian Rhapsody"))
-        .stdout(contains("The Matrix"));
+        .stdout(contains("Admin,false"))
+        .stdout(contains("Libraries,all"))
+        .stdout(contains("Max Parental Rating,none"));
 }
 
 #[test]
-fn search_no_results() {
+fn users_policy_updates_settings() {
     let mut server = mockito::Server::new();
-    let _mock = server
-        .mock("GET", "/emby/Items")
-        .match_query(mockito::Matcher::Any)
-        .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
-        .with_header("content-type", "application/json")
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
+    let update = server
+        .mock("POST", "/emby/Users/def456/Policy")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "IsAdministrator": false,
+            "IsDisabled": false,
+            "EnableAllFolders": false,
+            "EnabledFolders": ["lib-001", "lib-003"],
+            "RemoteClientBitrateLimit": 8_000_000,
+            "MaxParentalRating": 7,
+            "EnableContentDownloading": false
+        })))
+        .with_status(204)
         .create();
 
     emby_cmd(&server)
-        .args(["search", "nonexistent"])
+        .args([
+            "users",
+            "policy",
+            "bob",
+            "--enabled",
+            "true",
+            "--libraries",
+            "movies,Music",
+            "--max-bitrate",
+            "8M",
+            "--max-parental-rating",
+            "PG-13",
+        ])
         .assert()
         .success()
-        .stdout(contains("No results found"));
+        .stdout(contains("Updated policy for bob"))
+        .stdout(contains("libraries: all -> Movies, Music"))
+        .stdout(contains("max parental rating: none -> PG-13"))
+        .stdout(contains("enabled").not());
+
+    update.assert();
 }
 
-// --- playing ---
+#[test]
+fn users_policy_unknown_library() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
+
+    emby_cmd(&server)
+        .args(["users", "policy", "bob", "--libraries", "Books"])
+        .assert()
+        .code(5)
+        .stderr(contains("Library 'Books' not found"));
+}
 
 #[test]
-fn playing_shows_text_output() {
+fn users_apply_dry_run() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("sessions.json");
-    let _mock = server
-        .mock("GET", "/emby/Sessions")
-        .with_body(&body)
-        .with_header("content-type", "application/json")
-        .create();
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
+
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(
+        file,
+        r#"
+        [[users]]
+        name = "josh"
+        policy = {{ admin = true }}
+
+        [[users]]
+        name = "bob"
+        policy = {{ libraries = ["Movies"], max_parental_rating = "PG" }}
+
+        [[users]]
+        name = "alice"
+        "#
+    )
+    .unwrap();
 
     emby_cmd(&server)
-        .args(["playing", "--plain"])
+        .args(["users", "apply", "--dry-run"])
+        .arg(file.path())
         .assert()
         .success()
-        .stdout(contains("Friends - S03E02"))
-        .stdout(contains("Queen - Bohemian Rhapsody"))
-        .stdout(contains("Duration:"))
-        .stdout(contains("Progress:"));
+        .stdout(contains("~ update bob"))
+        .stdout(contains("    libraries: all -> Movies"))
+        .stdout(contains("+ create alice"))
+        .stdout(contains("josh").not())
+        .stdout(contains("1 to create, 1 to update, 0 to delete"))
+        .stdout(contains("Dry run, nothing changed"));
 }
 
 #[test]
-fn playing_json_output() {
+fn users_apply_from_stdin() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("sessions.json");
-    let _mock = server
-        .mock("GET", "/emby/Sessions")
-        .with_body(&body)
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
+    let create = server
+        .mock("POST", "/emby/Users/New")
+        .match_body(mockito::Matcher::Json(serde_json::json!({"Name": "alice"})))
+        .with_body(r#"{"Name": "alice", "Id": "ghi789", "Policy": {"IsAdministrator": false}}"#)
         .with_header("content-type", "application/json")
         .create();
+    let policy = server
+        .mock("POST", "/emby/Users/ghi789/Policy")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"RemoteClientBitrateLimit": 4_000_000}),
+        ))
+        .with_status(204)
+        .create();
+    let delete = server
+        .mock("DELETE", "/emby/Users/def456")
+        .with_status(204)
+        .create();
 
-    let output = emby_cmd(&server)
-        .args(["playing", "--json"])
-        .output()
-        .unwrap();
+    emby_cmd(&server)
+        .args(["users", "apply", "-", "--prune", "--yes"])
+        .write_stdin(
+            r#"{"users": [
+                {"name": "josh"},
+                {"name": "alice", "policy": {"max_bitrate": "4M"}}
+            ]}"#,
+        )
+        .assert()
+        .success()
+        .stdout(contains("- delete bob"))
+        .stdout(contains("Created alice"))
+        .stdout(contains("Deleted bob"));
 
-    assert!(output.status.success());
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert!(json.is_array());
-    assert_eq!(json.as_array().unwrap().len(), 2);
+    create.assert();
+    policy.assert();
+    delete.assert();
 }
 
 #[test]
-fn playing_raw_output() {
+fn users_apply_prune_needs_confirmation() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("sessions.json");
-    let _mock = server
-        .mock("GET", "/emby/Sessions")
-        .with_body(&body)
-        .with_header("content-type", "application/json")
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Users/def456")
+        .expect(0)
         .create();
 
-    let output = emby_cmd(&server)
-        .args(["playing", "--raw"])
-        .output()
-        .unwrap();
+    emby_cmd(&server)
+        .args(["users", "apply", "-", "--prune"])
+        .write_stdin(r#"{"users": [{"name": "josh"}]}"#)
+        .assert()
+        .code(2)
+        .stdout(contains("- delete bob"))
+        .stderr(contains("Delete 1 user? Pass --yes"));
 
-    assert!(output.status.success());
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert!(json.is_array());
-    // Raw shows only active sessions (with NowPlayingItem)
-    assert_eq!(json.as_array().unwrap().len(), 2);
+    delete.assert();
 }
 
 #[test]
-fn playing_nothing_playing() {
+fn users_apply_rejects_unknown_settings() {
     let mut server = mockito::Server::new();
-    let _mock = server
-        .mock("GET", "/emby/Sessions")
-        .with_body(r#"[{"UserName": "idle", "DeviceName": "Browser"}]"#)
-        .with_header("content-type", "application/json")
-        .create();
+    let _users = users_mock(&mut server);
+    let _names = policy_names_mocks(&mut server);
 
     emby_cmd(&server)
-        .args(["playing", "--plain"])
+        .args(["users", "apply", "-"])
+        .write_stdin(r#"{"users": [{"name": "bob", "policy": {"superuser": true}}]}"#)
         .assert()
-        .success()
-        .stdout(contains("Nothing playing"));
+        .failure()
+        .stderr(contains("unknown field `superuser`"));
 }
 
-// --- tasks ---
+// --- devices ---
 
 #[test]
-fn tasks_shows_table() {
+fn devices_shows_table() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("scheduled_tasks.json");
+    let body = load_fixture("devices.json");
     let _mock = server
-        .mock("GET", "/emby/ScheduledTasks")
+        .mock("GET", "/emby/Devices")
         .with_body(&body)
         .with_header("content-type", "application/json")
         .create();
 
     emby_cmd(&server)
-        .arg("tasks")
+        .arg("devices")
         .assert()
         .success()
-        .stdout(contains("Scan Media Library"))
-        .stdout(contains("Library"))
-        .stdout(contains("Idle"))
-        .stdout(contains("Download Subtitles"));
+        .stdout(contains("Apple TV"))
+        .stdout(contains("192.168.1.100"))
+        .stdout(contains("Infuse"))
+        .stdout(contains("device-001"));
+}
+
+fn devices_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/Devices")
+        .with_body(load_fixture("devices.json"))
+        .with_header("content-type", "application/json")
+        .create()
 }
 
 #[test]
-fn tasks_hides_hidden_by_default() {
+fn devices_delete_by_name() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("scheduled_tasks.json");
-    let _mock = server
-        .mock("GET", "/emby/ScheduledTasks")
-        .with_body(&body)
-        .with_header("content-type", "application/json")
+    let _devices = devices_mock(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Devices")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "Id".into(),
+            "device-002".into(),
+        ))
+        .with_status(204)
         .create();
 
-    let output = emby_cmd(&server).arg("tasks").output().unwrap();
+    emby_cmd(&server)
+        .args(["devices", "delete", "iphone", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted device iPhone (device-002)"));
 
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    assert!(stdout.contains("Scan Media Library"));
-    assert!(!stdout.contains("Clean Cache"));
+    delete.assert();
 }
 
 #[test]
-fn tasks_all_shows_hidden() {
+fn devices_rename() {
     let mut server = mockito::Server::new();
-    let body = load_fixture("scheduled_tasks.json");
-    let _mock = server
-        .mock("GET", "/emby/ScheduledTasks")
-        .with_body(&body)
-        .with_header("content-type", "application/json")
+    let _devices = devices_mock(&mut server);
+    let rename = server
+        .mock("POST", "/emby/Devices/Options")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "Id".into(),
+            "device-001".into(),
+        ))
+        .match_body(mockito::Matcher::Json(
+            serde_json::json!({"CustomName": "Living Room"}),
+        ))
+        .with_status(204)
         .create();
 
     emby_cmd(&server)
-        .args(["tasks", "--all"])
+        .args(["devices", "rename", "device-001", "Living Room"])
         .assert()
         .success()
-        .stdout(contains("Clean Cache"));
-}
+        .stdout(contains("Renamed device Apple TV to Living Room"));
 
-// --- latest ---
+    rename.assert();
+}
 
 #[test]
-fn latest_shows_table() {
+fn devices_prune_dry_run() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-    let latest_body = load_fixture("latest_items.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
-        .with_header("content-type", "application/json")
-        .create();
-
-    let _latest_mock = server
-        .mock("GET", "/emby/Users/abc123/Items/Latest")
+    let _devices = devices_mock(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Devices")
         .match_query(mockito::Matcher::Any)
-        .with_body(&latest_body)
-        .with_header("content-type", "application/json")
+        .expect(0)
         .create();
 
     emby_cmd(&server)
-        .arg("latest")
+        .args(["devices", "prune", "--older-than", "90d", "--dry-run"])
         .assert()
         .success()
-        .stdout(contains("Inception"))
-        .stdout(contains("Friends - S05E14"))
-        .stdout(contains("Led Zeppelin - Stairway to Heaven"));
+        .stdout(contains(
+            "Would delete iPhone (device-002), last active Jan 10, 2023",
+        ));
+
+    delete.assert();
 }
 
 #[test]
-fn latest_with_user_flag() {
+fn devices_prune() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-    let latest_body = load_fixture("latest_items.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
-        .with_header("content-type", "application/json")
-        .create();
-
-    let _latest_mock = server
-        .mock("GET", "/emby/Users/def456/Items/Latest")
+    let _devices = devices_mock(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Devices")
         .match_query(mockito::Matcher::Any)
-        .with_body(&latest_body)
-        .with_header("content-type", "application/json")
+        .with_status(204)
+        .expect(2)
         .create();
 
     emby_cmd(&server)
-        .args(["latest", "--user", "bob"])
+        .args(["devices", "prune", "--older-than", "90d", "--yes"])
         .assert()
         .success()
-        .stdout(contains("Inception"));
+        .stdout(contains("Deleted 2 devices"));
+
+    delete.assert();
 }
 
 #[test]
-fn latest_empty() {
+fn devices_prune_rejects_bad_duration() {
+    emby_cmd(&mockito::Server::new())
+        .args(["devices", "prune", "--older-than", "soon"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid duration 'soon'"));
+}
+
+#[test]
+fn devices_prune_rejects_zero_or_negative_age() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
+    let devices = server
+        .mock("GET", "/emby/Devices")
+        .match_query(mockito::Matcher::Any)
+        .expect(0)
+        .create();
+    let delete = server
+        .mock("DELETE", "/emby/Devices")
+        .match_query(mockito::Matcher::Any)
+        .expect(0)
+        .create();
+
+    for age in ["-1d", "0d", "-9223372036854775808s"] {
+        emby_cmd(&server)
+            .args(["devices", "prune", "--yes", &format!("--older-than={age}")])
+            .assert()
+            .code(2)
+            .stderr(contains(format!("Invalid duration '{age}'")));
+    }
+
+    devices.assert();
+    delete.assert();
+}
+
+// --- libraries ---
+
+fn libraries_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(load_fixture("libraries.json"))
+        .with_header("content-type", "application/json")
+        .create()
+}
+
+#[test]
+fn libraries_shows_table() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let _counts = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("Recursive".into(), "true".into()),
+            mockito::Matcher::UrlEncoded("IsFolder".into(), "false".into()),
+            mockito::Matcher::UrlEncoded("Limit".into(), "0".into()),
+        ]))
+        .with_body(r#"{"Items": [], "TotalRecordCount": 1234}"#)
+        .with_header("content-type", "application/json")
+        .expect(4)
+        .create();
+
+    emby_cmd(&server)
+        .arg("libraries")
+        .assert()
+        .success()
+        .stdout(contains("Movies"))
+        .stdout(contains("movies"))
+        .stdout(contains("lib-001"))
+        .stdout(contains("TV Shows"))
+        .stdout(contains("tvshows"))
+        .stdout(contains("1234"))
+        .stdout(contains("/media/tv, /mnt/archive/tv"));
+}
+
+#[test]
+fn libraries_list_survives_failed_counts() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let _counts = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"Items": [], "TotalRecordCount": 1234}"#)
+        .with_header("content-type", "application/json")
+        .create();
+    let _failed = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "ParentId".into(),
+            "lib-002".into(),
+        ))
+        .with_status(500)
+        .create();
+
+    emby_cmd(&server)
+        .args(["libraries", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(contains("Movies,movies,1234,"))
+        .stdout(contains("TV Shows,tvshows,,"));
+}
+
+#[test]
+fn libraries_create() {
+    let mut server = mockito::Server::new();
+    let create = server
+        .mock("POST", "/emby/Library/VirtualFolders")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("Name".into(), "Kids".into()),
+            mockito::Matcher::UrlEncoded("CollectionType".into(), "movies".into()),
+            mockito::Matcher::UrlEncoded("RefreshLibrary".into(), "false".into()),
+        ]))
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "LibraryOptions": {
+                "PathInfos": [{"Path": "/media/kids"}, {"Path": "/mnt/kids"}]
+            }
+        })))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args([
+            "libraries",
+            "create",
+            "Kids",
+            "--type",
+            "movies",
+            "--path",
+            "/media/kids",
+            "--path",
+            "/mnt/kids",
+            "--no-scan",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Created library Kids (movies)"));
+
+    create.assert();
+}
+
+#[test]
+fn libraries_add_path() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let add = server
+        .mock("POST", "/emby/Library/VirtualFolders/Paths")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "RefreshLibrary".into(),
+            "true".into(),
+        ))
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Name": "Movies",
+            "PathInfo": {"Path": "/mnt/movies"}
+        })))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["libraries", "add-path", "lib-001", "/mnt/movies"])
+        .assert()
+        .success()
+        .stdout(contains("Added /mnt/movies to library Movies"));
+
+    add.assert();
+}
+
+#[test]
+fn libraries_remove_path() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let remove = server
+        .mock("DELETE", "/emby/Library/VirtualFolders/Paths")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("Name".into(), "TV Shows".into()),
+            mockito::Matcher::UrlEncoded("Path".into(), "/mnt/archive/tv".into()),
+        ]))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["libraries", "remove-path", "tv shows", "/mnt/archive/tv"])
+        .assert()
+        .success()
+        .stdout(contains("Removed /mnt/archive/tv from library TV Shows"));
+
+    remove.assert();
+}
+
+#[test]
+fn libraries_remove_unknown_path() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["libraries", "remove-path", "Movies", "/mnt/nope"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Library Movies has no path /mnt/nope (paths: /media/movies)",
+        ));
+}
+
+#[test]
+fn libraries_rename() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let rename = server
+        .mock("POST", "/emby/Library/VirtualFolders/Name")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("Name".into(), "Music".into()),
+            mockito::Matcher::UrlEncoded("NewName".into(), "Audio".into()),
+        ]))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["libraries", "rename", "music", "Audio"])
+        .assert()
+        .success()
+        .stdout(contains("Renamed library Music to Audio"));
+
+    rename.assert();
+}
+
+#[test]
+fn libraries_delete() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let delete = server
+        .mock("DELETE", "/emby/Library/VirtualFolders")
+        .match_query(mockito::Matcher::UrlEncoded("Name".into(), "Photos".into()))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["libraries", "delete", "photos", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted library Photos"));
+
+    delete.assert();
+}
+
+#[test]
+fn libraries_unknown_library() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["libraries", "rename", "Books", "Reading"])
+        .assert()
+        .failure()
+        .stderr(contains("Library 'Books' not found (available: Movies"));
+}
+
+// --- activity ---
+
+#[test]
+fn activity_shows_table() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("activity_log.json");
+    let _mock = server
+        .mock("GET", "/emby/System/ActivityLog/Entries")
+        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "25".into()))
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("activity")
+        .assert()
+        .success()
+        .stdout(contains("josh logged in"))
+        .stdout(contains("Info"))
+        .stdout(contains("Login from 192.168.1.100"));
+}
+
+#[test]
+fn activity_with_limit() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("activity_log.json");
+    let _mock = server
+        .mock("GET", "/emby/System/ActivityLog/Entries")
+        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "5".into()))
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["activity", "--limit", "5"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn activity_all_pages_through_log() {
+    let mut server = mockito::Server::new();
+    let entries = server
+        .mock("GET", "/emby/System/ActivityLog/Entries")
+        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "500".into()))
+        .with_body(load_fixture("activity_log.json"))
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["activity", "--all"])
+        .assert()
+        .success();
+
+    entries.assert();
+}
+
+#[test]
+fn activity_filters_by_type() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("activity_log.json");
+    let _mock = server
+        .mock("GET", "/emby/System/ActivityLog/Entries")
+        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "100".into()))
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["activity", "--type", "taskcompleted", "--severity", "info"])
+        .assert()
+        .success()
+        .stdout(contains("Library scan completed"))
+        .stdout(contains("josh logged in").not());
+}
+
+#[test]
+fn activity_since_until() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("activity_log.json");
+    let _mock = server
+        .mock("GET", "/emby/System/ActivityLog/Entries")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "MinDate".into(),
+            "2024-06-13T00:00:00Z".into(),
+        ))
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args([
+            "activity",
+            "--since",
+            "2024-06-13",
+            "--until",
+            "2024-06-15",
+            "--output",
+            "tsv",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Library scan completed"))
+        .stdout(contains("bob played The Matrix"))
+        .stdout(contains("josh logged in").not());
+}
+
+#[test]
+fn activity_rejects_invalid_time() {
+    emby_cmd(&mockito::Server::new())
+        .args(["activity", "--since", "last tuesday"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid time 'last tuesday'"));
+
+    emby_cmd(&mockito::Server::new())
+        .args(["activity", "--since=-9223372036854775808s"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid time"));
+}
+
+#[test]
+fn activity_follow_rejects_json() {
+    emby_cmd(&mockito::Server::new())
+        .args(["activity", "--follow", "--output", "json"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Follow mode only supports table and ndjson output",
+        ));
+}
+
+// --- events ---
+
+/// Stands in for Emby's WebSocket: reads the client's `subscriptions`,
+/// sends `messages`, then closes the connection or waits for the client to.
+/// Returns the URL to connect to, and a handle that gives back the request
+/// URI and the subscriptions.
+#[allow(clippy::result_large_err)] // tungstenite's handshake callback signature
+fn websocket_server(
+    subscriptions: usize,
+    messages: Vec<&'static str>,
+    close: bool,
+) -> (String, thread::JoinHandle<(String, Vec<String>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut uri = String::new();
+        let mut socket = tungstenite::accept_hdr(
+            stream,
+            |request: &tungstenite::handshake::server::Request,
+             response: tungstenite::handshake::server::Response| {
+                uri = request.uri().to_string();
+                Ok(response)
+            },
+        )
+        .unwrap();
+
+        let subscriptions = (0..subscriptions)
+            .map(|_| socket.read().unwrap().into_text().unwrap().to_string())
+            .collect();
+        for message in messages {
+            socket.send(tungstenite::Message::text(message)).unwrap();
+        }
+
+        if close {
+            socket.close(None).unwrap();
+        }
+        while socket.read().is_ok() {}
+
+        (uri, subscriptions)
+    });
+
+    (url, handle)
+}
+
+fn events_cmd(url: &str) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("emby");
+    cmd.env("EMBY_API_URL", url);
+    cmd.env("EMBY_API_KEY", "test-api-key");
+    cmd.env("EMBY_RETRIES", "0");
+    cmd.timeout(Duration::from_secs(10));
+    cmd
+}
+
+const SESSIONS_EVENT: &str =
+    r#"{"MessageType":"Sessions","Data":[{"Id":"abc","UserName":"josh"}]}"#;
+const LIBRARY_EVENT: &str =
+    r#"{"MessageType":"LibraryChanged","Data":{"ItemsAdded":["123"],"ItemsRemoved":[]}}"#;
+
+#[test]
+fn events_streams_ndjson() {
+    let (url, server) = websocket_server(
+        3,
+        vec![
+            r#"{"MessageType":"ForceKeepAlive","Data":60}"#,
+            SESSIONS_EVENT,
+            LIBRARY_EVENT,
+        ],
+        false,
+    );
+
+    events_cmd(&url)
+        .args(["events", "--count", "2"])
+        .assert()
+        .success()
+        .stdout(format!("{SESSIONS_EVENT}\n{LIBRARY_EVENT}\n"));
+
+    let (uri, subscriptions) = server.join().unwrap();
+    assert!(uri.starts_with("/embywebsocket?api_key=test-api-key&deviceId=emby-cli-"));
+    assert_eq!(
+        subscriptions,
+        [
+            r#"{"MessageType":"SessionsStart","Data":"0,1500"}"#,
+            r#"{"MessageType":"ScheduledTasksInfoStart","Data":"0,1500"}"#,
+            r#"{"MessageType":"ActivityLogEntryStart","Data":"0,1500"}"#,
+        ]
+    );
+}
+
+#[test]
+fn events_filters_by_type() {
+    let (url, server) = websocket_server(3, vec![SESSIONS_EVENT, LIBRARY_EVENT], false);
+
+    events_cmd(&url)
+        .args(["events", "--type", "librarychanged", "-n", "1"])
+        .assert()
+        .success()
+        .stdout(format!("{LIBRARY_EVENT}\n"));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn events_connection_closed() {
+    let (url, server) = websocket_server(3, vec![SESSIONS_EVENT], true);
+
+    events_cmd(&url)
+        .arg("events")
+        .assert()
+        .code(6)
+        .stdout(format!("{SESSIONS_EVENT}\n"))
+        .stderr(contains("Could not reach the Emby server"));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn events_auth_failure() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/embywebsocket")
+        .match_query(mockito::Matcher::Any)
+        .with_status(401)
+        .create();
+
+    emby_cmd(&server)
+        .arg("events")
+        .assert()
+        .code(4)
+        .stderr(contains("Authentication failed"));
+}
+
+// --- hooks ---
+
+const NOTHING_PLAYING: &str =
+    r#"{"MessageType":"Sessions","Data":[{"Id":"abc","UserName":"josh"}]}"#;
+const PLAYING_HEAT: &str = r#"{"MessageType":"Sessions","Data":[{"Id":"abc","UserName":"josh","DeviceName":"Living Room","NowPlayingItem":{"Id":"1","Name":"Heat","Type":"Movie"}}]}"#;
+
+fn hooks_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(file, "{contents}").unwrap();
+    file
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_run_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.txt");
+    let file = hooks_file(&format!(
+        r#"
+        [[hook]]
+        event = "playback_started"
+        command = 'echo "$EMBY_EVENT $EMBY_USER $EMBY_ITEM" >> "{0}"'
+
+        [[hook]]
+        event = "playback_stopped"
+        command = 'echo "$EMBY_EVENT $EMBY_DEVICE" >> "{0}"'
+        "#,
+        out.display()
+    ));
+    let (url, server) = websocket_server(
+        1,
+        vec![NOTHING_PLAYING, PLAYING_HEAT, NOTHING_PLAYING],
+        true,
+    );
+
+    // Exits once the connection closes, since retries are off
+    events_cmd(&url)
+        .args(["hooks", "run", "--config"])
+        .arg(file.path())
+        .assert()
+        .code(6)
+        .stdout(
+            "playback_started: josh on Living Room: Heat\n\
+             playback_stopped: josh on Living Room: Heat\n",
+        );
+
+    let (_, subscriptions) = server.join().unwrap();
+    assert_eq!(
+        subscriptions,
+        [r#"{"MessageType":"SessionsStart","Data":"0,1500"}"#]
+    );
+    assert_eq!(
+        std::fs::read_to_string(out).unwrap(),
+        "playback_started josh Heat\nplayback_stopped Living Room\n"
+    );
+}
+
+#[test]
+fn hooks_post_webhooks() {
+    let mut webhook = mockito::Server::new();
+    let mock = webhook
+        .mock("POST", "/hook")
+        .match_header("Authorization", "Bearer token")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "text": "josh is watching Heat",
+            "item": {"id": "1"}
+        })))
+        .expect(1)
+        .create();
+
+    let file = hooks_file(&format!(
+        r#"
+        [[hook]]
+        event = "playback_started"
+        url = "{}/hook"
+        headers = {{ Authorization = "Bearer token" }}
+        payload = {{ text = "{{{{user}}}} is watching {{{{item}}}}", item = {{ id = "{{{{item_id}}}}" }} }}
+        "#,
+        webhook.url()
+    ));
+    let (url, server) = websocket_server(1, vec![NOTHING_PLAYING, PLAYING_HEAT], true);
+
+    events_cmd(&url)
+        .args(["hooks", "run", "--config"])
+        .arg(file.path())
+        .assert()
+        .code(6)
+        .stderr(contains("Running 1 hook, waiting for events"));
+
+    server.join().unwrap();
+    mock.assert();
+}
+
+#[test]
+fn hooks_reject_invalid_config() {
+    let file = hooks_file(
+        r#"
+        [[hook]]
+        event = "task_failed"
+        command = "echo $EMBY_NAME"
+        url = "http://example.com"
+        "#,
+    );
+
+    emby_cmd(&mockito::Server::new())
+        .args(["hooks", "run", "--config"])
+        .arg(file.path())
+        .assert()
+        .code(2)
+        .stderr(contains("Hook 1 (task_failed) has both command and url"));
+}
+
+// --- search ---
+
+#[test]
+fn search_shows_results() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("search_hints.json");
+    let _mock = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("SearchTerm".into(), "friends".into()),
+            mockito::Matcher::UrlEncoded("Recursive".into(), "true".into()),
+            mockito::Matcher::UrlEncoded("Limit".into(), "25".into()),
+        ]))
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["search", "friends"])
+        .assert()
+        .success()
+        .stdout(contains("Friends - S03E02 - The One Where No One's Ready"))
+        .stdout(contains("Queen - Bohemian Rhapsody"))
+        .stdout(contains("The Matrix"));
+}
+
+#[test]
+fn search_all_pages_through_results() {
+    let mut server = mockito::Server::new();
+    let first = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("SearchTerm".into(), "friends".into()),
+            mockito::Matcher::UrlEncoded("Limit".into(), "500".into()),
+        ]))
+        .with_body(r#"{"Items": [{"Name": "Friends", "Type": "Series", "Id": "1"}], "TotalRecordCount": 2}"#)
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create();
+    let second = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("StartIndex".into(), "1".into()),
+            mockito::Matcher::UrlEncoded("Limit".into(), "500".into()),
+        ]))
+        .with_body(r#"{"Items": [{"Name": "Friends Reunion", "Type": "Movie", "Id": "2"}], "TotalRecordCount": 2}"#)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["search", "friends", "--all", "-o", "csv"])
+        .assert()
+        .success()
+        .stdout("Type,Name,Year,ID\nSeries,Friends,,1\nMovie,Friends Reunion,,2\n");
+
+    first.assert();
+    second.assert();
+}
+
+#[test]
+fn search_with_filters() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let years = (1990..=1999).map(|y| y.to_string()).collect::<Vec<_>>();
+    let search = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("IncludeItemTypes".into(), "Movie".into()),
+            mockito::Matcher::UrlEncoded("Years".into(), years.join(",")),
+            mockito::Matcher::UrlEncoded("IsPlayed".into(), "false".into()),
+            mockito::Matcher::UrlEncoded("UserId".into(), "abc123".into()),
+            mockito::Matcher::UrlEncoded("Is4K".into(), "true".into()),
+            mockito::Matcher::UrlEncoded("Genres".into(), "Action|Crime".into()),
+            mockito::Matcher::UrlEncoded("Person".into(), "Al Pacino".into()),
+            mockito::Matcher::UrlEncoded("PersonTypes".into(), "Actor".into()),
+            mockito::Matcher::UrlEncoded("SortBy".into(), "CommunityRating,SortName".into()),
+            mockito::Matcher::UrlEncoded("SortOrder".into(), "Descending".into()),
+            mockito::Matcher::UrlEncoded(
+                "Fields".into(),
+                "ProductionYear,PremiereDate,SeriesName,CommunityRating,Genres".into(),
+            ),
+        ]))
+        .with_body(
+            r#"{"Items": [{"Name": "Heat", "Type": "Movie", "ProductionYear": 1995,
+                "CommunityRating": 8.3, "Genres": ["Action", "Crime"]}],
+                "TotalRecordCount": 1}"#,
+        )
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["search", "--type", "movie", "--year", "1990s", "--unplayed"])
+        .args(["--resolution", "4k", "-g", "Action", "-g", "Crime"])
+        .args(["--person", "Al Pacino", "--person-type", "actor"])
+        .args(["--sort", "rating", "--fields", "name,year,score,genres"])
+        .args(["-o", "csv"])
+        .assert()
+        .success()
+        .stdout("Name,Year,Score,Genres\nHeat,1995,8.3,\"Action, Crime\"\n");
+
+    search.assert();
+}
+
+#[test]
+fn search_in_library() {
+    let mut server = mockito::Server::new();
+    let _libraries = libraries_mock(&mut server);
+    let search = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("ParentId".into(), "lib-001".into()),
+            mockito::Matcher::UrlEncoded("SortBy".into(), "SortName".into()),
+            mockito::Matcher::UrlEncoded("SortOrder".into(), "Descending".into()),
+        ]))
+        .with_body(load_fixture("search_hints.json"))
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args([
+            "search",
+            "--library",
+            "movies",
+            "--sort",
+            "name",
+            "--reverse",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("The Matrix"));
+
+    search.assert();
+}
+
+#[test]
+fn search_rejects_bad_year() {
+    emby_cmd(&mockito::Server::new())
+        .args(["search", "--year", "nineties"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid year 'nineties'"));
+}
+
+#[test]
+fn pick_requires_terminal() {
+    emby_cmd(&mockito::Server::new())
+        .arg("pick")
+        .assert()
+        .code(2)
+        .stderr(contains("needs a terminal"));
+}
+
+#[test]
+fn dashboard_requires_terminal() {
+    emby_cmd(&mockito::Server::new())
+        .arg("dashboard")
+        .assert()
+        .code(2)
+        .stderr(contains("needs a terminal"));
+}
+
+#[test]
+fn search_no_results() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Items")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["search", "nonexistent"])
+        .assert()
+        .success()
+        .stdout(contains("No results found"));
+}
+
+// --- playing ---
+
+#[test]
+fn playing_shows_text_output() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("sessions.json");
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["playing", "--plain"])
+        .assert()
+        .success()
+        .stdout(contains("Friends - S03E02"))
+        .stdout(contains("Queen - Bohemian Rhapsody"))
+        .stdout(contains("Duration:"))
+        .stdout(contains("Progress:"));
+}
+
+#[test]
+fn playing_json_output() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("sessions.json");
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let output = emby_cmd(&server)
+        .args(["playing", "--json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.is_array());
+    assert_eq!(json.as_array().unwrap().len(), 2);
+
+    // Sorted by IP, so bob's transcoding session comes first
+    assert_eq!(json[0]["transcode"]["target_audio_codec"], "aac");
+    assert_eq!(json[0]["transcode"]["progress_percent"], 61.5);
+    assert!(json[1]["transcode"].is_null());
+}
+
+#[test]
+fn playing_raw_output() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("sessions.json");
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let output = emby_cmd(&server)
+        .args(["playing", "--raw"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.is_array());
+    // Raw shows only active sessions (with NowPlayingItem)
+    assert_eq!(json.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn playing_nothing_playing() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .with_body(r#"[{"UserName": "idle", "DeviceName": "Browser"}]"#)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["playing", "--plain"])
+        .assert()
+        .success()
+        .stdout(contains("Nothing playing"));
+}
+
+// --- session ---
+
+fn sessions_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/Sessions")
+        .with_body(load_fixture("sessions.json"))
+        .with_header("content-type", "application/json")
+        .create()
+}
+
+#[test]
+fn session_pause_by_user() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+    let pause = server
+        .mock("POST", "/emby/Sessions/session-001/Playing/Pause")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["session", "pause", "--user", "josh"])
+        .assert()
+        .success()
+        .stdout(contains("Paused: josh on Apple TV (session session-001)"));
+
+    pause.assert();
+}
+
+#[test]
+fn session_seek_by_device() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+    let seek = server
+        .mock("POST", "/emby/Sessions/session-002/Playing/Seek")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "SeekPositionTicks".into(),
+            "900000000".into(),
+        ))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["session", "seek", "--device", "iphone", "1:30"])
+        .assert()
+        .success()
+        .stdout(contains("Seeked to 01:30"));
+
+    seek.assert();
+}
+
+#[test]
+fn session_volume() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+    let volume = server
+        .mock("POST", "/emby/Sessions/session-001/Command")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Name": "SetVolume",
+            "Arguments": {"Volume": "40"}
+        })))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["session", "volume", "--id", "session-001", "40"])
+        .assert()
+        .success();
+
+    volume.assert();
+}
+
+#[test]
+fn session_message_to_idle_session() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+    let message = server
+        .mock("POST", "/emby/Sessions/session-003/Message")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Header": "Message",
+            "Text": "Dinner time",
+            "TimeoutMs": 5000
+        })))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["session", "message", "--user", "idle", "Dinner time"])
+        .args(["--timeout", "5"])
+        .assert()
+        .success();
+
+    message.assert();
+}
+
+#[test]
+fn session_rejects_out_of_range_values() {
+    let server = mockito::Server::new();
+
+    emby_cmd(&server)
+        .args(["session", "message", "--user", "idle", "Hi"])
+        .args(["--timeout", "18446744073709552"])
+        .assert()
+        .code(2)
+        .stderr(contains("too large"));
+
+    emby_cmd(&server)
+        .args(["session", "seek", "--user", "josh", "99999999999999"])
+        .assert()
+        .code(2)
+        .stderr(contains("too large"));
+}
+
+#[test]
+fn session_stop_all() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+    let stop1 = server
+        .mock("POST", "/emby/Sessions/session-001/Playing/Stop")
+        .with_status(204)
+        .create();
+    let stop2 = server
+        .mock("POST", "/emby/Sessions/session-002/Playing/Stop")
+        .with_status(204)
+        .create();
+
+    // The idle session isn't playing anything, so it's left alone
+    emby_cmd(&server)
+        .args(["session", "stop", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Stopped: josh"))
+        .stdout(contains("Stopped: bob"));
+
+    stop1.assert();
+    stop2.assert();
+}
+
+#[test]
+fn session_no_match() {
+    let mut server = mockito::Server::new();
+    let _sessions = sessions_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["session", "pause", "--user", "nobody"])
+        .assert()
+        .failure()
+        .stderr(contains("No matching session"));
+}
+
+// --- tasks ---
+
+#[test]
+fn tasks_shows_table() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("scheduled_tasks.json");
+    let _mock = server
+        .mock("GET", "/emby/ScheduledTasks")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("tasks")
+        .assert()
+        .success()
+        .stdout(contains("Scan Media Library"))
+        .stdout(contains("Library"))
+        .stdout(contains("Idle"))
+        .stdout(contains("Download Subtitles"));
+}
+
+#[test]
+fn tasks_hides_hidden_by_default() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("scheduled_tasks.json");
+    let _mock = server
+        .mock("GET", "/emby/ScheduledTasks")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let output = emby_cmd(&server).arg("tasks").output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Scan Media Library"));
+    assert!(!stdout.contains("Clean Cache"));
+}
+
+#[test]
+fn tasks_all_shows_hidden() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("scheduled_tasks.json");
+    let _mock = server
+        .mock("GET", "/emby/ScheduledTasks")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["tasks", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Clean Cache"));
+}
+
+#[test]
+fn tasks_shows_progress_of_running_task() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/ScheduledTasks")
+        .with_body(
+            r#"[{"Name": "Scan Media Library", "State": "Running", "Id": "task-001",
+                 "CurrentProgressPercentage": 42.4, "Category": "Library"}]"#,
+        )
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["tasks", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(contains("Category,Name,State,Progress,"))
+        .stdout(contains("Library,Scan Media Library,Running,42%,"));
+}
+
+/// Mocks running task-002 where the task finishes with `status`.
+fn task_run_mocks(server: &mut mockito::ServerGuard, status: &str) -> Vec<mockito::Mock> {
+    vec![
+        server
+            .mock("GET", "/emby/ScheduledTasks")
+            .with_body(load_fixture("scheduled_tasks.json"))
+            .with_header("content-type", "application/json")
+            .create(),
+        server
+            .mock("POST", "/emby/ScheduledTasks/Running/task-002")
+            .with_status(204)
+            .create(),
+        server
+            .mock("GET", "/emby/ScheduledTasks/task-002")
+            .with_body(format!(
+                r#"{{"Name": "Download Subtitles", "Id": "task-002", "State": "Idle",
+                    "LastExecutionResult": {{"EndTimeUtc": "2024-06-16T03:05:00.0000000Z",
+                                             "Status": "{status}"}}}}"#
+            ))
+            .with_header("content-type", "application/json")
+            .create(),
+    ]
+}
+
+#[test]
+fn tasks_run_wait_until_completed() {
+    let mut server = mockito::Server::new();
+    let _mocks = task_run_mocks(&mut server, "Completed");
+
+    emby_cmd(&server)
+        .args(["tasks", "run", "task-002", "--wait"])
+        .assert()
+        .success()
+        .stdout(contains("Task Download Subtitles started"))
+        .stdout(contains("Task Download Subtitles completed"));
+}
+
+#[test]
+fn tasks_run_wait_reports_failure() {
+    let mut server = mockito::Server::new();
+    let _mocks = task_run_mocks(&mut server, "Aborted");
+
+    emby_cmd(&server)
+        .args(["tasks", "run", "download", "--wait"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Task 'Download Subtitles' finished with status Aborted",
+        ));
+}
+
+fn tasks_list_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/ScheduledTasks")
+        .with_body(load_fixture("scheduled_tasks.json"))
+        .with_header("content-type", "application/json")
+        .create()
+}
+
+#[test]
+fn tasks_stop_by_name() {
+    let mut server = mockito::Server::new();
+    let _list = tasks_list_mock(&mut server);
+    let mock = server
+        .mock("DELETE", "/emby/ScheduledTasks/Running/task-001")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["tasks", "stop", "scan media"])
+        .assert()
+        .success()
+        .stdout(contains("Task Scan Media Library stopped"));
+
+    mock.assert();
+}
+
+#[test]
+fn tasks_unknown_name() {
+    let mut server = mockito::Server::new();
+    let _list = tasks_list_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["tasks", "run", "backup"])
+        .assert()
+        .failure()
+        .stderr(contains("No task matches 'backup'"));
+}
+
+#[test]
+fn tasks_triggers_list() {
+    let mut server = mockito::Server::new();
+    let _list = tasks_list_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["tasks", "triggers", "task-001"])
+        .assert()
+        .success()
+        .stdout(contains("every 12h"))
+        .stdout(contains("Sunday at 03:00"))
+        .stdout(contains("2h"));
+}
+
+#[test]
+fn tasks_triggers_add() {
+    let mut server = mockito::Server::new();
+    let _list = tasks_list_mock(&mut server);
+    let mock = server
+        .mock("POST", "/emby/ScheduledTasks/task-002/Triggers")
+        .match_body(mockito::Matcher::Json(serde_json::json!([
+            {"Type": "DailyTrigger", "TimeOfDayTicks": 126_000_000_000_u64}
+        ])))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args([
+            "tasks", "triggers", "download", "add", "daily", "--at", "03:30",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Added daily (at 03:30) trigger to Download Subtitles",
+        ));
+
+    mock.assert();
+}
+
+#[test]
+fn tasks_triggers_remove() {
+    let mut server = mockito::Server::new();
+    let _list = tasks_list_mock(&mut server);
+    let mock = server
+        .mock("POST", "/emby/ScheduledTasks/task-001/Triggers")
+        .match_body(mockito::Matcher::Json(serde_json::json!([
+            {"Type": "IntervalTrigger", "IntervalTicks": 432_000_000_000_u64}
+        ])))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["tasks", "triggers", "scan", "remove", "2"])
+        .assert()
+        .success()
+        .stdout(contains("Removed weekly (Sunday at 03:00) trigger #2"));
+
+    mock.assert();
+}
+
+#[test]
+fn tasks_triggers_weekly_requires_day() {
+    emby_cmd(&mockito::Server::new())
+        .args([
+            "tasks", "triggers", "scan", "add", "weekly", "--at", "03:00",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--day"));
+}
+
+// --- item ---
+
+fn item_mock(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("GET", "/emby/Users/abc123/Items/item-001")
+        .with_body(load_fixture("item.json"))
+        .with_header("content-type", "application/json")
+        .create()
+}
+
+#[test]
+fn item_shows_details() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _item = item_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["item", "item-001"])
+        .assert()
+        .success()
+        .stdout(contains("Name:      Inception\n"))
+        .stdout(contains("Genres:    Action, Science Fiction\n"))
+        .stdout(contains("Providers: Imdb tt1375666, Tmdb 27205\n"))
+        .stdout(contains("Size:      15.0 GB\n"))
+        .stdout(contains("Played:    yes, 2 plays, last Jun 20, 2024\n"))
+        .stdout(contains("Leonardo DiCaprio (Actor, as Cobb)"))
+        .stdout(contains("Sources:   Inception (mkv, 15.0 GB, 13.5 Mbps)\n"))
+        .stdout(contains("  Audio: English DTS 5.1 (Default)\n"));
+}
+
+#[test]
+fn item_json_output() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _item = item_mock(&mut server);
+
+    let output = emby_cmd(&server)
+        .args(["--output", "json", "item", "item-001"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["Name"], "Inception");
+    assert_eq!(json[0]["ProviderIds"]["Imdb"], "tt1375666");
+    assert_eq!(
+        json[0]["MediaSources"][0]["MediaStreams"][1]["Codec"],
+        "dts"
+    );
+    assert_eq!(json[0]["UserData"]["PlayCount"], 2);
+}
+
+// --- played, favorite, rate ---
+
+#[test]
+fn played_marks_item() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _item = item_mock(&mut server);
+    let played = server
+        .mock("POST", "/emby/Users/abc123/PlayedItems/item-001")
+        .with_body("{}")
+        .create();
+
+    emby_cmd(&server)
+        .args(["played", "item-001"])
+        .assert()
+        .success()
+        .stdout(contains("Marked Inception as played"));
+
+    played.assert();
+}
+
+#[test]
+fn unplayed_recursive_marks_episodes() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _series = server
+        .mock("GET", "/emby/Users/def456/Items/series-001")
+        .with_body(r#"{"Name": "Friends", "Id": "series-001", "Type": "Series", "IsFolder": true}"#)
+        .with_header("content-type", "application/json")
+        .create();
+    let _episodes = server
+        .mock("GET", "/emby/Users/def456/Items")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("ParentId".into(), "series-001".into()),
+            mockito::Matcher::UrlEncoded("Recursive".into(), "true".into()),
+            mockito::Matcher::UrlEncoded("IsFolder".into(), "false".into()),
+        ]))
+        .with_body(r#"{"Items": [{"Id": "ep-1"}, {"Id": "ep-2"}], "TotalRecordCount": 2}"#)
+        .with_header("content-type", "application/json")
+        .create();
+    let unplayed = server
+        .mock(
+            "DELETE",
+            mockito::Matcher::Regex(r"^/emby/Users/def456/PlayedItems/ep-[12]$".to_string()),
+        )
+        .with_body("{}")
+        .expect(2)
+        .create();
+
+    emby_cmd(&server)
+        .args(["unplayed", "series-001", "--recursive", "--user", "bob"])
+        .assert()
+        .success()
+        .stdout(contains("Marked 2 items in Friends as unplayed"));
+
+    unplayed.assert();
+}
+
+#[test]
+fn favorite_and_unfavorite() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _item = item_mock(&mut server);
+    let favorite = server
+        .mock("POST", "/emby/Users/abc123/FavoriteItems/item-001")
+        .with_body("{}")
+        .create();
+    let unfavorite = server
+        .mock("DELETE", "/emby/Users/abc123/FavoriteItems/item-001")
+        .with_body("{}")
+        .create();
+
+    emby_cmd(&server)
+        .args(["favorite", "item-001"])
+        .assert()
+        .success()
+        .stdout(contains("Marked Inception as a favorite"));
+    emby_cmd(&server)
+        .args(["unfavorite", "item-001"])
+        .assert()
+        .success()
+        .stdout(contains("Marked Inception as not a favorite"));
+
+    favorite.assert();
+    unfavorite.assert();
+}
+
+#[test]
+fn rate_like() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let _item = item_mock(&mut server);
+    let rating = server
+        .mock("POST", "/emby/Users/abc123/Items/item-001/Rating")
+        .match_query(mockito::Matcher::UrlEncoded("Likes".into(), "true".into()))
+        .with_body("{}")
+        .create();
+
+    emby_cmd(&server)
+        .args(["rate", "like", "item-001"])
+        .assert()
+        .success()
+        .stdout(contains("Marked Inception as liked"));
+
+    rating.assert();
+}
+
+#[test]
+fn played_requires_an_id() {
+    emby_cmd(&mockito::Server::new())
+        .arg("played")
+        .assert()
+        .failure()
+        .stderr(contains("<IDS>..."));
+}
+
+// --- latest ---
+
+#[test]
+fn latest_shows_table() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+    let latest_body = load_fixture("latest_items.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _latest_mock = server
+        .mock("GET", "/emby/Users/abc123/Items/Latest")
+        .match_query(mockito::Matcher::Any)
+        .with_body(&latest_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("latest")
+        .assert()
+        .success()
+        .stdout(contains("Inception"))
+        .stdout(contains("Friends - S05E14"))
+        .stdout(contains("Led Zeppelin - Stairway to Heaven"));
+}
+
+#[test]
+fn latest_with_user_flag() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+    let latest_body = load_fixture("latest_items.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _latest_mock = server
+        .mock("GET", "/emby/Users/def456/Items/Latest")
+        .match_query(mockito::Matcher::Any)
+        .with_body(&latest_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["latest", "--user", "bob"])
+        .assert()
+        .success()
+        .stdout(contains("Inception"));
+}
+
+#[test]
+fn latest_empty() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _latest_mock = server
+        .mock("GET", "/emby/Users/abc123/Items/Latest")
+        .match_query(mockito::Matcher::Any)
+        .with_body("[]")
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("latest")
+        .assert()
+        .success()
+        .stdout(contains("No recently added items"));
+}
+
+// --- next-up ---
+
+#[test]
+fn next_up_shows_episodes() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+    let next_up_body = load_fixture("next_up.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _next_up_mock = server
+        .mock("GET", "/emby/Shows/NextUp")
+        .match_query(mockito::Matcher::Any)
+        .with_body(&next_up_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("next-up")
+        .assert()
+        .success()
+        .stdout(contains("Friends"))
+        .stdout(contains("S04E12"))
+        .stdout(contains("Breaking Bad"))
+        .stdout(contains("S05E14"));
+}
+
+#[test]
+fn next_up_limit_zero_fetches_all() {
+    let mut server = mockito::Server::new();
+    let _users = users_mock(&mut server);
+    let next_up = server
+        .mock("GET", "/emby/Shows/NextUp")
+        .match_query(mockito::Matcher::UrlEncoded("Limit".into(), "500".into()))
+        .with_body(load_fixture("next_up.json"))
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["next-up", "--limit", "0", "-o", "ndjson"])
+        .assert()
+        .success()
+        .stdout(contains("Breaking Bad"));
+
+    next_up.assert();
+}
+
+#[test]
+fn next_up_empty() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _next_up_mock = server
+        .mock("GET", "/emby/Shows/NextUp")
+        .match_query(mockito::Matcher::Any)
+        .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("next-up")
+        .assert()
+        .success()
+        .stdout(contains("No next up episodes"));
+}
+
+// --- upcoming ---
+
+#[test]
+fn upcoming_shows_episodes() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
+    let upcoming_body = load_fixture("upcoming.json");
+
+    let _users_mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&users_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _upcoming_mock = server
+        .mock("GET", "/emby/Shows/Upcoming")
+        .match_query(mockito::Matcher::Any)
+        .with_body(&upcoming_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("upcoming")
+        .assert()
+        .success()
+        .stdout(contains("The Last of Us"))
+        .stdout(contains("S02E01"))
+        .stdout(contains("Severance"))
+        .stdout(contains("S03E01"));
+}
+
+#[test]
+fn upcoming_empty() {
+    let mut server = mockito::Server::new();
+    let users_body = load_fixture("users.json");
 
     let _users_mock = server
         .mock("GET", "/emby/Users")
@@ -399,248 +2168,647 @@ fn latest_empty() {
         .with_header("content-type", "application/json")
         .create();
 
-    let _latest_mock = server
-        .mock("GET", "/emby/Users/abc123/Items/Latest")
+    let _upcoming_mock = server
+        .mock("GET", "/emby/Shows/Upcoming")
         .match_query(mockito::Matcher::Any)
-        .with_body("[]")
+        .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .arg("upcoming")
+        .assert()
+        .success()
+        .stdout(contains("No upcoming episodes"));
+}
+
+// --- scan ---
+
+#[test]
+fn scan_all_libraries() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    // Expect POST for every library, whatever its type
+    let _scan1 = server
+        .mock("POST", "/emby/Items/lib-001/Refresh")
+        .with_status(204)
+        .create();
+    let _scan2 = server
+        .mock("POST", "/emby/Items/lib-002/Refresh")
+        .with_status(204)
+        .create();
+    let _scan3 = server
+        .mock("POST", "/emby/Items/lib-003/Refresh")
+        .with_status(204)
+        .create();
+    let _scan4 = server
+        .mock("POST", "/emby/Items/lib-004/Refresh")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .arg("scan")
+        .assert()
+        .success()
+        .stdout(contains("Scanning for movies in library ID lib-001"))
+        .stdout(contains("Scanning for tvshows in library ID lib-002"))
+        .stdout(contains("Scanning for music in library ID lib-003"))
+        .stdout(contains("Scanning for photos in library ID lib-004"));
+}
+
+#[test]
+fn scan_library_by_name() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let scan = server
+        .mock("POST", "/emby/Items/lib-002/Refresh")
+        .with_status(204)
+        .expect(1)
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "tv shows"])
+        .assert()
+        .success()
+        .stdout(contains("Scanning for tvshows in library ID lib-002"));
+
+    scan.assert();
+}
+
+#[test]
+fn scan_unknown_library() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "books"])
+        .assert()
+        .failure()
+        .stderr(contains("No library matches 'books'"));
+}
+
+#[test]
+fn scan_changed_path() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("POST", "/emby/Library/Media/Updated")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Updates": [{"Path": "/media/movies/Heat (1995)", "UpdateType": "Created"}]
+        })))
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args([
+            "scan",
+            "--path",
+            "/media/movies/Heat (1995)",
+            "--update-type",
+            "Created",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Notified Emby of created path /media/movies/Heat (1995)",
+        ));
+
+    mock.assert();
+}
+
+#[test]
+fn scan_specific_type() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _scan = server
+        .mock("POST", "/emby/Items/lib-001/Refresh")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "movies"])
+        .assert()
+        .success()
+        .stdout(contains("Scanning for movies"));
+}
+
+#[test]
+fn scan_type_alias_shows() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _scan = server
+        .mock("POST", "/emby/Items/lib-002/Refresh")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "shows"])
+        .assert()
+        .success()
+        .stdout(contains("Scanning for tvshows"));
+}
+
+#[test]
+fn scan_type_alias_tv() {
+    let mut server = mockito::Server::new();
+    let libs_body = load_fixture("libraries.json");
+
+    let _libs_mock = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&libs_body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let _scan = server
+        .mock("POST", "/emby/Items/lib-002/Refresh")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "tv"])
+        .assert()
+        .success()
+        .stdout(contains("Scanning for tvshows"));
+}
+
+fn scan_wait_mocks(server: &mut mockito::ServerGuard) -> Vec<mockito::Mock> {
+    let libs_body = load_fixture("libraries.json");
+    let refreshing = libs_body.replace(
+        r#""ItemId": "lib-001","#,
+        r#""ItemId": "lib-001", "RefreshStatus": "Active", "RefreshProgress": 40.0,"#,
+    );
+
+    // Listed once to pick the library, then refreshing, then idle again
+    vec![
+        server
+            .mock("GET", "/emby/Library/VirtualFolders")
+            .with_body(&libs_body)
+            .with_header("content-type", "application/json")
+            .expect(1)
+            .create(),
+        server
+            .mock("GET", "/emby/Library/VirtualFolders")
+            .with_body(&refreshing)
+            .with_header("content-type", "application/json")
+            .expect(1)
+            .create(),
+        server
+            .mock("GET", "/emby/Library/VirtualFolders")
+            .with_body(&libs_body)
+            .with_header("content-type", "application/json")
+            .create(),
+        server
+            .mock("POST", "/emby/Items/lib-001/Refresh")
+            .with_status(204)
+            .create(),
+    ]
+}
+
+#[test]
+fn scan_wait_until_completed() {
+    let mut server = mockito::Server::new();
+    let _mocks = scan_wait_mocks(&mut server);
+
+    emby_cmd(&server)
+        .args(["scan", "movies", "--wait"])
+        .assert()
+        .success()
+        .stdout(contains("Library scan completed"));
+}
+
+#[test]
+fn scan_wait_times_out() {
+    let mut server = mockito::Server::new();
+    let refreshing = load_fixture("libraries.json").replace(
+        r#""ItemId": "lib-001","#,
+        r#""ItemId": "lib-001", "RefreshStatus": "Active","#,
+    );
+
+    let _libs = server
+        .mock("GET", "/emby/Library/VirtualFolders")
+        .with_body(&refreshing)
         .with_header("content-type", "application/json")
         .create();
+    let _refresh = server
+        .mock("POST", "/emby/Items/lib-001/Refresh")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "movies", "--wait", "--timeout", "1"])
+        .assert()
+        .failure()
+        .stderr(contains("Timed out"));
+}
+
+fn scan_all_wait_mocks(server: &mut mockito::ServerGuard) -> Vec<mockito::Mock> {
+    let libs_body = load_fixture("libraries.json");
+    let tasks_body = load_fixture("scheduled_tasks.json");
+
+    vec![
+        server
+            .mock("GET", "/emby/Library/VirtualFolders")
+            .with_body(&libs_body)
+            .with_header("content-type", "application/json")
+            .create(),
+        server
+            .mock("GET", "/emby/ScheduledTasks")
+            .with_body(&tasks_body)
+            .with_header("content-type", "application/json")
+            .create(),
+    ]
+}
+
+#[test]
+fn scan_all_wait_runs_scan_task() {
+    let mut server = mockito::Server::new();
+    let _mocks = scan_all_wait_mocks(&mut server);
+
+    let run = server
+        .mock("POST", "/emby/ScheduledTasks/Running/task-001")
+        .with_status(204)
+        .create();
+    let refresh = server
+        .mock(
+            "POST",
+            mockito::Matcher::Regex(r"^/emby/Items/.*/Refresh$".to_string()),
+        )
+        .expect(0)
+        .create();
+    let _task = server
+        .mock("GET", "/emby/ScheduledTasks/task-001")
+        .with_body(
+            r#"{"Name": "Scan Media Library", "Id": "task-001", "State": "Idle",
+                "LastExecutionResult": {"EndTimeUtc": "2024-06-16T02:15:00.0000000Z",
+                                        "Status": "Completed"}}"#,
+        )
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "--wait"])
+        .assert()
+        .success()
+        .stdout(contains("Library scan completed"));
+
+    run.assert();
+    refresh.assert();
+}
+
+#[test]
+fn scan_all_wait_reports_failure() {
+    let mut server = mockito::Server::new();
+    let _mocks = scan_all_wait_mocks(&mut server);
+
+    let _run = server
+        .mock("POST", "/emby/ScheduledTasks/Running/task-001")
+        .with_status(204)
+        .create();
+    let _task = server
+        .mock("GET", "/emby/ScheduledTasks/task-001")
+        .with_body(
+            r#"{"Name": "Scan Media Library", "Id": "task-001", "State": "Idle",
+                "LastExecutionResult": {"EndTimeUtc": "2024-06-16T02:15:00.0000000Z",
+                                        "Status": "Failed"}}"#,
+        )
+        .with_header("content-type", "application/json")
+        .create();
+
+    emby_cmd(&server)
+        .args(["scan", "all", "--wait"])
+        .assert()
+        .failure()
+        .stderr(contains("finished with status Failed"));
+}
+
+// --- restart ---
+
+#[test]
+fn restart_sends_post() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("POST", "/emby/System/Restart")
+        .with_status(204)
+        .create();
+
+    emby_cmd(&server).arg("restart").assert().success();
+}
+
+// --- output formats ---
+
+#[test]
+fn users_json_output() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("users.json");
+    let _mock = server
+        .mock("GET", "/emby/Users")
+        .with_body(&body)
+        .with_header("content-type", "application/json")
+        .create();
+
+    let output = emby_cmd(&server)
+        .args(["users", "--output", "json"])
+        .output()
+        .unwrap();
 
-    emby_cmd(&server)
-        .arg("latest")
-        .assert()
-        .success()
-        .stdout(contains("No recently added items"));
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["name"], "josh");
+    assert_eq!(json[0]["id"], "abc123");
+    assert_eq!(json[1]["admin"], "false");
 }
 
-// --- next-up ---
-
 #[test]
-fn next_up_shows_episodes() {
+fn devices_csv_output() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-    let next_up_body = load_fixture("next_up.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
+    let body = load_fixture("devices.json");
+    let _mock = server
+        .mock("GET", "/emby/Devices")
+        .with_body(&body)
         .with_header("content-type", "application/json")
         .create();
 
-    let _next_up_mock = server
-        .mock("GET", "/emby/Shows/NextUp")
+    let output = emby_cmd(&server)
+        .args(["--output", "csv", "devices"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("Name,IP Address,Last User,App (Version),Last Active,ID")
+    );
+    assert!(lines.next().unwrap().starts_with("Apple TV,192.168.1.100,"));
+}
+
+#[test]
+fn search_tsv_output() {
+    let mut server = mockito::Server::new();
+    let body = load_fixture("search_hints.json");
+    let _mock = server
+        .mock("GET", "/emby/Items")
         .match_query(mockito::Matcher::Any)
-        .with_body(&next_up_body)
+        .with_body(&body)
         .with_header("content-type", "application/json")
         .create();
 
     emby_cmd(&server)
-        .arg("next-up")
+        .args(["search", "friends", "-o", "tsv"])
         .assert()
         .success()
-        .stdout(contains("Friends"))
-        .stdout(contains("S04E12"))
-        .stdout(contains("Breaking Bad"))
-        .stdout(contains("S05E14"));
+        .stdout(contains("Type\tName\tYear\tID"))
+        .stdout(contains("Movie\tThe Matrix\t1999"));
 }
 
 #[test]
-fn next_up_empty() {
+fn empty_list_json_output() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
-        .with_header("content-type", "application/json")
-        .create();
-
-    let _next_up_mock = server
-        .mock("GET", "/emby/Shows/NextUp")
+    let _mock = server
+        .mock("GET", "/emby/Items")
         .match_query(mockito::Matcher::Any)
         .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
         .with_header("content-type", "application/json")
         .create();
 
     emby_cmd(&server)
-        .arg("next-up")
+        .args(["search", "nonexistent", "--output", "json"])
         .assert()
         .success()
-        .stdout(contains("No next up episodes"));
+        .stdout("[]\n");
 }
 
-// --- upcoming ---
-
 #[test]
-fn upcoming_shows_episodes() {
+fn playing_ndjson_output() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-    let upcoming_body = load_fixture("upcoming.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
+    let body = load_fixture("sessions.json");
+    let _mock = server
+        .mock("GET", "/emby/Sessions")
+        .with_body(&body)
         .with_header("content-type", "application/json")
         .create();
 
-    let _upcoming_mock = server
-        .mock("GET", "/emby/Shows/Upcoming")
-        .match_query(mockito::Matcher::Any)
-        .with_body(&upcoming_body)
-        .with_header("content-type", "application/json")
-        .create();
+    let output = emby_cmd(&server)
+        .args(["playing", "--output", "ndjson"])
+        .output()
+        .unwrap();
 
-    emby_cmd(&server)
-        .arg("upcoming")
-        .assert()
-        .success()
-        .stdout(contains("The Last of Us"))
-        .stdout(contains("S02E01"))
-        .stdout(contains("Severance"))
-        .stdout(contains("S03E01"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0]["progress_percent"].is_number());
 }
 
 #[test]
-fn upcoming_empty() {
+fn system_json_output() {
     let mut server = mockito::Server::new();
-    let users_body = load_fixture("users.json");
-
-    let _users_mock = server
-        .mock("GET", "/emby/Users")
-        .with_body(&users_body)
+    let body = load_fixture("system_info.json");
+    let _mock = server
+        .mock("GET", "/emby/System/Info")
+        .with_body(&body)
         .with_header("content-type", "application/json")
         .create();
 
-    let _upcoming_mock = server
-        .mock("GET", "/emby/Shows/Upcoming")
-        .match_query(mockito::Matcher::Any)
-        .with_body(r#"{"Items": [], "TotalRecordCount": 0}"#)
-        .with_header("content-type", "application/json")
-        .create();
+    let output = emby_cmd(&server)
+        .args(["system", "--output", "json"])
+        .output()
+        .unwrap();
 
-    emby_cmd(&server)
-        .arg("upcoming")
-        .assert()
-        .success()
-        .stdout(contains("No upcoming episodes"));
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["version"], "4.8.0.0");
+    assert_eq!(json[0]["update_available"], false);
 }
 
-// --- scan ---
+// --- login / logout ---
 
 #[test]
-fn scan_all_libraries() {
+fn login_saves_access_token() {
     let mut server = mockito::Server::new();
-    let libs_body = load_fixture("libraries.json");
-
-    let _libs_mock = server
-        .mock("GET", "/emby/Library/VirtualFolders")
-        .with_body(&libs_body)
+    let _mock = server
+        .mock("POST", "/emby/Users/AuthenticateByName")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "Username": "bob",
+            "Pw": "hunter2"
+        })))
+        .with_body(r#"{"User": {"Name": "bob", "Id": "def456"}, "AccessToken": "token-123"}"#)
         .with_header("content-type", "application/json")
         .create();
 
-    // Expect POST for each supported library (movies, tvshows, music — not photos)
-    let _scan1 = server
-        .mock("POST", "/emby/Items/lib-001/Refresh")
-        .with_status(204)
-        .create();
-    let _scan2 = server
-        .mock("POST", "/emby/Items/lib-002/Refresh")
-        .with_status(204)
-        .create();
-    let _scan3 = server
-        .mock("POST", "/emby/Items/lib-003/Refresh")
-        .with_status(204)
-        .create();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("emby-api.json");
 
-    emby_cmd(&server)
-        .arg("scan")
+    cargo_bin_cmd!("emby")
+        .args(["login", "--server", &server.url(), "--username", "bob"])
+        .arg("--password-stdin")
+        .env("EMBY_CONFIG", &config_path)
+        .env_remove("EMBY_PROFILE")
+        .write_stdin("hunter2\n")
         .assert()
         .success()
-        .stdout(contains("Scanning for movies in library ID lib-001"))
-        .stdout(contains("Scanning for tvshows in library ID lib-002"))
-        .stdout(contains("Scanning for music in library ID lib-003"));
+        .stdout(contains("Logged in as bob"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(json["api_url"], server.url());
+    assert_eq!(json["access_token"], "token-123");
+    assert_eq!(json["user_id"], "def456");
 }
 
 #[test]
-fn scan_specific_type() {
+fn login_creates_profile() {
     let mut server = mockito::Server::new();
-    let libs_body = load_fixture("libraries.json");
-
-    let _libs_mock = server
-        .mock("GET", "/emby/Library/VirtualFolders")
-        .with_body(&libs_body)
+    let _mock = server
+        .mock("POST", "/emby/Users/AuthenticateByName")
+        .with_body(r#"{"User": {"Name": "bob", "Id": "def456"}, "AccessToken": "token-123"}"#)
         .with_header("content-type", "application/json")
         .create();
 
-    let _scan = server
-        .mock("POST", "/emby/Items/lib-001/Refresh")
-        .with_status(204)
-        .create();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("emby-api.json");
 
-    emby_cmd(&server)
-        .args(["scan", "movies"])
+    cargo_bin_cmd!("emby")
+        .args(["--profile", "family", "login", "--server", &server.url()])
+        .args(["--username", "bob", "--password-stdin"])
+        .env("EMBY_CONFIG", &config_path)
+        .write_stdin("hunter2\n")
         .assert()
-        .success()
-        .stdout(contains("Scanning for movies"));
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(json["profiles"]["family"]["access_token"], "token-123");
 }
 
 #[test]
-fn scan_type_alias_shows() {
+fn login_rejected_credentials() {
     let mut server = mockito::Server::new();
-    let libs_body = load_fixture("libraries.json");
-
-    let _libs_mock = server
-        .mock("GET", "/emby/Library/VirtualFolders")
-        .with_body(&libs_body)
-        .with_header("content-type", "application/json")
+    let _mock = server
+        .mock("POST", "/emby/Users/AuthenticateByName")
+        .with_status(401)
         .create();
 
-    let _scan = server
-        .mock("POST", "/emby/Items/lib-002/Refresh")
-        .with_status(204)
-        .create();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("emby-api.json");
 
-    emby_cmd(&server)
-        .args(["scan", "shows"])
+    cargo_bin_cmd!("emby")
+        .args(["login", "--server", &server.url(), "--username", "bob"])
+        .arg("--password-stdin")
+        .env("EMBY_CONFIG", &config_path)
+        .env_remove("EMBY_PROFILE")
+        .write_stdin("wrong\n")
         .assert()
-        .success()
-        .stdout(contains("Scanning for tvshows"));
+        .code(4)
+        .stderr(contains("Invalid username or password"));
+
+    assert!(!config_path.exists());
 }
 
 #[test]
-fn scan_type_alias_tv() {
+fn access_token_used_for_requests() {
     let mut server = mockito::Server::new();
-    let libs_body = load_fixture("libraries.json");
+    let latest_body = load_fixture("latest_items.json");
 
-    let _libs_mock = server
-        .mock("GET", "/emby/Library/VirtualFolders")
-        .with_body(&libs_body)
+    // The logged-in user is used without looking up /Users
+    let _latest_mock = server
+        .mock("GET", "/emby/Users/def456/Items/Latest")
+        .match_header("X-Emby-Token", "token-123")
+        .match_query(mockito::Matcher::Any)
+        .with_body(&latest_body)
         .with_header("content-type", "application/json")
         .create();
 
-    let _scan = server
-        .mock("POST", "/emby/Items/lib-002/Refresh")
-        .with_status(204)
-        .create();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        file,
+        r#"{{"api_url": "{}", "access_token": "token-123", "user_id": "def456"}}"#,
+        server.url()
+    )
+    .unwrap();
 
-    emby_cmd(&server)
-        .args(["scan", "tv"])
+    cargo_bin_cmd!("emby")
+        .arg("latest")
+        .env("EMBY_CONFIG", file.path())
+        .env_remove("EMBY_PROFILE")
+        .env_remove("EMBY_API_KEY")
+        .env_remove("EMBY_API_URL")
         .assert()
         .success()
-        .stdout(contains("Scanning for tvshows"));
+        .stdout(contains("Inception"));
 }
 
-// --- restart ---
-
 #[test]
-fn restart_sends_post() {
+fn logout_revokes_token() {
     let mut server = mockito::Server::new();
-    let _mock = server
-        .mock("POST", "/emby/System/Restart")
+    let logout_mock = server
+        .mock("POST", "/emby/Sessions/Logout")
+        .match_header("X-Emby-Token", "token-123")
         .with_status(204)
         .create();
 
-    emby_cmd(&server).arg("restart").assert().success();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        file,
+        r#"{{"api_url": "{}", "access_token": "token-123", "user_id": "def456"}}"#,
+        server.url()
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("emby")
+        .arg("logout")
+        .env("EMBY_CONFIG", file.path())
+        .env_remove("EMBY_PROFILE")
+        .env_remove("EMBY_API_KEY")
+        .env_remove("EMBY_API_URL")
+        .assert()
+        .success()
+        .stdout(contains("Logged out"));
+
+    logout_mock.assert();
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(file.path()).unwrap()).unwrap();
+    assert!(json.get("access_token").is_none());
+    assert!(json.get("user_id").is_none());
+    assert_eq!(json["api_url"], server.url());
 }
 
 // --- Error cases ---
@@ -657,8 +2825,9 @@ fn error_on_401() {
     emby_cmd(&server)
         .arg("system")
         .assert()
-        .failure()
-        .stderr(contains("Error"));
+        .code(4)
+        .stderr(contains("HTTP 401"))
+        .stderr(contains("emby login"));
 }
 
 #[test]
@@ -673,8 +2842,115 @@ fn error_on_500() {
     emby_cmd(&server)
         .arg("users")
         .assert()
+        .code(7)
+        .stderr(contains("Emby server error (HTTP 500)"));
+}
+
+#[test]
+fn error_on_404() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/emby/Users/abc123/Items/missing")
+        .with_status(404)
+        .create();
+    let _users = users_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["item", "missing"])
+        .assert()
+        .code(5)
+        .stderr(contains("HTTP 404"));
+}
+
+#[test]
+fn error_when_server_unreachable() {
+    cargo_bin_cmd!("emby")
+        .arg("system")
+        .env("EMBY_API_URL", "http://127.0.0.1:1")
+        .env("EMBY_API_KEY", "test-key")
+        .env("EMBY_RETRIES", "0")
+        .env_remove("EMBY_CONFIG")
+        .env_remove("EMBY_PROFILE")
+        .assert()
+        .code(6)
+        .stderr(contains("Could not reach the Emby server"));
+}
+
+#[test]
+fn retries_get_after_server_error() {
+    let mut server = mockito::Server::new();
+    let unavailable = server
+        .mock("GET", "/emby/Users")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let _users = users_mock(&mut server);
+
+    emby_cmd(&server)
+        .args(["--retries", "1", "users"])
+        .assert()
+        .success()
+        .stdout(contains("josh"));
+
+    unavailable.assert();
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let mut server = mockito::Server::new();
+    let unavailable = server
+        .mock("GET", "/emby/Users")
+        .with_status(503)
+        .expect(2)
+        .create();
+
+    emby_cmd(&server)
+        .args(["users"])
+        .env("EMBY_RETRIES", "1")
+        .assert()
+        .failure()
+        .stderr(contains("503"));
+
+    unavailable.assert();
+}
+
+#[test]
+fn does_not_retry_post() {
+    let mut server = mockito::Server::new();
+    let restart = server
+        .mock("POST", "/emby/System/Restart")
+        .with_status(503)
+        .expect(1)
+        .create();
+
+    emby_cmd(&server)
+        .args(["--retries", "2", "restart"])
+        .assert()
+        .failure();
+
+    restart.assert();
+}
+
+#[test]
+fn invalid_retries_in_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("emby-api.json");
+    std::fs::write(
+        &config,
+        r#"{"api_url": "http://localhost:1", "api_key": "k", "retries": "lots"}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("emby")
+        .env("EMBY_CONFIG", &config)
+        .env_remove("EMBY_API_URL")
+        .env_remove("EMBY_API_KEY")
+        .env_remove("EMBY_PROFILE")
+        .arg("system")
+        .assert()
         .failure()
-        .stderr(contains("Error"));
+        .stderr(contains("'retries' in"))
+        .stderr(contains("must be a whole number"));
 }
 
 // NOTE: find-server uses UDP broadcast, not HTTP. Skipping — would require
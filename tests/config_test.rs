@@ -11,7 +11,7 @@ fn missing_config_shows_error() {
         .env_remove("EMBY_API_KEY")
         .env_remove("EMBY_API_URL")
         .assert()
-        .failure()
+        .code(3)
         .stderr(contains("doesn't exist"));
 }
 
@@ -61,3 +61,90 @@ fn no_args_shows_help() {
         .failure()
         .stderr(contains("Usage"));
 }
+
+fn profiles_config(server: &mockito::ServerGuard) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"{{
+            "default": "prod",
+            "profiles": {{
+                "prod": {{"api_url": "{url}", "api_key": "prod-key"}},
+                "staging": {{"api_url": "{url}", "api_key": "staging-key"}}
+            }}
+        }}"#,
+        url = server.url()
+    )
+    .unwrap();
+    file
+}
+
+#[test]
+fn profile_flag_selects_profile() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("POST", "/emby/System/Restart")
+        .match_header("X-Emby-Token", "staging-key")
+        .with_status(204)
+        .create();
+    let file = profiles_config(&server);
+
+    cargo_bin_cmd!("emby")
+        .args(["--profile", "staging", "restart"])
+        .env("EMBY_CONFIG", file.path().to_str().unwrap())
+        .env_remove("EMBY_PROFILE")
+        .assert()
+        .success();
+}
+
+#[test]
+fn profile_env_selects_profile() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("POST", "/emby/System/Restart")
+        .match_header("X-Emby-Token", "staging-key")
+        .with_status(204)
+        .create();
+    let file = profiles_config(&server);
+
+    cargo_bin_cmd!("emby")
+        .arg("restart")
+        .env("EMBY_CONFIG", file.path().to_str().unwrap())
+        .env("EMBY_PROFILE", "staging")
+        .assert()
+        .success();
+}
+
+#[test]
+fn profile_defaults_to_default_profile() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("POST", "/emby/System/Restart")
+        .match_header("X-Emby-Token", "prod-key")
+        .with_status(204)
+        .create();
+    let file = profiles_config(&server);
+
+    cargo_bin_cmd!("emby")
+        .arg("restart")
+        .env("EMBY_CONFIG", file.path().to_str().unwrap())
+        .env_remove("EMBY_PROFILE")
+        .env_remove("EMBY_API_KEY")
+        .env_remove("EMBY_API_URL")
+        .assert()
+        .success();
+}
+
+#[test]
+fn unknown_profile_shows_error() {
+    let server = mockito::Server::new();
+    let file = profiles_config(&server);
+
+    cargo_bin_cmd!("emby")
+        .args(["--profile", "family", "system"])
+        .env("EMBY_CONFIG", file.path().to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(contains("Profile 'family' not found"))
+        .stderr(contains("prod, staging"));
+}
@@ -75,6 +75,22 @@ fn playing_calculates_progress() {
     assert_eq!(episode.remaining, "12:00");
 }
 
+#[test]
+fn playing_includes_transcoding_info() {
+    let sessions = load_sessions();
+    let entries = emby_cli::format::playing::build_entries(&sessions);
+
+    let episode = entries.iter().find(|e| e.media_type == "Episode").unwrap();
+    assert!(episode.transcode.is_none());
+
+    let audio = entries.iter().find(|e| e.media_type == "Audio").unwrap();
+    let transcode = audio.transcode.as_ref().unwrap();
+    assert_eq!(transcode.source_audio_codec.as_deref(), Some("flac"));
+    assert_eq!(transcode.target_audio_codec.as_deref(), Some("aac"));
+    assert_eq!(transcode.bitrate, Some(320_000));
+    assert_eq!(transcode.reasons, vec!["AudioCodecNotSupported"]);
+}
+
 #[test]
 fn playing_infuse_client_normalized() {
     let sessions = load_sessions();